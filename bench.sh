@@ -4,4 +4,4 @@
 # cargo bench doesn't parse arguments to target function properly when using `--`.
 export TARGET_ARGS="$*"
 
-cargo bench --all-features --bench benchmark
+cargo bench --features tlcr --bench benchmark
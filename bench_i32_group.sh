@@ -1,3 +1,3 @@
 #!/bin/bash
 
-cargo bench --all-features --bench i32_group_benchmark
+cargo bench --features tlcr --bench i32_group_benchmark
@@ -50,7 +50,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     // force early validation of all target names
     let targets: Vec<fn()> = names.iter().map(|name| target(name)).collect();
 
-    for (name, target) in names.into_iter().zip(targets.into_iter()) {
+    for (name, target) in names.into_iter().zip(targets) {
         c.bench_function(name, |b| b.iter(target));
     }
 }
@@ -0,0 +1,55 @@
+//! Benchmark of the cost of a single contribution with `with_data_mut`, on the current thread, compared to that of
+//! updating a plain `thread_local!` `Cell`, which is the floor for any thread-local collection.
+//!
+//! The `default` targets use control objects without optional subsystems, the `extras` targets use control objects
+//! with activity tracking and thread-local dependencies. To compare against an earlier revision of the crate, run
+//! `cargo bench --bench contribution_benchmark -- --save-baseline <name>` on that revision, then
+//! `cargo bench --bench contribution_benchmark -- --baseline <name>` on this one.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::{cell::Cell, thread::ThreadId};
+use thread_local_collect::tlm::{joined, probed};
+
+fn op(data: i32, acc: &mut i32, _: ThreadId) {
+    *acc += data;
+}
+
+thread_local! {
+    static RAW_TL: Cell<i32> = const { Cell::new(0) };
+    static JOINED_TL: joined::Holder<i32, i32> = const { joined::Holder::new() };
+    static JOINED_EXTRAS_TL: joined::Holder<i32, i32> = const { joined::Holder::new() };
+    static PROBED_TL: probed::Holder<i32, i32> = probed::Holder::new();
+    static PROBED_EXTRAS_TL: probed::Holder<i32, i32> = probed::Holder::new();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let joined_default = joined::Control::new(&JOINED_TL, 0, || 0, op);
+    let joined_extras = joined::Control::new(&JOINED_EXTRAS_TL, 0, || 0, op)
+        .with_activity_tracking()
+        .with_tl_dependencies(|| RAW_TL.with(|_| ()));
+    let probed_default = probed::Control::new(&PROBED_TL, 0, || 0, op);
+    let probed_extras = probed::Control::new(&PROBED_EXTRAS_TL, 0, || 0, op)
+        .with_activity_tracking()
+        .with_tl_dependencies(|| RAW_TL.with(|_| ()));
+
+    let mut group = c.benchmark_group("contribution");
+    group.bench_function("raw_thread_local", |b| {
+        b.iter(|| RAW_TL.with(|data| data.set(data.get() + black_box(1))))
+    });
+    group.bench_function("tlm_joined_default", |b| {
+        b.iter(|| joined_default.with_data_mut(|data| *data += black_box(1)))
+    });
+    group.bench_function("tlm_joined_extras", |b| {
+        b.iter(|| joined_extras.with_data_mut(|data| *data += black_box(1)))
+    });
+    group.bench_function("tlm_probed_default", |b| {
+        b.iter(|| probed_default.with_data_mut(|data| *data += black_box(1)))
+    });
+    group.bench_function("tlm_probed_extras", |b| {
+        b.iter(|| probed_extras.with_data_mut(|data| *data += black_box(1)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -9,7 +9,7 @@ mod map_bench {
     use super::*;
 
     thread_local! {
-        static MY_TL: Holder<Data> = Holder::new();
+        static MY_TL: Holder<Data> = const { Holder::new() };
     }
 
     impl BenchTarget<Data, AccValue> for Control<Data, AccValue> {
@@ -36,7 +36,7 @@ mod i32_bench {
     use super::*;
 
     thread_local! {
-        static MY_TL: Holder<Data> = Holder::new();
+        static MY_TL: Holder<Data> = const { Holder::new() };
     }
 
     impl BenchTarget<Data, AccValue> for Control<Data, AccValue> {
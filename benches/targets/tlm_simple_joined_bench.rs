@@ -9,7 +9,7 @@ mod map_bench {
     use super::*;
 
     thread_local! {
-        static MY_TL: Holder<Data, AccValue> = Holder::new();
+        static MY_TL: Holder<Data, AccValue> = const { Holder::new() };
     }
 
     fn insert_tl_entry(k: i32, v: Foo, control: &Control<Data, AccValue>) {
@@ -41,7 +41,7 @@ mod i32_bench {
     use super::*;
 
     thread_local! {
-        static MY_TL: Holder<Data, AccValue> = Holder::new();
+        static MY_TL: Holder<Data, AccValue> = const { Holder::new() };
     }
 
     fn update_tl(value: Data, control: &Control<Data, AccValue>) {
@@ -9,7 +9,7 @@ mod map_bench {
     use super::*;
 
     thread_local! {
-        static MY_TL: Holder<AccValue> = Holder::new();
+        static MY_TL: Holder<AccValue> = const { Holder::new() };
     }
 
     impl BenchTarget<Data, AccValue> for Control<AccValue> {
@@ -35,7 +35,7 @@ mod i32_bench {
     use super::*;
 
     thread_local! {
-        static MY_TL: Holder<AccValue> = Holder::new();
+        static MY_TL: Holder<AccValue> = const { Holder::new() };
     }
 
     impl BenchTarget<Data, AccValue> for Control<AccValue> {
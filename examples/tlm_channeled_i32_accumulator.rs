@@ -15,7 +15,7 @@ type AccValue = i32;
 
 // Define your thread-local:
 thread_local! {
-    static MY_TL: Holder<Data> = Holder::new();
+    static MY_TL: Holder<Data> = const { Holder::new() };
 }
 
 // Define your accumulation operation.
@@ -21,7 +21,7 @@ type Data = (i32, Foo);
 type AccValue = HashMap<ThreadId, HashMap<i32, Foo>>;
 
 thread_local! {
-    static MY_TL: Holder<Data> = Holder::new();
+    static MY_TL: Holder<Data> = const { Holder::new() };
 }
 
 fn op(data: Data, acc: &mut AccValue, tid: ThreadId) {
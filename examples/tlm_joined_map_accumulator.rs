@@ -18,7 +18,7 @@ type Data = HashMap<i32, Foo>;
 type AccValue = HashMap<ThreadId, HashMap<i32, Foo>>;
 
 thread_local! {
-    static MY_TL: Holder<Data, AccValue> = Holder::new();
+    static MY_TL: Holder<Data, AccValue> = const { Holder::new() };
 }
 
 fn insert_tl_entry(k: i32, v: Foo, control: &Control<Data, AccValue>) {
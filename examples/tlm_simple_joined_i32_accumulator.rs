@@ -14,7 +14,7 @@ type AccValue = i32;
 
 // Define your thread-local:
 thread_local! {
-    static MY_TL: Holder<Data, AccValue> = Holder::new();
+    static MY_TL: Holder<Data, AccValue> = const { Holder::new() };
 }
 
 // Define your accumulation operation.
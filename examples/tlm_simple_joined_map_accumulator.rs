@@ -22,7 +22,7 @@ type Data = HashMap<i32, Foo>;
 type AccumulatorMap = HashMap<ThreadId, HashMap<i32, Foo>>;
 
 thread_local! {
-    static MY_TL: Holder<Data, AccumulatorMap> = Holder::new();
+    static MY_TL: Holder<Data, AccumulatorMap> = const { Holder::new() };
 }
 
 fn insert_tl_entry(k: i32, v: Foo, control: &Control<Data, AccumulatorMap>) {
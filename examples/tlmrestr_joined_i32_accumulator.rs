@@ -25,7 +25,7 @@ fn op_r(acc1: AccValue, acc2: AccValue) -> AccValue {
 }
 
 thread_local! {
-    static MY_TL: Holder<AccValue> = Holder::new();
+    static MY_TL: Holder<AccValue> = const { Holder::new() };
 }
 
 const NTHREADS: i32 = 5;
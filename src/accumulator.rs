@@ -0,0 +1,539 @@
+//! Trait that defines an accumulation in a single place, as an alternative to passing closures to `Control`
+//! constructors.
+//!
+//! A type that implements [`Accumulator`] defines the zero value of the accumulation, how data contributed from
+//! a thread is absorbed into an accumulated value, and how two accumulated values are merged. Every `Control`
+//! type in this library has a `from_accumulator` constructor that derives the closures required by its `new`
+//! constructor from such an implementation:
+//! - The [`crate::tlm`] direct sub-modules use [`Accumulator::zero`] for the initial accumulated value and
+//!   [`Accumulator::absorb`] to combine thread-local data with the accumulated value.
+//! - The [`crate::tlm::restr`] and `tlcr` sub-modules use [`Accumulator::zero`] for the zero value of
+//!   thread-local accumulations and [`Accumulator::merge`] to reduce them; their `absorb_data` method uses
+//!   [`Accumulator::absorb`] to aggregate data on the calling thread.
+//!
+//! [`Keyed`] lifts an [`Accumulator`] to one that maintains a separate accumulated value per key, for the common
+//! case of aggregation per label, endpoint, tenant, etc.
+//!
+//! [`Summary`] computes the count, sum, minimum, maximum, and mean of numeric values, e.g., timings or sizes. Wrapped
+//! in [`Keyed`] with [`ThreadId`] keys, it computes these statistics per thread.
+//!
+//! [`PerThread`] keeps the latest value contributed by each thread, keyed by [`ThreadId`], which is the accumulation
+//! used throughout the examples. For `Control` types that take the accumulation as closures, [`ops`] produces the
+//! `zero`, `op`, and `op_r` functions of any [`Accumulator`], and [`per_thread`] those of [`PerThread`].
+//!
+//! [`Tabular`] renders map accumulators as aligned `key  value` rows, which the `format_acc` methods of the
+//! `Control` types use for quick debugging dumps, e.g., at shutdown. [`PerThread`] and [`Keyed`] also implement
+//! [`Display`] in this form when their values do, and [`Summary`] implements [`Display`] as a single line.
+//!
+//! [`Recycle`] resets an accumulated value to its zero while retaining its allocations, so that the accumulated
+//! values taken from the `tlm` `Control` types can be returned to them with `recycle_acc` and reused by
+//! `take_acc_recycled`, instead of allocating a new zero each time, e.g., for map accumulators taken at high
+//! frequency.
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::{Debug, Display},
+    hash::{BuildHasher, Hash},
+    mem::{replace, swap},
+    ops::{AddAssign, Deref},
+    thread::ThreadId,
+    time::Duration,
+};
+
+/// Defines the zero value, absorption of data, and merging of accumulated values for an accumulation.
+///
+/// `merge` is expected to be associative and `zero` to be its identity, so that results are independent of
+/// the order in which thread-local values are reduced.
+pub trait Accumulator: Sized {
+    /// Type of data absorbed into the accumulated value.
+    type Dat;
+
+    /// Returns the zero value of the accumulation.
+    fn zero() -> Self;
+
+    /// Absorbs `data` contributed from thread `tid` into `self`.
+    fn absorb(&mut self, data: Self::Dat, tid: ThreadId);
+
+    /// Merges two accumulated values into one.
+    fn merge(self, other: Self) -> Self;
+}
+
+/// [`Accumulator`] that maintains a separate accumulated value of type `A` for each key of type `K`. It absorbs
+/// `(key, data)` pairs by absorbing `data` into the accumulated value for `key`, and merges by merging the
+/// accumulated values key by key.
+///
+/// Instances dereference to the underlying [`HashMap`].
+#[derive(Debug, Clone)]
+pub struct Keyed<K, A>(HashMap<K, A>);
+
+impl<K, A> Keyed<K, A> {
+    /// Returns the underlying map from keys to accumulated values.
+    pub fn into_inner(self) -> HashMap<K, A> {
+        self.0
+    }
+}
+
+impl<K, A> PartialEq for Keyed<K, A>
+where
+    K: Eq + Hash,
+    A: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K, A> Default for Keyed<K, A> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<K, A> Deref for Keyed<K, A> {
+    type Target = HashMap<K, A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K, A> From<HashMap<K, A>> for Keyed<K, A> {
+    fn from(map: HashMap<K, A>) -> Self {
+        Self(map)
+    }
+}
+
+impl<K, A> Accumulator for Keyed<K, A>
+where
+    K: Eq + Hash,
+    A: Accumulator,
+{
+    type Dat = (K, A::Dat);
+
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn absorb(&mut self, (key, data): Self::Dat, tid: ThreadId) {
+        self.0.entry(key).or_insert_with(A::zero).absorb(data, tid);
+    }
+
+    fn merge(mut self, mut other: Self) -> Self {
+        // Merge the smaller map into the larger one.
+        if self.0.len() < other.0.len() {
+            swap(&mut self, &mut other);
+        }
+        for (key, acc) in other.0 {
+            match self.0.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let merged = entry.get_mut();
+                    let prev = replace(merged, A::zero());
+                    *merged = prev.merge(acc);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(acc);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// [`Accumulator`] that keeps the latest value of type `V` contributed by each thread, keyed by the thread's
+/// [`ThreadId`]. It absorbs data by replacing the value of the contributing thread, and merges by taking the union of
+/// the per-thread values.
+///
+/// Instances dereference to the underlying [`HashMap`].
+#[derive(Debug, Clone)]
+pub struct PerThread<V>(HashMap<ThreadId, V>);
+
+impl<V> PerThread<V> {
+    /// Returns the underlying map from thread IDs to values.
+    pub fn into_inner(self) -> HashMap<ThreadId, V> {
+        self.0
+    }
+}
+
+impl<V: PartialEq> PartialEq for PerThread<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<V> Default for PerThread<V> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<V> Deref for PerThread<V> {
+    type Target = HashMap<ThreadId, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<V> From<HashMap<ThreadId, V>> for PerThread<V> {
+    fn from(map: HashMap<ThreadId, V>) -> Self {
+        Self(map)
+    }
+}
+
+impl<V> Accumulator for PerThread<V> {
+    type Dat = V;
+
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn absorb(&mut self, data: V, tid: ThreadId) {
+        self.0.insert(tid, data);
+    }
+
+    fn merge(mut self, mut other: Self) -> Self {
+        // Merge the smaller map into the larger one.
+        if self.0.len() < other.0.len() {
+            swap(&mut self, &mut other);
+        }
+        self.0.extend(other.0);
+        self
+    }
+}
+
+/// Functions that define the accumulation of an [`Accumulator`] of type `A`, in the form taken by the `new`
+/// constructors and `aggregate_data` methods of the `Control` types. Obtained with [`ops`] or [`per_thread`].
+#[derive(Debug)]
+pub struct Ops<A: Accumulator> {
+    /// Produces the zero value of the accumulation, see [`Accumulator::zero`].
+    pub zero: fn() -> A,
+    /// Combines data contributed from a thread with an accumulated value, see [`Accumulator::absorb`].
+    pub op: fn(A::Dat, &mut A, ThreadId),
+    /// Merges two accumulated values, see [`Accumulator::merge`].
+    pub op_r: fn(A, A) -> A,
+}
+
+impl<A: Accumulator> Clone for Ops<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A: Accumulator> Copy for Ops<A> {}
+
+/// Returns the functions that define the accumulation of `A`.
+pub fn ops<A: Accumulator>() -> Ops<A> {
+    Ops {
+        zero: A::zero,
+        op: |data, acc, tid| acc.absorb(data, tid),
+        op_r: A::merge,
+    }
+}
+
+/// Returns the functions that keep the latest value of type `V` contributed by each thread, keyed by
+/// [`ThreadId`]. See [`PerThread`].
+///
+/// ```rust
+/// use std::thread;
+/// use thread_local_collect::accumulator::per_thread;
+///
+/// let per_thread = per_thread::<&str>();
+/// let mut acc = (per_thread.zero)();
+/// (per_thread.op)("a", &mut acc, thread::current().id());
+/// (per_thread.op)("b", &mut acc, thread::current().id());
+/// assert_eq!(acc.get(&thread::current().id()), Some(&"b"));
+/// ```
+pub fn per_thread<V>() -> Ops<PerThread<V>> {
+    ops()
+}
+
+/// Numeric value type summarized by [`Summary`].
+pub trait SummaryValue: Copy + Default + PartialOrd + AddAssign {
+    /// Converts `self` to an `f64`, possibly with loss of precision, to compute the mean.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_summary_value {
+    ($($t:ty),*) => {
+        $(
+            impl SummaryValue for $t {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_summary_value!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl SummaryValue for Duration {
+    /// Converts `self` to a number of seconds.
+    fn to_f64(self) -> f64 {
+        self.as_secs_f64()
+    }
+}
+
+/// [`Accumulator`] that computes standard statistics of the numeric values of type `N` contributed from threads:
+/// count, sum, minimum, maximum, and mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary<N> {
+    count: u64,
+    sum: N,
+    min: Option<N>,
+    max: Option<N>,
+}
+
+impl<N: SummaryValue> Summary<N> {
+    /// Returns the number of values absorbed.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the sum of the values absorbed.
+    pub fn sum(&self) -> N {
+        self.sum
+    }
+
+    /// Returns the minimum value absorbed, or `None` if no values have been absorbed.
+    pub fn min(&self) -> Option<N> {
+        self.min
+    }
+
+    /// Returns the maximum value absorbed, or `None` if no values have been absorbed.
+    pub fn max(&self) -> Option<N> {
+        self.max
+    }
+
+    /// Returns the mean of the values absorbed, or `None` if no values have been absorbed.
+    /// For [`Duration`] values, the mean is in seconds.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum.to_f64() / self.count as f64)
+    }
+
+    /// Returns the lesser of `a` and `b`, where `None` is greater than any value.
+    fn min_of(a: Option<N>, b: Option<N>) -> Option<N> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b < a { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns the greater of `a` and `b`, where `None` is less than any value.
+    fn max_of(a: Option<N>, b: Option<N>) -> Option<N> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b > a { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+impl<N: SummaryValue> Default for Summary<N> {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: N::default(),
+            min: None,
+            max: None,
+        }
+    }
+}
+
+impl<N: SummaryValue> Accumulator for Summary<N> {
+    type Dat = N;
+
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn absorb(&mut self, data: N, _tid: ThreadId) {
+        self.count += 1;
+        self.sum += data;
+        self.min = Self::min_of(self.min, Some(data));
+        self.max = Self::max_of(self.max, Some(data));
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = Self::min_of(self.min, other.min);
+        self.max = Self::max_of(self.max, other.max);
+        self
+    }
+}
+
+impl<N: SummaryValue + Debug> Display for Summary<N> {
+    /// Writes `count=.. sum=.. min=.. max=.. mean=..`, with `-` for the statistics of an empty summary.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_owned());
+        write!(
+            f,
+            "count={} sum={:?} min={} max={} mean={}",
+            self.count,
+            self.sum,
+            opt(self.min.map(|v| format!("{v:?}"))),
+            opt(self.max.map(|v| format!("{v:?}"))),
+            opt(self.mean().map(|v| v.to_string())),
+        )
+    }
+}
+
+/// Accumulated value that can be reset to the zero of its accumulation in place, retaining its allocations, e.g.,
+/// by clearing a collection without releasing its capacity.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use thread_local_collect::accumulator::Recycle;
+///
+/// let mut acc = HashMap::from([("a", 1)]);
+/// let capacity = acc.capacity();
+/// acc.recycle();
+/// assert!(acc.is_empty());
+/// assert_eq!(acc.capacity(), capacity);
+/// ```
+pub trait Recycle {
+    /// Resets `self` to the zero of its accumulation.
+    fn recycle(&mut self);
+}
+
+impl<K, V, S: BuildHasher> Recycle for HashMap<K, V, S> {
+    fn recycle(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T, S: BuildHasher> Recycle for HashSet<T, S> {
+    fn recycle(&mut self) {
+        self.clear()
+    }
+}
+
+impl<K, V> Recycle for BTreeMap<K, V> {
+    fn recycle(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T> Recycle for BTreeSet<T> {
+    fn recycle(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T> Recycle for Vec<T> {
+    fn recycle(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T> Recycle for VecDeque<T> {
+    fn recycle(&mut self) {
+        self.clear()
+    }
+}
+
+impl Recycle for String {
+    fn recycle(&mut self) {
+        self.clear()
+    }
+}
+
+impl<V> Recycle for PerThread<V> {
+    fn recycle(&mut self) {
+        self.0.clear()
+    }
+}
+
+impl<K, A> Recycle for Keyed<K, A> {
+    fn recycle(&mut self) {
+        self.0.clear()
+    }
+}
+
+impl<N: SummaryValue> Recycle for Summary<N> {
+    fn recycle(&mut self) {
+        *self = Self::default()
+    }
+}
+
+/// Accumulated value that can be rendered as a table of `key  value` rows, one per entry, with the keys aligned,
+/// by the `format_acc` methods of the `Control` types. Keys and values are rendered with [`Debug`].
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use thread_local_collect::accumulator::Tabular;
+///
+/// let acc = HashMap::from([("b", 2), ("a", 10)]);
+/// assert_eq!(acc.format_table(), "\"a\"  10\n\"b\"  2\n");
+/// ```
+pub trait Tabular {
+    /// Returns the rendered keys and values of the table's rows, in display order.
+    fn rows(&self) -> Vec<(String, String)>;
+
+    /// Renders `self` as a table, one row per line.
+    fn format_table(&self) -> String {
+        format_rows(self.rows())
+    }
+}
+
+/// Renders `rows` with the keys padded to the width of the widest key.
+fn format_rows(rows: Vec<(String, String)>) -> String {
+    let width = rows.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    rows.into_iter()
+        .map(|(k, v)| format!("{k:<width$}  {v}\n"))
+        .collect()
+}
+
+/// Returns the rows of the entries of a map, rendering values with `value`, sorted by rendered key, since the
+/// iteration order of a [`HashMap`] is arbitrary.
+fn sorted_rows<'a, K: Debug + 'a, V: 'a>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    value: impl Fn(&V) -> String,
+) -> Vec<(String, String)> {
+    let mut rows = entries
+        .map(|(k, v)| (format!("{k:?}"), value(v)))
+        .collect::<Vec<_>>();
+    rows.sort();
+    rows
+}
+
+impl<K: Debug, V: Debug> Tabular for HashMap<K, V> {
+    fn rows(&self) -> Vec<(String, String)> {
+        sorted_rows(self.iter(), |v| format!("{v:?}"))
+    }
+}
+
+impl<K: Debug, V: Debug> Tabular for BTreeMap<K, V> {
+    /// Returns the rows in key order.
+    fn rows(&self) -> Vec<(String, String)> {
+        self.iter()
+            .map(|(k, v)| (format!("{k:?}"), format!("{v:?}")))
+            .collect()
+    }
+}
+
+impl<V: Debug> Tabular for PerThread<V> {
+    fn rows(&self) -> Vec<(String, String)> {
+        self.0.rows()
+    }
+}
+
+impl<K: Debug, A: Debug> Tabular for Keyed<K, A> {
+    fn rows(&self) -> Vec<(String, String)> {
+        self.0.rows()
+    }
+}
+
+impl<V: Display> Display for PerThread<V> {
+    /// Writes one row per thread, as with [`Tabular::format_table`], with the values rendered with [`Display`].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_rows(sorted_rows(self.0.iter(), V::to_string)))
+    }
+}
+
+impl<K: Debug, A: Display> Display for Keyed<K, A> {
+    /// Writes one row per key, as with [`Tabular::format_table`], with the values rendered with [`Display`].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_rows(sorted_rows(self.0.iter(), A::to_string)))
+    }
+}
@@ -0,0 +1,99 @@
+//! Optional tracking of the contribution activity of each thread, enabled with the `with_activity_tracking` methods
+//! of the `Control` types. It allows, for example, the detection of stalled worker threads or of an uneven
+//! distribution of load across a worker pool from the same control object that is used for aggregation.
+//!
+//! A contribution is a call, directly or through a [`crate::handles::ControlSender`], to a method of the control
+//! object that contributes data from the current thread: `with_data_mut` in the [`crate::tlm`] direct
+//! sub-modules, `send_data` in [`crate::tlm::channeled`], and `with_tl_acc_mut` and the methods based on it
+//! (`aggregate_data`, `absorb_data`, and `add_data`) in the [`crate::tlm::restr`] and `tlcr` sub-modules.
+//!
+//! When tracking is enabled, each contribution acquires an internal lock to record the activity, which adds some
+//! overhead and contention to contributions.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread::ThreadId,
+    time::{Duration, Instant},
+};
+
+const POISONED_ACTIVITY_MUTEX: &str = "poisoned activity mutex";
+
+/// Contribution activity of a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadActivity {
+    /// Time of the thread's first contribution.
+    pub first_seen: Instant,
+    /// Time of the thread's most recent contribution.
+    pub last_seen: Instant,
+    /// Number of contributions made by the thread.
+    pub contributions: u64,
+}
+
+/// Records the contribution activity of each thread.
+#[derive(Debug, Default)]
+pub(crate) struct ActivityTracker(Mutex<HashMap<ThreadId, ThreadActivity>>);
+
+impl ActivityTracker {
+    /// Records a contribution from thread `tid`.
+    ///
+    /// # Panics
+    /// If the tracker's mutex is poisoned.
+    fn record(&self, tid: ThreadId) {
+        let now = Instant::now();
+        let mut map = self.0.lock().expect(POISONED_ACTIVITY_MUTEX);
+        map.entry(tid)
+            .and_modify(|activity| {
+                activity.last_seen = now;
+                activity.contributions += 1;
+            })
+            .or_insert(ThreadActivity {
+                first_seen: now,
+                last_seen: now,
+                contributions: 1,
+            });
+    }
+
+    /// Returns the activity recorded for each thread.
+    ///
+    /// # Panics
+    /// If the tracker's mutex is poisoned.
+    fn snapshot(&self) -> HashMap<ThreadId, ThreadActivity> {
+        self.0.lock().expect(POISONED_ACTIVITY_MUTEX).clone()
+    }
+}
+
+/// Records a contribution from thread `tid` in `tracker`, if any.
+pub(crate) fn record(tracker: Option<&Arc<ActivityTracker>>, tid: ThreadId) {
+    if let Some(tracker) = tracker {
+        tracker.record(tid);
+    }
+}
+
+/// Returns the activity recorded in `tracker`, or an empty map if there is no tracker.
+pub(crate) fn snapshot(
+    tracker: Option<&Arc<ActivityTracker>>,
+) -> HashMap<ThreadId, ThreadActivity> {
+    tracker
+        .map(|tracker| tracker.snapshot())
+        .unwrap_or_default()
+}
+
+/// Returns the number of contributions of each thread recorded in `tracker`, or an empty map if there is no tracker.
+pub(crate) fn counts(tracker: Option<&Arc<ActivityTracker>>) -> HashMap<ThreadId, u64> {
+    snapshot(tracker)
+        .into_iter()
+        .map(|(tid, activity)| (tid, activity.contributions))
+        .collect()
+}
+
+/// Returns the threads recorded in `tracker` whose last contribution is older than `since`, or an empty vector if
+/// there is no tracker.
+pub(crate) fn idle(tracker: Option<&Arc<ActivityTracker>>, since: Duration) -> Vec<ThreadId> {
+    let now = Instant::now();
+    snapshot(tracker)
+        .into_iter()
+        .filter(|(_, activity)| now.saturating_duration_since(activity.last_seen) > since)
+        .map(|(tid, _)| tid)
+        .collect()
+}
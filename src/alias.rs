@@ -0,0 +1,92 @@
+//! Deterministic aliases for threads, for reproducible per-thread output.
+//!
+//! The [`Debug`](std::fmt::Debug) representations of [`ThreadId`]s, e.g., `ThreadId(7)`, depend on how many threads
+//! the process created before, so per-thread output that includes them changes from run to run, e.g., when
+//! snapshot-testing or diffing drained values. [`ThreadAliases`] maps [`ThreadId`]s to small indices, `0`, `1`, ...,
+//! in the order in which the threads are first seen. When attached to a `tlm` control object with its
+//! `with_thread_aliases` method, threads are seen when they register with the control object, i.e., on their first
+//! contribution, so the aliases follow the registration order.
+//!
+//! The aliases are used by [`Breakdown::aliased`](crate::breakdown::Breakdown::aliased) and
+//! [`write_per_thread_aliased`](crate::export::write_per_thread_aliased), and can be looked up with
+//! [`ThreadAliases::get`] for any other output.
+//!
+//! ```rust
+//! use std::thread;
+//! use thread_local_collect::{
+//!     alias::ThreadAliases,
+//!     tlm::probed::{Control, Holder},
+//! };
+//!
+//! thread_local! {
+//!     static COUNT_TL: Holder<u64, u64> = Holder::new();
+//! }
+//!
+//! let aliases = ThreadAliases::new();
+//! let control = Control::<u64, u64>::new_additive(&COUNT_TL).with_thread_aliases(aliases.clone());
+//!
+//! control.with_data_mut(|data| *data += 1);
+//! thread::scope(|s| {
+//!     s.spawn(|| control.with_data_mut(|data| *data += 10));
+//! });
+//!
+//! assert_eq!(aliases.get(thread::current().id()), Some(0));
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread::ThreadId,
+};
+
+const POISONED_ALIASES_MUTEX: &str = "poisoned aliases mutex";
+
+/// Maps [`ThreadId`]s to small indices in the order in which the threads are first seen. Clones share the same
+/// mapping. See the [module](self) documentation.
+#[derive(Debug, Clone, Default)]
+pub struct ThreadAliases(Arc<Mutex<HashMap<ThreadId, usize>>>);
+
+impl ThreadAliases {
+    /// Instantiates an empty mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the alias of thread `tid`, assigning it the next index if it has not been seen before.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn alias(&self, tid: ThreadId) -> usize {
+        let mut map = self.0.lock().expect(POISONED_ALIASES_MUTEX);
+        let next = map.len();
+        *map.entry(tid).or_insert(next)
+    }
+
+    /// Returns the alias of thread `tid`, if it has been seen.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn get(&self, tid: ThreadId) -> Option<usize> {
+        self.0
+            .lock()
+            .expect(POISONED_ALIASES_MUTEX)
+            .get(&tid)
+            .copied()
+    }
+
+    /// Returns the number of threads seen.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn len(&self) -> usize {
+        self.0.lock().expect(POISONED_ALIASES_MUTEX).len()
+    }
+
+    /// Returns `true` if no threads have been seen.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
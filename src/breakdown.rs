@@ -0,0 +1,147 @@
+//! Per-thread breakdown of drained values, returned by the methods that drain the thread-local values of a control
+//! object without aggregating them, e.g., [`crate::tlm::probed::Control::drain_per_thread`].
+//!
+//! A [`Breakdown`] implements [`IntoIterator`] with `Item = (ThreadId, V)`, with an exact size hint, so it plugs
+//! directly into iterator pipelines and collectors:
+//!
+//! ```rust
+//! use std::{collections::HashMap, thread};
+//! use thread_local_collect::tlm::probed::{Control, Holder};
+//!
+//! thread_local! {
+//!     static COUNT_TL: Holder<u64, u64> = Holder::new();
+//! }
+//!
+//! let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+//! control.with_data_mut(|data| *data += 1);
+//!
+//! let counts: HashMap<_, _> = control.drain_per_thread().into_iter().collect();
+//! assert_eq!(counts[&thread::current().id()], 1);
+//! assert_eq!(control.probe_tls(), 0);
+//! ```
+
+use crate::alias::ThreadAliases;
+use std::{cmp::Ordering, thread::ThreadId, vec};
+
+/// Values of type `V` drained from the thread-local variables of a control object, one per thread, in arbitrary
+/// order unless sorted with [`Self::sorted`] or [`Self::sorted_by_key`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakdown<V>(Vec<(ThreadId, V)>);
+
+impl<V> Breakdown<V> {
+    pub(crate) fn new(values: Vec<(ThreadId, V)>) -> Self {
+        Self(values)
+    }
+
+    /// Returns the number of threads.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no threads.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over references to the threads' values.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &(ThreadId, V)> {
+        self.0.iter()
+    }
+
+    /// Sorts the values in the order in which their threads were created. [`ThreadId`]s are not [`Ord`], but they
+    /// are allocated in increasing order and their [`Debug`] representation, `ThreadId(n)`, reflects this.
+    pub fn sorted(mut self) -> Self {
+        self.0.sort_by(|(a, _), (b, _)| cmp_threads(a, b));
+        self
+    }
+
+    /// Sorts the values by the key that `f` extracts from each thread and its value. The sort is stable.
+    pub fn sorted_by_key<K: Ord>(mut self, mut f: impl FnMut(&ThreadId, &V) -> K) -> Self {
+        self.0.sort_by_key(|(tid, v)| f(tid, v));
+        self
+    }
+
+    /// Returns the values keyed by the aliases of their threads in `aliases`, sorted by alias, so that the result
+    /// does not depend on run-dependent [`ThreadId`]s, e.g., for snapshot tests. Threads without an alias are
+    /// assigned one, in the order in which they were created. See [`crate::alias`].
+    ///
+    /// # Panics
+    /// If the mutex of `aliases` is poisoned.
+    pub fn aliased(self, aliases: &ThreadAliases) -> Vec<(usize, V)> {
+        let mut values = self
+            .sorted()
+            .into_iter()
+            .map(|(tid, v)| (aliases.alias(tid), v))
+            .collect::<Vec<_>>();
+        values.sort_by_key(|&(alias, _)| alias);
+        values
+    }
+}
+
+/// Compares thread IDs in the order in which the threads were created.
+fn cmp_threads(a: &ThreadId, b: &ThreadId) -> Ordering {
+    let (a, b) = (format!("{a:?}"), format!("{b:?}"));
+    // The representations differ only in the number, so a shorter one has a smaller number.
+    a.len().cmp(&b.len()).then_with(|| a.cmp(&b))
+}
+
+impl<V> IntoIterator for Breakdown<V> {
+    type Item = (ThreadId, V);
+    type IntoIter = vec::IntoIter<(ThreadId, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Breakdown<V> {
+    type Item = &'a (ThreadId, V);
+    type IntoIter = std::slice::Iter<'a, (ThreadId, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::Breakdown;
+    use crate::{alias::ThreadAliases, dev_support::assert_eq_and_println};
+    use std::thread;
+
+    #[test]
+    fn sorted_and_size_hint() {
+        let tids = (0..12)
+            .map(|_| thread::spawn(|| thread::current().id()).join().unwrap())
+            .collect::<Vec<_>>();
+        let values = tids.iter().rev().zip(0..).map(|(&tid, i)| (tid, i));
+        let breakdown = Breakdown::new(values.collect());
+
+        let iter = breakdown.clone().into_iter();
+        assert_eq_and_println(&iter.size_hint(), &(12, Some(12)), "size hint");
+
+        let sorted = breakdown.clone().sorted();
+        let sorted_tids = sorted.iter().map(|(tid, _)| *tid).collect::<Vec<_>>();
+        assert_eq_and_println(&sorted_tids, &tids, "sorted by thread creation");
+
+        let by_value = breakdown.sorted_by_key(|_, v| *v);
+        let values = by_value.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+        assert_eq_and_println(&values, &(0..12).collect::<Vec<_>>(), "sorted by value");
+    }
+
+    #[test]
+    fn aliased() {
+        let tids = (0..3)
+            .map(|_| thread::spawn(|| thread::current().id()).join().unwrap())
+            .collect::<Vec<_>>();
+        let aliases = ThreadAliases::new();
+        assert_eq_and_println(&aliases.alias(tids[2]), &0, "pre-assigned alias");
+
+        let values = tids.iter().zip(["a", "b", "c"]).map(|(&tid, v)| (tid, v));
+        let breakdown = Breakdown::new(values.rev().collect());
+        let expected = vec![(0, "c"), (1, "a"), (2, "b")];
+        assert_eq_and_println(&breakdown.aliased(&aliases), &expected, "aliased");
+        assert_eq_and_println(&aliases.len(), &3, "aliases len");
+    }
+}
@@ -0,0 +1,49 @@
+//! Pluggable diagnostics hook that receives structured events about the library's internal operations.
+//!
+//! A hook of type [`DiagHook`] can be attached to a `Control` object with its `with_diagnostics` method,
+//! which is available for all `Control` types in this library. The hook is shared by all clones of the
+//! `Control` object created after it is attached, so it should normally be attached immediately after
+//! the `Control` object is instantiated. This lets embedders route the library's internal events to their own
+//! telemetry, independently of the **"log"** and **"tracing"** features.
+//!
+//! For finer-grained debugging, e.g., when an accumulated value comes out wrong, the `instrumented` methods of the
+//! `tlm` `Control` types log every invocation of their `op` as an [`OpRecord`].
+
+use std::{sync::Arc, thread::ThreadId, time::Duration};
+
+/// Structured event emitted by the library's internal operations.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DiagEvent {
+    /// Operation `op` processed a thread-local value from thread `tid`, if known.
+    Item {
+        op: &'static str,
+        tid: Option<ThreadId>,
+    },
+    /// Operation `op` completed after processing `count` thread-local values in `elapsed` time.
+    Completed {
+        op: &'static str,
+        count: usize,
+        elapsed: Duration,
+    },
+    /// Point event for operation `op` on thread `tid`, e.g., the registration of a thread-local variable.
+    Point { op: &'static str, tid: ThreadId },
+}
+
+/// Diagnostics callback that receives [`DiagEvent`]s.
+pub type DiagHook = Arc<dyn Fn(&DiagEvent) + Send + Sync>;
+
+/// Record of a single invocation of the `op` of a `Control` object, passed to the logger attached with its
+/// `instrumented` method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OpRecord {
+    /// Position of the invocation in the sequence of invocations logged by the same logger, starting at `0`.
+    pub seq: usize,
+    /// Thread the data came from.
+    pub tid: ThreadId,
+    /// [`Debug`](std::fmt::Debug) representation of the data, truncated to 80 characters followed by `…`.
+    pub summary: String,
+    /// Time taken by `op`.
+    pub elapsed: Duration,
+}
@@ -0,0 +1,207 @@
+//! Writing of per-thread map accumulators as CSV or TSV rows, for the common case of dumping per-thread metrics to a
+//! file at shutdown, without a serialization framework.
+//!
+//! [`write_per_thread`] writes a header row `thread,key,value` followed by one row per key of each thread's map. The
+//! accumulated value is typically obtained by draining the control object, e.g., with `take_acc` or
+//! `drain_and_reset`.
+//!
+//! ```rust
+//! use std::{collections::HashMap, thread::{self, ThreadId}};
+//! use thread_local_collect::{
+//!     export::{write_per_thread, Delimiter},
+//!     tlm::channeled::{Control, Holder},
+//! };
+//!
+//! thread_local! {
+//!     static HITS_TL: Holder<&'static str> = Holder::new();
+//! }
+//!
+//! let control = Control::new(
+//!     &HITS_TL,
+//!     HashMap::new(),
+//!     |path, acc: &mut HashMap<ThreadId, HashMap<&str, u64>>, tid| {
+//!         *acc.entry(tid).or_default().entry(path).or_default() += 1
+//!     },
+//! );
+//! let sender = control.sender();
+//! thread::spawn(move || sender.send_data("/index")).join().unwrap();
+//!
+//! let mut out = Vec::new();
+//! write_per_thread(&mut out, control.drain_and_reset(HashMap::new()), Delimiter::Comma).unwrap();
+//! let out = String::from_utf8(out).unwrap();
+//! assert!(out.starts_with("thread,key,value\n"));
+//! assert!(out.ends_with(",/index,1\n"));
+//! ```
+//!
+//! [`write_per_thread_aliased`] renders threads as deterministic aliases instead (see [`crate::alias`]), so that the
+//! output can be compared across runs.
+
+use crate::alias::ThreadAliases;
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    thread::ThreadId,
+};
+
+/// Field delimiter of the rows written by [`write_per_thread`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Comma-separated values.
+    Comma,
+    /// Tab-separated values.
+    Tab,
+}
+
+impl Delimiter {
+    fn as_char(self) -> char {
+        match self {
+            Self::Comma => ',',
+            Self::Tab => '\t',
+        }
+    }
+}
+
+/// Writes `acc`, a map from [`ThreadId`]s to maps from keys to values, to `writer` as delimited rows
+/// `thread,key,value`, preceded by a header row. Threads are rendered with [`Debug`](std::fmt::Debug) (e.g.,
+/// `ThreadId(3)`), and keys and values with [`Display`]. The rows are sorted by thread and then by key, so the output
+/// does not depend on the iteration order of the maps.
+///
+/// A field that contains the delimiter, a double quote, or a line break is enclosed in double quotes, with its
+/// double quotes doubled, as in RFC 4180.
+///
+/// # Errors
+/// Returns any error from writing to `writer`.
+pub fn write_per_thread<W, M, K, V>(
+    writer: &mut W,
+    acc: impl IntoIterator<Item = (ThreadId, M)>,
+    delimiter: Delimiter,
+) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    M: IntoIterator<Item = (K, V)>,
+    K: Display,
+    V: Display,
+{
+    let mut rows = acc
+        .into_iter()
+        .flat_map(|(tid, map)| {
+            let tid = format!("{tid:?}");
+            map.into_iter()
+                .map(move |(k, v)| [tid.clone(), k.to_string(), v.to_string()])
+        })
+        .collect::<Vec<_>>();
+    rows.sort();
+    write_rows(writer, rows, delimiter)
+}
+
+/// Same as [`write_per_thread`], except that threads are rendered as their aliases in `aliases`, e.g., `0`, and
+/// rows are sorted by alias and then by key, so that the output does not depend on run-dependent [`ThreadId`]s,
+/// e.g., for snapshot tests. Threads without an alias are assigned one, in the order in which they appear in `acc`.
+/// See [`crate::alias`].
+///
+/// # Errors
+/// Returns any error from writing to `writer`.
+///
+/// # Panics
+/// If the mutex of `aliases` is poisoned.
+pub fn write_per_thread_aliased<W, M, K, V>(
+    writer: &mut W,
+    acc: impl IntoIterator<Item = (ThreadId, M)>,
+    delimiter: Delimiter,
+    aliases: &ThreadAliases,
+) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    M: IntoIterator<Item = (K, V)>,
+    K: Display,
+    V: Display,
+{
+    let mut rows = acc
+        .into_iter()
+        .flat_map(|(tid, map)| {
+            let alias = aliases.alias(tid);
+            map.into_iter()
+                .map(move |(k, v)| (alias, k.to_string(), v.to_string()))
+        })
+        .collect::<Vec<_>>();
+    rows.sort();
+    let rows = rows
+        .into_iter()
+        .map(|(alias, k, v)| [alias.to_string(), k, v])
+        .collect();
+    write_rows(writer, rows, delimiter)
+}
+
+/// Writes a header row followed by `rows`.
+fn write_rows<W>(writer: &mut W, rows: Vec<[String; 3]>, delimiter: Delimiter) -> io::Result<()>
+where
+    W: Write + ?Sized,
+{
+    let header = ["thread", "key", "value"].map(str::to_owned);
+    for row in std::iter::once(header).chain(rows) {
+        let line = row
+            .iter()
+            .map(|field| escape(field, delimiter))
+            .collect::<Vec<_>>()
+            .join(&delimiter.as_char().to_string());
+        writeln!(writer, "{line}")?;
+    }
+    Ok(())
+}
+
+/// Quotes `field` if it contains the delimiter, a double quote, or a line break.
+fn escape(field: &str, delimiter: Delimiter) -> String {
+    if field.contains([delimiter.as_char(), '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{write_per_thread, write_per_thread_aliased, Delimiter};
+    use crate::{alias::ThreadAliases, dev_support::assert_eq_and_println};
+    use std::{collections::HashMap, thread};
+
+    #[test]
+    fn csv_and_tsv() {
+        let tid = thread::current().id();
+        let acc = HashMap::from([(
+            tid,
+            HashMap::from([("b", "x,y"), ("a", "say \"hi\""), ("c\td", "z")]),
+        )]);
+
+        let mut out = Vec::new();
+        write_per_thread(&mut out, acc.clone(), Delimiter::Comma).unwrap();
+        let expected = format!(
+            "thread,key,value\n{tid:?},a,\"say \"\"hi\"\"\"\n{tid:?},b,\"x,y\"\n{tid:?},c\td,z\n"
+        );
+        assert_eq_and_println(&String::from_utf8(out).unwrap(), &expected, "csv");
+
+        let mut out = Vec::new();
+        write_per_thread(&mut out, acc, Delimiter::Tab).unwrap();
+        let expected = format!(
+            "thread\tkey\tvalue\n{tid:?}\ta\t\"say \"\"hi\"\"\"\n{tid:?}\tb\tx,y\n{tid:?}\t\"c\td\"\tz\n"
+        );
+        assert_eq_and_println(&String::from_utf8(out).unwrap(), &expected, "tsv");
+    }
+
+    #[test]
+    fn aliased() {
+        let tid = thread::current().id();
+        let tid_spawned = thread::spawn(|| thread::current().id()).join().unwrap();
+        let aliases = ThreadAliases::new();
+        aliases.alias(tid_spawned);
+        let acc = HashMap::from([
+            (tid, HashMap::from([("b", 2), ("a", 1)])),
+            (tid_spawned, HashMap::from([("c", 3)])),
+        ]);
+
+        let mut out = Vec::new();
+        write_per_thread_aliased(&mut out, acc, Delimiter::Comma, &aliases).unwrap();
+        let expected = "thread,key,value\n0,c,3\n1,a,1\n1,b,2\n";
+        assert_eq_and_println(&String::from_utf8(out).unwrap().as_str(), &expected, "csv");
+    }
+}
@@ -0,0 +1,780 @@
+//! This module provides a facade [`Control`] whose collection strategy is a type parameter, so that an application
+//! can switch between strategies, e.g., after benchmarking them, by changing a single type alias.
+//!
+//! The strategy is one of the *mode* types [`Joined`], [`Probed`], [`Deferred`], [`Channeled`], and [`Tlcr`], each of which
+//! implements [`CollectMode`] on top of the corresponding module of this library. All modes share the following
+//! method set, which follows the `send` API of the [`crate::tlm::restr`] and `tlcr` sub-modules:
+//! - [`Control::send`] is called from the participating threads, directly or through a
+//!   [`ControlSender`], to aggregate data with the thread's contribution.
+//! - [`Control::probe`] returns the current accumulated value, for the modes that implement [`ProbeMode`]
+//!   (all except [`Joined`]).
+//! - [`Control::drain`] returns the accumulated value after all participating threads have terminated and been
+//!   explicitly joined, directly or indirectly, into the calling thread, which satisfies the requirements of all modes.
+//!
+//! [`Control::finish`] drains a control object by value and returns, along with the accumulated value, a control
+//! object in the [`Drained`] state, which lacks the methods above, so that sending data through it after the final
+//! drain is a compile-time error:
+//!
+//! ```compile_fail
+//! # use thread_local_collect::facade::{holder, Control, Holder, Probed};
+//! # thread_local! {
+//! #     static TL: Holder<Probed, u64, u64> = holder::<Probed, u64, u64>();
+//! # }
+//! let control = Control::<Probed, u64, u64>::new(&TL, || 0, |data, acc, _| *acc += data, |a, b| a + b);
+//! let (acc, control) = control.finish();
+//! control.send(1); // `send` is not available on `Control<Probed, u64, u64, Drained>`
+//! ```
+//!
+//! This does not apply to clones of the control object, including [`ControlSender`]s, that were made before the call
+//! to [`Control::finish`] and remain in the [`Active`] state.
+//!
+//! Each mode requires a thread-local static of type [`Holder<M, T, U>`], initialized with [`holder`], even if it
+//! does not use it (e.g., [`Tlcr`]), so that switching modes does not require any other changes.
+//!
+//! Each mode is available when the features that enable its underlying module are enabled, except [`Noop`], which
+//! is always available. [`Noop`] discards all data, so instrumentation can be compiled in but disabled wholesale by
+//! switching the mode type alias, e.g., depending on an application feature, without any checks at the call sites:
+//!
+//! ```rust
+//! # use thread_local_collect::facade::{Noop, Probed};
+//! #[cfg(feature = "metrics")]
+//! type Mode = Probed;
+//! #[cfg(not(feature = "metrics"))]
+//! type Mode = Noop;
+//! ```
+//!
+//! ## Usage pattern
+//!
+//! ```rust
+//! use std::thread;
+//! use thread_local_collect::facade::{holder, Control, Holder, Probed};
+//!
+//! // Switch the collection strategy here, e.g., to `Channeled` or `Tlcr`.
+//! type Mode = Probed;
+//!
+//! thread_local! {
+//!     static TL: Holder<Mode, u64, u64> = holder::<Mode, u64, u64>();
+//! }
+//!
+//! let mut control = Control::<Mode, u64, u64>::new(&TL, || 0, |data, acc, _| *acc += data, |a, b| a + b);
+//!
+//! thread::scope(|s| {
+//!     for i in 1..=3 {
+//!         let sender = control.sender();
+//!         s.spawn(move || sender.send(i));
+//!     }
+//! });
+//!
+//! assert_eq!(control.probe(), 6);
+//! assert_eq!(control.drain(), 6);
+//! ```
+
+use crate::handles::ControlSender;
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    thread::{LocalKey, ThreadId},
+};
+
+#[cfg(feature = "tlcr")]
+use crate::tlcr;
+#[cfg(feature = "channeled")]
+use crate::tlm::channeled;
+#[cfg(all(feature = "restr", feature = "joined"))]
+use crate::tlm::restr::joined;
+#[cfg(all(feature = "restr", feature = "probed"))]
+use crate::tlm::restr::probed;
+
+/// Collection strategy of a [`Control`] that collects data of type `T` into an accumulated value of type `U`.
+pub trait CollectMode<T, U>: Sized + 'static {
+    /// Type of the thread-local static required by this mode.
+    type Holder: 'static;
+
+    /// Type of the underlying control object.
+    type Inner: Clone + Send;
+
+    /// Instantiates a holder for the thread-local static.
+    fn new_holder() -> Self::Holder;
+
+    /// Instantiates the underlying control object. See [`Control::new`].
+    fn new_inner(
+        tl: &'static LocalKey<Self::Holder>,
+        acc_zero: fn() -> U,
+        op: fn(T, &mut U, ThreadId),
+        op_r: fn(U, U) -> U,
+    ) -> Self::Inner;
+
+    /// Aggregates `data` with the calling thread's contribution. See [`Control::send`].
+    fn send(inner: &Self::Inner, data: T, op: fn(T, &mut U, ThreadId));
+
+    /// Returns the accumulated value, replacing it with the zero value. See [`Control::drain`].
+    fn drain(inner: &mut Self::Inner, acc_zero: fn() -> U) -> U;
+}
+
+/// Collection strategy that supports probing the accumulated value while the participating threads are active.
+pub trait ProbeMode<T, U>: CollectMode<T, U> {
+    /// Returns the current accumulated value. See [`Control::probe`].
+    fn probe(inner: &Self::Inner) -> U;
+}
+
+/// Type of the thread-local static required by mode `M`.
+pub type Holder<M, T, U> = <M as CollectMode<T, U>>::Holder;
+
+/// Instantiates a holder for the thread-local static required by mode `M`.
+pub fn holder<M: CollectMode<T, U>, T, U>() -> Holder<M, T, U> {
+    M::new_holder()
+}
+
+/// Mode based on [`crate::tlm::restr::joined`].
+#[cfg(all(feature = "restr", feature = "joined"))]
+#[derive(Debug)]
+pub enum Joined {}
+
+#[cfg(all(feature = "restr", feature = "joined"))]
+impl<T, U> CollectMode<T, U> for Joined
+where
+    U: Send + 'static,
+{
+    type Holder = joined::Holder<U>;
+    type Inner = joined::Control<U>;
+
+    fn new_holder() -> Self::Holder {
+        joined::Holder::new()
+    }
+
+    fn new_inner(
+        tl: &'static LocalKey<Self::Holder>,
+        acc_zero: fn() -> U,
+        _op: fn(T, &mut U, ThreadId),
+        op_r: fn(U, U) -> U,
+    ) -> Self::Inner {
+        joined::Control::new(tl, acc_zero, op_r)
+    }
+
+    fn send(inner: &Self::Inner, data: T, op: fn(T, &mut U, ThreadId)) {
+        inner.aggregate_data(data, op)
+    }
+
+    fn drain(inner: &mut Self::Inner, _acc_zero: fn() -> U) -> U {
+        inner.drain_tls()
+    }
+}
+
+/// Mode based on [`crate::tlm::restr::probed`].
+#[cfg(all(feature = "restr", feature = "probed"))]
+#[derive(Debug)]
+pub enum Probed {}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> CollectMode<T, U> for Probed
+where
+    U: Send + 'static,
+{
+    type Holder = probed::Holder<U>;
+    type Inner = probed::Control<U>;
+
+    fn new_holder() -> Self::Holder {
+        probed::Holder::new()
+    }
+
+    fn new_inner(
+        tl: &'static LocalKey<Self::Holder>,
+        acc_zero: fn() -> U,
+        _op: fn(T, &mut U, ThreadId),
+        op_r: fn(U, U) -> U,
+    ) -> Self::Inner {
+        probed::Control::new(tl, acc_zero, op_r)
+    }
+
+    fn send(inner: &Self::Inner, data: T, op: fn(T, &mut U, ThreadId)) {
+        inner.aggregate_data(data, op)
+    }
+
+    fn drain(inner: &mut Self::Inner, _acc_zero: fn() -> U) -> U {
+        inner.drain_tls()
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> ProbeMode<T, U> for Probed
+where
+    U: Clone + Send + 'static,
+{
+    fn probe(inner: &Self::Inner) -> U {
+        inner.probe_tls()
+    }
+}
+
+/// Mode based on [`crate::tlm::channeled`], without a background receiver thread.
+#[cfg(feature = "channeled")]
+#[derive(Debug)]
+pub enum Channeled {}
+
+#[cfg(feature = "channeled")]
+impl<T, U> CollectMode<T, U> for Channeled
+where
+    T: Send + 'static,
+    U: Send + 'static,
+{
+    type Holder = channeled::Holder<T>;
+    type Inner = channeled::Control<T, U>;
+
+    fn new_holder() -> Self::Holder {
+        channeled::Holder::new()
+    }
+
+    fn new_inner(
+        tl: &'static LocalKey<Self::Holder>,
+        acc_zero: fn() -> U,
+        op: fn(T, &mut U, ThreadId),
+        _op_r: fn(U, U) -> U,
+    ) -> Self::Inner {
+        channeled::Control::new(tl, acc_zero(), op)
+    }
+
+    fn send(inner: &Self::Inner, data: T, _op: fn(T, &mut U, ThreadId)) {
+        inner.send_data(data)
+    }
+
+    fn drain(inner: &mut Self::Inner, acc_zero: fn() -> U) -> U {
+        inner.drain_and_reset(acc_zero())
+    }
+}
+
+#[cfg(feature = "channeled")]
+impl<T, U> ProbeMode<T, U> for Channeled
+where
+    T: Send + 'static,
+    U: Clone + Send + 'static,
+{
+    /// Receives the pending values before returning a clone of the accumulated value.
+    fn probe(inner: &Self::Inner) -> U {
+        inner.drain_tls();
+        inner.clone_acc()
+    }
+}
+
+/// Mode based on [`crate::tlcr::probed`], which does not use its thread-local static.
+#[cfg(feature = "tlcr")]
+#[derive(Debug)]
+pub enum Tlcr {}
+
+#[cfg(feature = "tlcr")]
+impl<T, U> CollectMode<T, U> for Tlcr
+where
+    U: Send + 'static,
+{
+    type Holder = ();
+    type Inner = tlcr::probed::Control<U>;
+
+    fn new_holder() -> Self::Holder {}
+
+    fn new_inner(
+        _tl: &'static LocalKey<Self::Holder>,
+        acc_zero: fn() -> U,
+        _op: fn(T, &mut U, ThreadId),
+        op_r: fn(U, U) -> U,
+    ) -> Self::Inner {
+        tlcr::probed::Control::new(acc_zero, op_r)
+    }
+
+    fn send(inner: &Self::Inner, data: T, op: fn(T, &mut U, ThreadId)) {
+        inner.aggregate_data(data, op)
+    }
+
+    /// # Panics
+    /// If a clone of `inner` exists, e.g., held by a participating thread that is still active.
+    fn drain(inner: &mut Self::Inner, _acc_zero: fn() -> U) -> U {
+        inner
+            .drain_tls()
+            .expect("drain called while participating threads were active")
+    }
+}
+
+#[cfg(feature = "tlcr")]
+impl<T, U> ProbeMode<T, U> for Tlcr
+where
+    U: Clone + Send + 'static,
+{
+    fn probe(inner: &Self::Inner) -> U {
+        inner.probe_tls()
+    }
+}
+
+/// Mode that discards all data, so that sending data costs nothing and probing or draining returns the zero value.
+/// Its thread-local static is not used. See the [module](self) documentation.
+#[derive(Debug)]
+pub enum Noop {}
+
+impl<T, U> CollectMode<T, U> for Noop
+where
+    U: 'static,
+{
+    type Holder = ();
+    type Inner = fn() -> U;
+
+    fn new_holder() -> Self::Holder {}
+
+    fn new_inner(
+        _tl: &'static LocalKey<Self::Holder>,
+        acc_zero: fn() -> U,
+        _op: fn(T, &mut U, ThreadId),
+        _op_r: fn(U, U) -> U,
+    ) -> Self::Inner {
+        acc_zero
+    }
+
+    #[inline(always)]
+    fn send(_inner: &Self::Inner, _data: T, _op: fn(T, &mut U, ThreadId)) {}
+
+    fn drain(_inner: &mut Self::Inner, acc_zero: fn() -> U) -> U {
+        acc_zero()
+    }
+}
+
+impl<T, U> ProbeMode<T, U> for Noop
+where
+    U: 'static,
+{
+    fn probe(inner: &Self::Inner) -> U {
+        inner()
+    }
+}
+
+/// Mode based on [`crate::tlm::restr::probed`] that defers the application of `op`: the data sent from each thread
+/// is appended, together with the thread's [`ThreadId`], to a thread-local log, and `op` is only applied when the
+/// accumulated value is requested with [`Control::aggregate_now`], [`Control::probe`], or [`Control::drain`]. This
+/// trades memory, which grows with the number of contributions, for a minimal cost per contribution, e.g., for short
+/// benchmark runs. The raw log can be retrieved with [`Control::drain_log`] for offline post-processing.
+#[cfg(all(feature = "restr", feature = "probed"))]
+#[derive(Debug)]
+pub enum Deferred {}
+
+/// Underlying control object of mode [`Deferred`].
+#[cfg(all(feature = "restr", feature = "probed"))]
+pub struct DeferredInner<T, U>
+where
+    T: 'static,
+{
+    /// Collects the logs of the participating threads.
+    log: probed::Control<Vec<(ThreadId, T)>>,
+    /// Produces the zero value of the accumulation.
+    acc_zero: fn() -> U,
+    /// Operation that combines logged data with the accumulated value.
+    op: fn(T, &mut U, ThreadId),
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> DeferredInner<T, U>
+where
+    T: 'static,
+{
+    /// Returns the control object that collects the logs of the participating threads.
+    pub fn log(&self) -> &probed::Control<Vec<(ThreadId, T)>> {
+        &self.log
+    }
+
+    /// Applies `op` to the entries of `log`, in order, starting from the zero value.
+    fn aggregate(&self, log: impl IntoIterator<Item = (ThreadId, T)>) -> U {
+        let mut acc = (self.acc_zero)();
+        for (tid, data) in log {
+            (self.op)(data, &mut acc, tid);
+        }
+        acc
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> Clone for DeferredInner<T, U>
+where
+    T: 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            log: self.log.clone(),
+            acc_zero: self.acc_zero,
+            op: self.op,
+        }
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> Debug for DeferredInner<T, U>
+where
+    T: Debug + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeferredInner")
+            .field("log", &self.log)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> CollectMode<T, U> for Deferred
+where
+    T: Send + 'static,
+    U: 'static,
+{
+    type Holder = probed::Holder<Vec<(ThreadId, T)>>;
+    type Inner = DeferredInner<T, U>;
+
+    fn new_holder() -> Self::Holder {
+        probed::Holder::new()
+    }
+
+    fn new_inner(
+        tl: &'static LocalKey<Self::Holder>,
+        acc_zero: fn() -> U,
+        op: fn(T, &mut U, ThreadId),
+        _op_r: fn(U, U) -> U,
+    ) -> Self::Inner {
+        DeferredInner {
+            log: probed::Control::new(tl, Vec::new, |data, mut acc| {
+                acc.extend(data);
+                acc
+            }),
+            acc_zero,
+            op,
+        }
+    }
+
+    #[inline]
+    fn send(inner: &Self::Inner, data: T, _op: fn(T, &mut U, ThreadId)) {
+        inner
+            .log
+            .aggregate_data(data, |data, log, tid| log.push((tid, data)))
+    }
+
+    fn drain(inner: &mut Self::Inner, _acc_zero: fn() -> U) -> U {
+        let log = inner.log.drain_tls();
+        inner.aggregate(log)
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> ProbeMode<T, U> for Deferred
+where
+    T: Clone + Send + 'static,
+    U: 'static,
+{
+    /// Applies `op` to clones of the logged data, leaving the logs in place.
+    fn probe(inner: &Self::Inner) -> U {
+        inner.aggregate(inner.log.probe_tls())
+    }
+}
+
+/// State of a [`Control`] that accepts data from the participating threads and can be drained.
+#[derive(Debug)]
+pub enum Active {}
+
+/// State of a [`Control`] that has been drained with [`Control::finish`], which no longer provides methods to send
+/// data, probe, or drain.
+#[derive(Debug)]
+pub enum Drained {}
+
+/// Controls the collection and accumulation of values of type `T` into an accumulated value of type `U`, with
+/// collection strategy `M`. `S` is the state of the control object, [`Active`] or [`Drained`]. See the
+/// [module](self) documentation.
+pub struct Control<M, T, U, S = Active>
+where
+    M: CollectMode<T, U>,
+{
+    /// Underlying control object.
+    inner: M::Inner,
+    /// Produces the zero value of the accumulation.
+    acc_zero: fn() -> U,
+    /// Operation that combines data sent from a thread with the thread's contribution.
+    op: fn(T, &mut U, ThreadId),
+    _state: PhantomData<S>,
+}
+
+impl<M, T, U> Control<M, T, U>
+where
+    M: CollectMode<T, U>,
+{
+    /// Instantiates a *control* object.
+    ///
+    /// - `tl` - reference to thread-local static, see [`Holder`].
+    /// - `acc_zero` - produces the zero value of the accumulation.
+    /// - `op` - operation that combines data sent from a thread with the accumulated value.
+    /// - `op_r` - binary operation that reduces two accumulated values into one.
+    ///
+    /// `op` and `op_r` must be consistent, i.e., combining data into two accumulated values and then reducing them
+    /// must yield the same result regardless of the mode, which only uses the operations it needs.
+    pub fn new(
+        tl: &'static LocalKey<M::Holder>,
+        acc_zero: fn() -> U,
+        op: fn(T, &mut U, ThreadId),
+        op_r: fn(U, U) -> U,
+    ) -> Self {
+        Self {
+            inner: M::new_inner(tl, acc_zero, op, op_r),
+            acc_zero,
+            op,
+            _state: PhantomData,
+        }
+    }
+
+    /// Called from a thread to aggregate `data` with the thread's contribution.
+    #[inline]
+    pub fn send(&self, data: T) {
+        M::send(&self.inner, data, self.op)
+    }
+
+    /// Returns the accumulated value, replacing it with the zero value. Must be called after all participating
+    /// threads have terminated and been explicitly joined, directly or indirectly, into the calling thread.
+    ///
+    /// # Panics
+    /// With mode [`Tlcr`], if a clone of `self` (e.g., a [`ControlSender`]) still exists.
+    pub fn drain(&mut self) -> U {
+        M::drain(&mut self.inner, self.acc_zero)
+    }
+
+    /// Returns the accumulated value as with [`Self::drain`], consuming `self`, together with a control object in the
+    /// [`Drained`] state on which data can no longer be sent. Clones of `self` are not affected.
+    ///
+    /// # Panics
+    /// With mode [`Tlcr`], if a clone of `self` (e.g., a [`ControlSender`]) still exists.
+    pub fn finish(mut self) -> (U, Control<M, T, U, Drained>) {
+        let acc = self.drain();
+        let drained = Control {
+            inner: self.inner,
+            acc_zero: self.acc_zero,
+            op: self.op,
+            _state: PhantomData,
+        };
+        (acc, drained)
+    }
+
+    /// Returns a send-only handle to `self`.
+    pub fn sender(&self) -> ControlSender<Self> {
+        ControlSender(self.clone())
+    }
+}
+
+impl<M, T, U, S> Control<M, T, U, S>
+where
+    M: CollectMode<T, U>,
+{
+    /// Returns a reference to the underlying control object, which provides the methods specific to mode `M`.
+    pub fn inner(&self) -> &M::Inner {
+        &self.inner
+    }
+}
+
+impl<M, T, U> Control<M, T, U>
+where
+    M: ProbeMode<T, U>,
+{
+    /// Returns the current accumulated value, which includes the contributions of threads that are still active.
+    pub fn probe(&self) -> U {
+        M::probe(&self.inner)
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> Control<Deferred, T, U>
+where
+    T: Clone + Send + 'static,
+    U: 'static,
+{
+    /// Applies `op` to the data logged so far by all participating threads, including those that are still active,
+    /// and returns the result, without changing the logs. Same as [`Self::probe`].
+    pub fn aggregate_now(&self) -> U {
+        self.probe()
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<T, U> Control<Deferred, T, U>
+where
+    T: Send + 'static,
+    U: 'static,
+{
+    /// Returns the data logged by the participating threads, each with the [`ThreadId`] of the thread that sent it,
+    /// without applying `op`, and clears the logs. The data sent from each thread appear in the order they were sent.
+    /// Must be called after all participating threads have terminated and been explicitly joined, directly or
+    /// indirectly, into the calling thread.
+    pub fn drain_log(&mut self) -> Vec<(ThreadId, T)> {
+        self.inner.log.drain_tls()
+    }
+}
+
+impl<M, T, U, S> Clone for Control<M, T, U, S>
+where
+    M: CollectMode<T, U>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            acc_zero: self.acc_zero,
+            op: self.op,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<M, T, U, S> Debug for Control<M, T, U, S>
+where
+    M: CollectMode<T, U>,
+    M::Inner: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Control")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<M, T, U> ControlSender<Control<M, T, U>>
+where
+    M: CollectMode<T, U>,
+{
+    /// Called from a thread to aggregate `data` with the thread's contribution. See [`Control::send`].
+    #[inline]
+    pub fn send(&self, data: T) {
+        self.0.send(data)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{holder, CollectMode, Control, Holder};
+    use crate::dev_support::assert_eq_and_println;
+    use std::thread::{self, ThreadId};
+
+    const NTHREADS: u64 = 5;
+
+    fn op(data: u64, acc: &mut u64, _: ThreadId) {
+        *acc += data;
+    }
+
+    fn op_r(acc1: u64, acc2: u64) -> u64 {
+        acc1 + acc2
+    }
+
+    /// Contributes from the current thread and `NTHREADS` spawned threads, returning the expected total.
+    fn contribute<M: CollectMode<u64, u64>>(control: &Control<M, u64, u64>) -> u64 {
+        control.send(100);
+        // The threads are joined explicitly, as the implicit join at the end of the scope does not wait for the
+        // threads' thread-local values to be dropped, which the `Joined` mode relies on.
+        thread::scope(|s| {
+            let hs = (0..NTHREADS)
+                .map(|i| {
+                    let sender = control.sender();
+                    s.spawn(move || sender.send(i))
+                })
+                .collect::<Vec<_>>();
+            hs.into_iter().for_each(|h| h.join().unwrap());
+        });
+        100 + (0..NTHREADS).sum::<u64>()
+    }
+
+    /// Exercises `send`, `probe`, and `drain` with mode `M`; the same code works for every mode.
+    #[cfg(any(
+        all(feature = "restr", feature = "probed"),
+        feature = "channeled",
+        feature = "tlcr"
+    ))]
+    fn probe_mode<M: super::ProbeMode<u64, u64>>(
+        tl: &'static std::thread::LocalKey<Holder<M, u64, u64>>,
+        name: &str,
+    ) {
+        let mut control = Control::<M, _, _>::new(tl, || 0, op, op_r);
+        let expected = contribute(&control);
+        assert_eq_and_println(&control.probe(), &expected, &format!("{name} probe"));
+        assert_eq_and_println(&control.drain(), &expected, &format!("{name} drain"));
+        assert_eq_and_println(&control.drain(), &0, &format!("{name} drain after drain"));
+
+        control.send(7);
+        let (acc, _drained) = control.finish();
+        assert_eq_and_println(&acc, &7, &format!("{name} finish"));
+    }
+
+    #[cfg(all(feature = "restr", feature = "joined"))]
+    #[test]
+    fn joined() {
+        use super::Joined;
+
+        thread_local! {static TL: Holder<Joined, u64, u64> = holder::<Joined, u64, u64>();}
+
+        let mut control = Control::<Joined, _, _>::new(&TL, || 0, op, op_r);
+        let expected = contribute(&control);
+        assert_eq_and_println(&control.drain(), &expected, "Joined drain");
+    }
+
+    #[cfg(all(feature = "restr", feature = "probed"))]
+    #[test]
+    fn probed() {
+        use super::Probed;
+
+        thread_local! {static TL: Holder<Probed, u64, u64> = holder::<Probed, u64, u64>();}
+        probe_mode::<Probed>(&TL, "Probed");
+    }
+
+    #[cfg(all(feature = "restr", feature = "probed"))]
+    #[test]
+    fn deferred() {
+        use super::Deferred;
+        use std::collections::HashMap;
+
+        thread_local! {
+            static TL: Holder<Deferred, u64, u64> = holder::<Deferred, u64, u64>();
+            static TL2: Holder<Deferred, u64, u64> = holder::<Deferred, u64, u64>();
+        }
+        probe_mode::<Deferred>(&TL, "Deferred");
+
+        let mut control = Control::<Deferred, _, _>::new(&TL2, || 0, op, op_r);
+        control.send(1);
+        assert_eq_and_println(&control.aggregate_now(), &1, "Deferred aggregate_now");
+        let expected = contribute(&control);
+        assert_eq_and_println(
+            &control.aggregate_now(),
+            &(1 + expected),
+            "Deferred aggregate_now after threads",
+        );
+
+        let mut per_thread = HashMap::<ThreadId, Vec<u64>>::new();
+        for (tid, data) in control.drain_log() {
+            per_thread.entry(tid).or_default().push(data);
+        }
+        let own = per_thread.remove(&thread::current().id()).unwrap();
+        assert_eq_and_println(&own, &vec![1, 100], "Deferred own log");
+        let mut others = per_thread.into_values().flatten().collect::<Vec<_>>();
+        others.sort();
+        assert_eq_and_println(&others, &(0..NTHREADS).collect(), "Deferred other logs");
+        assert_eq_and_println(&control.drain(), &0, "Deferred drain after drain_log");
+    }
+
+    #[cfg(feature = "channeled")]
+    #[test]
+    fn channeled() {
+        use super::Channeled;
+
+        thread_local! {static TL: Holder<Channeled, u64, u64> = holder::<Channeled, u64, u64>();}
+        probe_mode::<Channeled>(&TL, "Channeled");
+    }
+
+    #[cfg(feature = "tlcr")]
+    #[test]
+    fn tlcr() {
+        use super::Tlcr;
+
+        thread_local! {static TL: Holder<Tlcr, u64, u64> = holder::<Tlcr, u64, u64>();}
+        probe_mode::<Tlcr>(&TL, "Tlcr");
+    }
+
+    #[test]
+    fn noop() {
+        use super::Noop;
+
+        thread_local! {static TL: Holder<Noop, u64, u64> = holder::<Noop, u64, u64>();}
+
+        let mut control = Control::<Noop, _, _>::new(&TL, || 0, op, op_r);
+        contribute(&control);
+        assert_eq_and_println(&control.probe(), &0, "Noop probe");
+        assert_eq_and_println(&control.drain(), &0, "Noop drain");
+    }
+}
@@ -0,0 +1,225 @@
+//! C API for the contribution of C/C++ components of a mixed-language process to Rust-side aggregation, enabled
+//! with the **"ffi"** feature.
+//!
+//! A [`TlcControl`] is an opaque handle to a [`tlm::channeled::Control`](crate::tlm::channeled::Control) that sums
+//! `double` values per string key. It is created with [`tlc_control_new`] and freed with [`tlc_control_free`].
+//! Threads, whether started from C or Rust, contribute with [`tlc_send`], and the accumulated sums are retrieved as
+//! JSON objects, e.g., `{"hits":3,"latency_ms":12.5}`, with [`tlc_probe_json`], which leaves them in place, or
+//! [`tlc_drain_json`], which resets them. The returned strings must be freed with [`tlc_string_free`].
+//!
+//! The functions are exported unmangled, so they are exported by any `cdylib` or `staticlib` crate that depends on
+//! this crate with the "ffi" feature and references this module, e.g., with `pub use thread_local_collect::ffi::*;`.
+//! The corresponding C declarations are:
+//!
+//! ```c
+//! typedef struct TlcControl TlcControl;
+//!
+//! TlcControl *tlc_control_new(void);
+//! void tlc_control_free(TlcControl *control);
+//! int tlc_send(const TlcControl *control, const char *key, double value);
+//! char *tlc_probe_json(const TlcControl *control);
+//! char *tlc_drain_json(const TlcControl *control);
+//! void tlc_string_free(char *s);
+//! ```
+
+use crate::tlm::{
+    channeled::{Control, Holder},
+    AccessError,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::{c_char, c_double, c_int, CStr, CString},
+    fmt::Write,
+    ptr,
+};
+
+/// Returned by [`tlc_send`] on success.
+pub const TLC_OK: c_int = 0;
+/// Returned by [`tlc_send`] if an argument is null or `key` is not valid UTF-8.
+pub const TLC_INVALID_ARGUMENT: c_int = -1;
+/// Returned by [`tlc_send`] if the current thread is terminating, in which case the value is dropped.
+pub const TLC_ACCESS_ERROR: c_int = -2;
+
+thread_local! {
+    static FFI_TL: Holder<(String, f64)> = const { Holder::new() };
+}
+
+/// Opaque handle to a control object that sums `double` values per string key.
+pub struct TlcControl(Control<(String, f64), HashMap<String, f64>>);
+
+/// Instantiates a [`TlcControl`], which must be freed with [`tlc_control_free`].
+#[no_mangle]
+pub extern "C" fn tlc_control_new() -> *mut TlcControl {
+    let control = Control::new(
+        &FFI_TL,
+        HashMap::new(),
+        |(key, value), acc: &mut HashMap<String, f64>, _| *acc.entry(key).or_default() += value,
+    );
+    Box::into_raw(Box::new(TlcControl(control)))
+}
+
+/// Frees `control`. Values sent with [`tlc_send`] and not yet drained are discarded. Does nothing if `control` is
+/// null.
+///
+/// # Safety
+/// `control` must be null or have been returned by [`tlc_control_new`] and not yet freed, and must not be used
+/// by other threads during or after this call.
+#[no_mangle]
+pub unsafe extern "C" fn tlc_control_free(control: *mut TlcControl) {
+    if !control.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { Box::from_raw(control) });
+    }
+}
+
+/// Adds `value` to the sum for `key`, from the current thread. Returns [`TLC_OK`] on success, or a negative error
+/// code.
+///
+/// # Safety
+/// `control` must be null or a live handle returned by [`tlc_control_new`], and `key` must be null or point to a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tlc_send(
+    control: *const TlcControl,
+    key: *const c_char,
+    value: c_double,
+) -> c_int {
+    if control.is_null() || key.is_null() {
+        return TLC_INVALID_ARGUMENT;
+    }
+    // SAFETY: guaranteed by the caller.
+    let (control, key) = unsafe { (&*control, CStr::from_ptr(key)) };
+    let Ok(key) = key.to_str() else {
+        return TLC_INVALID_ARGUMENT;
+    };
+    match control.0.try_send_data((key.to_owned(), value)) {
+        // Collection may be compiled out, see `crate::gate`.
+        Ok(()) | Err(AccessError::Disabled(_)) => TLC_OK,
+        Err(_) => TLC_ACCESS_ERROR,
+    }
+}
+
+/// Returns the sums of the values sent so far, as a JSON object, without resetting them. The result must be freed
+/// with [`tlc_string_free`]. Returns null if `control` is null.
+///
+/// # Safety
+/// `control` must be null or a live handle returned by [`tlc_control_new`].
+#[no_mangle]
+pub unsafe extern "C" fn tlc_probe_json(control: *const TlcControl) -> *mut c_char {
+    // SAFETY: guaranteed by the caller.
+    let Some(control) = (unsafe { control.as_ref() }) else {
+        return ptr::null_mut();
+    };
+    control.0.drain_tls();
+    into_c_string(control.0.with_acc(to_json))
+}
+
+/// Returns the sums of the values sent so far, as a JSON object, and resets them. The result must be freed with
+/// [`tlc_string_free`]. Returns null if `control` is null.
+///
+/// # Safety
+/// `control` must be null or a live handle returned by [`tlc_control_new`].
+#[no_mangle]
+pub unsafe extern "C" fn tlc_drain_json(control: *const TlcControl) -> *mut c_char {
+    // SAFETY: guaranteed by the caller.
+    let Some(control) = (unsafe { control.as_ref() }) else {
+        return ptr::null_mut();
+    };
+    into_c_string(to_json(&control.0.drain_and_reset(HashMap::new())))
+}
+
+/// Frees a string returned by [`tlc_probe_json`] or [`tlc_drain_json`]. Does nothing if `s` is null.
+///
+/// # Safety
+/// `s` must be null or have been returned by one of the above functions and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn tlc_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+fn into_c_string(json: String) -> *mut c_char {
+    // JSON strings produced by `to_json` escape all control characters, including nul.
+    CString::new(json).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Formats `acc` as a JSON object with sorted keys. Non-finite sums, which JSON cannot represent, are formatted as
+/// `null`.
+fn to_json(acc: &HashMap<String, f64>) -> String {
+    let mut json = String::from("{");
+    for (i, (key, value)) in acc
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .enumerate()
+    {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push('"');
+        for c in key.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                c if c.is_control() => _ = write!(json, "\\u{:04x}", c as u32),
+                c => json.push(c),
+            }
+        }
+        json.push_str("\":");
+        if value.is_finite() {
+            _ = write!(json, "{value}");
+        } else {
+            json.push_str("null");
+        }
+    }
+    json.push('}');
+    json
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::dev_support::assert_eq_and_println;
+    use std::thread;
+
+    fn take_json(s: *mut c_char) -> String {
+        let json = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_owned();
+        unsafe { tlc_string_free(s) };
+        json
+    }
+
+    #[test]
+    fn send_probe_drain() {
+        let control = tlc_control_new();
+        let shared = control as usize;
+
+        thread::scope(|s| {
+            for _ in 0..3 {
+                s.spawn(move || {
+                    let control = shared as *const TlcControl;
+                    unsafe {
+                        assert_eq!(tlc_send(control, c"hits".as_ptr(), 1.0), TLC_OK);
+                        assert_eq!(tlc_send(control, c"ms \"p\"\n".as_ptr(), 2.5), TLC_OK);
+                    }
+                });
+            }
+        });
+
+        let expected = r#"{"hits":3,"ms \"p\"\u000a":7.5}"#;
+        let probed = take_json(unsafe { tlc_probe_json(control) });
+        assert_eq_and_println(&probed.as_str(), &expected, "probe");
+        let drained = take_json(unsafe { tlc_drain_json(control) });
+        assert_eq_and_println(&drained.as_str(), &expected, "drain");
+        let empty = take_json(unsafe { tlc_probe_json(control) });
+        assert_eq_and_println(&empty.as_str(), &"{}", "after drain");
+
+        let invalid = unsafe { tlc_send(control, ptr::null(), 1.0) };
+        assert_eq_and_println(&invalid, &TLC_INVALID_ARGUMENT, "null key");
+        assert!(unsafe { tlc_probe_json(ptr::null()) }.is_null());
+
+        unsafe { tlc_control_free(control) };
+    }
+}
@@ -0,0 +1,71 @@
+//! Optional detection of the use of a control object in a child process created with `fork()`, enabled by the
+//! **"fork-aware"** feature on Unix platforms.
+//!
+//! A child process created with `fork()` inherits a copy of the parent's control objects, including their
+//! accumulated values (which would then be double-counted if collected in both processes) and their mutexes (which
+//! may have been locked by parent threads that do not exist in the child, so that locking them deadlocks).
+//!
+//! When the feature is enabled, each control object records the ID of the process in which it was instantiated and,
+//! before acquiring its state lock, checks that it is still being used in that process. If not, it panics with a
+//! diagnostic message, marking the control object as invalid in the child process, instead of silently
+//! double-counting or deadlocking.
+//!
+//! When the feature is disabled, [`ForkGuard`] is zero-sized and its check is a no-op.
+
+#[cfg(all(unix, feature = "fork-aware"))]
+use std::process;
+
+/// Records the process in which a control object was instantiated.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ForkGuard {
+    #[cfg(all(unix, feature = "fork-aware"))]
+    pid: u32,
+}
+
+impl ForkGuard {
+    /// Instantiates a guard for the current process.
+    pub(crate) fn new() -> Self {
+        Self {
+            #[cfg(all(unix, feature = "fork-aware"))]
+            pid: process::id(),
+        }
+    }
+
+    /// Checks that the current process is the one in which `self` was instantiated.
+    ///
+    /// # Panics
+    /// With the **"fork-aware"** feature on Unix platforms, if the current process is a child process created
+    /// with `fork()` after `self` was instantiated.
+    pub(crate) fn check(&self) {
+        #[cfg(all(unix, feature = "fork-aware"))]
+        if self.pid != process::id() {
+            panic!(
+                "control object used in a child process created with fork(); it was instantiated in process {} and \
+                its state is invalid in process {}",
+                self.pid,
+                process::id()
+            );
+        }
+    }
+}
+
+#[cfg(all(test, unix, feature = "fork-aware"))]
+mod tests {
+    use super::ForkGuard;
+    use std::process;
+
+    #[test]
+    fn same_process() {
+        ForkGuard::new().check();
+    }
+
+    #[test]
+    #[should_panic(expected = "child process created with fork()")]
+    fn other_process() {
+        // Simulates the guard of a control object inherited from a parent process.
+        let guard = ForkGuard {
+            pid: process::id().wrapping_add(1),
+        };
+        guard.check();
+    }
+}
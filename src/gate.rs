@@ -0,0 +1,14 @@
+//! Compile-time gating of collection, controlled by the `thread_local_collect_off` and
+//! `thread_local_collect_release_off` configuration options, analogous to the `max_level_off` and
+//! `release_max_level_off` features of the `log` crate. They are configuration options set with `--cfg`, e.g., in
+//! `RUSTFLAGS`, rather than features, because features are additive: a feature that turns collection off, enabled
+//! by any crate in the dependency graph, would turn it off for all of them, and would break `--all-features` builds.
+
+/// Whether contributions collect data. When `false`, the contribution methods that do not return a value, e.g.,
+/// `send_data` and `aggregate_data`, compile down to empty functions, so collection has no code generation cost,
+/// and the `tlm` control objects behave as if disabled with their `set_enabled` method.
+///
+/// It is `false` if the `thread_local_collect_off` option is set, or if the `thread_local_collect_release_off`
+/// option is set and debug assertions are disabled, as in release builds.
+pub(crate) const COLLECT_ENABLED: bool = !(cfg!(thread_local_collect_off)
+    || cfg!(all(thread_local_collect_release_off, not(debug_assertions))));
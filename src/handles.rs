@@ -0,0 +1,168 @@
+//! Capability handles that restrict the operations available on a control object, so that a clone of the
+//! control object can be handed to other code (e.g., monitoring threads) without giving that code the
+//! power to perform operations beyond its needs.
+//!
+//! It also provides [`FinishedControl`], a control object whose taken accumulated values are post-processed.
+//!
+//! The `acc_map` methods of the control objects and of [`ControlReader`] return a guard that dereferences to a part of
+//! the accumulated value, e.g., a field, so that a focused read does not require exposing the entire accumulated value
+//! to the reader.
+
+use std::{fmt::Debug, ops::Deref, sync::Arc};
+
+/// Read-only handle to a control object of type `C`. It provides the observation methods of the control object
+/// (e.g., `acc`, `with_acc`, `clone_acc`, and `probe_tls`, depending on the module), but not methods that
+/// contribute data to, take from, or reset the accumulated value.
+///
+/// Instances are obtained with the `reader` method of the control object.
+#[derive(Debug)]
+pub struct ControlReader<C>(pub(crate) C);
+
+impl<C: Clone> Clone for ControlReader<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Send-only handle to a control object of type `C`. It provides the methods of the control object that contribute
+/// data from the current thread (e.g., `send_data`, `aggregate_data`, and `with_data_mut`, depending on the
+/// module), but not methods that observe, take from, or reset the accumulated value. It is suitable for handing
+/// to worker code that should not be able to consume the accumulator.
+///
+/// Instances are obtained with the `sender` method of the control object.
+#[derive(Debug)]
+pub struct ControlSender<C>(pub(crate) C);
+
+impl<C: Clone> Clone for ControlSender<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Send-only handle to a control object of type `C` that accepts values of another type and converts them with a
+/// function of type `F` before sending them to the control object, so that a single accumulation can be fed by
+/// subsystems with different payload types. Apart from the conversion, it behaves like a [`ControlSender`].
+///
+/// Instances are obtained with the `map_input` method of the control object or of its [`ControlSender`].
+pub struct MappedSender<C, F> {
+    pub(crate) control: C,
+    pub(crate) f: Arc<F>,
+}
+
+impl<C: Clone, F> Clone for MappedSender<C, F> {
+    fn clone(&self) -> Self {
+        Self {
+            control: self.control.clone(),
+            f: self.f.clone(),
+        }
+    }
+}
+
+impl<C: Debug, F> Debug for MappedSender<C, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MappedSender")
+            .field("control", &self.control)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Control object of type `C` whose methods that take or drain the accumulated value (e.g., `take_acc`,
+/// `drain_and_reset`, and `drain_tls`, depending on the module) post-process it with a *finisher* function of type
+/// `F` before returning it, e.g., to convert sums to averages or to sort results, so that callers do not need to
+/// repeat the transformation. The other methods of the control object are available through [`Deref`].
+///
+/// Instances are obtained with the `with_finisher` method of the control object.
+pub struct FinishedControl<C, F> {
+    pub(crate) control: C,
+    pub(crate) finish: Arc<F>,
+}
+
+impl<C, F> FinishedControl<C, F> {
+    #[cfg(any(
+        feature = "joined",
+        feature = "probed",
+        feature = "channeled",
+        feature = "tlcr"
+    ))]
+    pub(crate) fn new(control: C, finish: F) -> Self {
+        Self {
+            control,
+            finish: Arc::new(finish),
+        }
+    }
+
+    /// Returns the wrapped control object, discarding the finisher.
+    pub fn into_inner(self) -> C {
+        self.control
+    }
+}
+
+impl<C, F> Deref for FinishedControl<C, F> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.control
+    }
+}
+
+impl<C: Clone, F> Clone for FinishedControl<C, F> {
+    fn clone(&self) -> Self {
+        Self {
+            control: self.control.clone(),
+            finish: self.finish.clone(),
+        }
+    }
+}
+
+impl<C: Debug, F> Debug for FinishedControl<C, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FinishedControl")
+            .field("control", &self.control)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Guard that dereferences to the part, selected by a function of type `F`, of the value that its inner guard of type
+/// `G` dereferences to. Returned, as an opaque type, by the `acc_map` methods. The inner guard's lock is held during
+/// the guard's lifetime.
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal"
+))]
+pub(crate) struct MappedGuard<G, F> {
+    guard: G,
+    f: F,
+}
+
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal"
+))]
+impl<G, F> MappedGuard<G, F> {
+    pub(crate) fn new(guard: G, f: F) -> Self {
+        Self { guard, f }
+    }
+}
+
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal"
+))]
+impl<G, F, V> Deref for MappedGuard<G, F>
+where
+    G: Deref,
+    F: Fn(&G::Target) -> &V,
+    V: ?Sized,
+{
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        (self.f)(&self.guard)
+    }
+}
@@ -0,0 +1,80 @@
+//! Bounded history of the accumulated values taken from a control object, enabled with the `with_history` methods
+//! of the `Control` types.
+
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+const POISONED_HISTORY_MUTEX: &str = "poisoned history mutex";
+
+/// Ring buffer that keeps clones of the last `capacity` accumulated values recorded in it.
+pub(crate) struct History<U> {
+    capacity: usize,
+    buf: Mutex<VecDeque<U>>,
+    /// Clones recorded values; stored so that recording does not require a `Clone` bound on `U`.
+    clone: fn(&U) -> U,
+}
+
+impl<U> History<U> {
+    /// Instantiates a history that keeps the last `capacity` recorded values.
+    pub(crate) fn new(capacity: usize) -> Arc<Self>
+    where
+        U: Clone,
+    {
+        Arc::new(Self {
+            capacity,
+            buf: Mutex::new(VecDeque::with_capacity(capacity)),
+            clone: U::clone,
+        })
+    }
+
+    /// Records a clone of `acc`, evicting the oldest value if the history is full.
+    ///
+    /// # Panics
+    /// If the history's mutex is poisoned.
+    pub(crate) fn record(&self, acc: &U) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut buf = self.buf.lock().expect(POISONED_HISTORY_MUTEX);
+        if buf.len() == self.capacity {
+            buf.pop_front();
+        }
+        buf.push_back((self.clone)(acc));
+    }
+
+    /// Returns clones of the recorded values, from oldest to newest.
+    ///
+    /// # Panics
+    /// If the history's mutex is poisoned.
+    pub(crate) fn snapshot(&self) -> Vec<U> {
+        let buf = self.buf.lock().expect(POISONED_HISTORY_MUTEX);
+        buf.iter().map(self.clone).collect()
+    }
+}
+
+impl<U> Debug for History<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self.buf.lock().map(|buf| buf.len()).unwrap_or_default();
+        f.debug_struct("History")
+            .field("capacity", &self.capacity)
+            .field("len", &len)
+            .finish()
+    }
+}
+
+/// Records `acc` in `history`, if any.
+pub(crate) fn record<U>(history: Option<&Arc<History<U>>>, acc: &U) {
+    if let Some(history) = history {
+        history.record(acc);
+    }
+}
+
+/// Returns the values recorded in `history`, or an empty vector if there is none.
+pub(crate) fn snapshot<U>(history: Option<&Arc<History<U>>>) -> Vec<U> {
+    history
+        .map(|history| history.snapshot())
+        .unwrap_or_default()
+}
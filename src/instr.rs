@@ -0,0 +1,223 @@
+//! Internal instrumentation of library operations.
+//!
+//! When the **"tracing"** feature is enabled, operations emit [`tracing`](https://docs.rs/tracing/latest/tracing/)
+//! spans and events with structured fields; otherwise, if the **"log"** feature is enabled, they emit
+//! [`log`](https://docs.rs/log/latest/log/) trace records. In addition, events are sent to the
+//! [`DiagHook`], if any, attached to the `Control` object.
+
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+use crate::diag::OpRecord;
+use crate::diag::{DiagEvent, DiagHook};
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal"
+))]
+use std::thread::ThreadId;
+use std::time::Instant;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Maximum number of characters of the data summaries in [`OpRecord`]s.
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+const SUMMARY_MAX_CHARS: usize = 80;
+
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+thread_local! {
+    /// Whether the current thread is inside [`probing`].
+    static PROBING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Instruments a single invocation of a library operation (e.g., `take_tls`, `probe_tls`, `drain_tls`).
+/// The operation's span, if any, is entered when this object is created and exited when it is dropped, at
+/// which point an event with the number of thread-local values processed and the elapsed time is emitted.
+pub(crate) struct OpInstr<'a> {
+    op: &'static str,
+    count: usize,
+    start: Instant,
+    hook: Option<&'a DiagHook>,
+    /// Whether the completion event is emitted even if no thread-local value was processed.
+    report_empty: bool,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+impl<'a> OpInstr<'a> {
+    /// Starts instrumenting operation `op`, sending events to `hook` if it is present.
+    pub(crate) fn start(op: &'static str, hook: Option<&'a DiagHook>) -> Self {
+        Self {
+            op,
+            count: 0,
+            start: Instant::now(),
+            hook,
+            report_empty: true,
+            #[cfg(feature = "tracing")]
+            _span: tracing::trace_span!("thread_local_collect", op).entered(),
+        }
+    }
+
+    /// Same as [`Self::start`], for one batch of an operation that is repeated in a loop, e.g., by a background
+    /// thread. No completion event is emitted if no thread-local value was processed, so idle batches are not
+    /// reported.
+    #[cfg(feature = "channeled")]
+    pub(crate) fn start_batch(op: &'static str, hook: Option<&'a DiagHook>) -> Self {
+        let mut instr = Self::start(op, hook);
+        instr.report_empty = false;
+        instr
+    }
+
+    /// Records the processing of a thread-local value from thread `tid`.
+    #[cfg(any(
+        feature = "joined",
+        feature = "probed",
+        feature = "channeled",
+        feature = "signal"
+    ))]
+    pub(crate) fn item(&mut self, tid: ThreadId) {
+        self.count += 1;
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(op = self.op, ?tid, "processing thread-local value");
+
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        log::trace!("executing `{}` for key={:?}", self.op, tid);
+
+        if let Some(hook) = self.hook {
+            hook(&DiagEvent::Item {
+                op: self.op,
+                tid: Some(tid),
+            });
+        }
+    }
+
+    /// Records the processing of a thread-local value whose originating thread is not known.
+    #[cfg(feature = "tlcr")]
+    pub(crate) fn cell(&mut self) {
+        self.count += 1;
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(op = self.op, "processing thread-local value");
+
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        log::trace!("executing `{}` for cell {}", self.op, self.count);
+
+        if let Some(hook) = self.hook {
+            hook(&DiagEvent::Item {
+                op: self.op,
+                tid: None,
+            });
+        }
+    }
+}
+
+impl Drop for OpInstr<'_> {
+    fn drop(&mut self) {
+        if self.count == 0 && !self.report_empty {
+            return;
+        }
+        let elapsed = self.start.elapsed();
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            op = self.op,
+            count = self.count,
+            elapsed_us = elapsed.as_micros() as u64,
+            "completed"
+        );
+
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        log::trace!(
+            "completed `{}`: count={}, elapsed={:?}",
+            self.op,
+            self.count,
+            elapsed
+        );
+
+        if let Some(hook) = self.hook {
+            hook(&DiagEvent::Completed {
+                op: self.op,
+                count: self.count,
+                elapsed,
+            });
+        }
+    }
+}
+
+/// Emits a point event for operation `op` on thread `tid`, e.g., the registration of a thread-local variable.
+#[cfg(any(feature = "joined", feature = "probed"))]
+pub(crate) fn event(op: &'static str, tid: ThreadId, hook: Option<&DiagHook>) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(op, ?tid);
+
+    #[cfg(all(feature = "log", not(feature = "tracing")))]
+    log::trace!("`{}` for key={:?}", op, tid);
+
+    if let Some(hook) = hook {
+        hook(&DiagEvent::Point { op, tid });
+    }
+}
+
+/// Runs `f`, which folds thread-local values into a copy of an accumulated value, e.g., for a probe. Invocations of
+/// an op wrapped with [`instrument_op`] made by `f` are not logged, since they do not accumulate data.
+#[cfg(feature = "probed")]
+pub(crate) fn probing<R>(f: impl FnOnce() -> R) -> R {
+    /// Restores the previous value of [`PROBING`], also if `f` panics.
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            PROBING.set(self.0);
+        }
+    }
+
+    let _reset = Reset(PROBING.replace(true));
+    f()
+}
+
+/// Wraps `op` so that each invocation is logged with `logger`, after `op` returns, except for the invocations made
+/// inside [`probing`].
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+pub(crate) fn instrument_op<T, U>(
+    op: Arc<dyn Fn(T, &mut U, ThreadId) + Send + Sync>,
+    logger: impl Fn(&OpRecord) + 'static + Send + Sync,
+) -> Arc<dyn Fn(T, &mut U, ThreadId) + Send + Sync>
+where
+    T: Debug + 'static,
+    U: 'static,
+{
+    let next_seq = AtomicUsize::new(0);
+    Arc::new(move |data, acc, tid| {
+        if PROBING.get() {
+            return op(data, acc, tid);
+        }
+        let summary = summarize(&data);
+        let seq = next_seq.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        op(data, acc, tid);
+        logger(&OpRecord {
+            seq,
+            tid,
+            summary,
+            elapsed: start.elapsed(),
+        });
+    })
+}
+
+/// Returns the [`Debug`] representation of `data`, truncated to [`SUMMARY_MAX_CHARS`] characters.
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+fn summarize<T: Debug>(data: &T) -> String {
+    let mut summary = format!("{data:?}");
+    if let Some((end, _)) = summary.char_indices().nth(SUMMARY_MAX_CHARS) {
+        summary.truncate(end);
+        summary.push('…');
+    }
+    summary
+}
@@ -0,0 +1,413 @@
+//! Cross-process counterpart of [`crate::tlm::channeled`], enabled with the **"ipc"** feature on Unix platforms.
+//! Values are serialized with [bincode](https://docs.rs/bincode) and sent over a Unix domain socket, so that worker
+//! processes, not just threads, can contribute to a single aggregation point.
+//!
+//! The aggregating process instantiates a [`Control`], which binds the socket, and calls
+//! [`Control::start_receiving`] to spawn a background thread that accepts connections and aggregates the values
+//! received on them. Each worker process connects a [`Sender`] to the socket and sends values with
+//! [`Sender::send_data`]. The aggregation operation receives the ID of the sending process instead of a
+//! [`ThreadId`](std::thread::ThreadId).
+//!
+//! ```rust
+//! use std::{thread, time::Duration};
+//! use thread_local_collect::ipc::{Control, Sender};
+//!
+//! let path = std::env::temp_dir().join(format!("tlc-ipc-doc-{}.sock", std::process::id()));
+//! let control = Control::new(&path, 0, |data: u64, acc: &mut u64, _pid| *acc += data).unwrap();
+//! control.start_receiving().unwrap();
+//!
+//! // Typically in a worker process.
+//! let sender = Sender::<u64>::connect(&path).unwrap();
+//! (1..=3).for_each(|i| sender.send_data(&i).unwrap());
+//!
+//! while control.received_count() < 3 {
+//!     thread::sleep(Duration::from_millis(1));
+//! }
+//! assert_eq!(control.take_acc(0), 6);
+//! control.stop_receiving();
+//! ```
+//!
+//! Each value is sent as a frame holding its length as a 4-byte little-endian integer followed by its bincode
+//! serialization, after a 4-byte header holding the ID of the sending process. A connection on which a frame
+//! cannot be read or deserialized is closed, and the values already received on it are kept.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::Debug,
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+    ops::DerefMut,
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+};
+
+const POISONED_CONTROL_MUTEX: &str = "poisoned control mutex";
+const POISONED_SENDER_MUTEX: &str = "poisoned sender mutex";
+
+/// Maximum length of a frame. Longer frames are treated as malformed, so that a corrupted length does not cause
+/// a huge allocation.
+const MAX_FRAME_LEN: u32 = 1 << 26;
+
+/// Socket bound by a [`Control`] and its clones, whose file is removed when the last of them is dropped.
+#[derive(Debug)]
+struct Endpoint {
+    listener: UnixListener,
+    path: PathBuf,
+    /// Set to signal the background receiver thread to stop accepting connections.
+    stop: AtomicBool,
+    /// Set while a background receiver thread is accepting connections.
+    receiving: AtomicBool,
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Controls the aggregation of the values sent by [`Sender`]s, typically in other processes, over a Unix domain
+/// socket.
+///
+/// `T` is the type of the values sent to this object and `U` is the type of the accumulated value.
+pub struct Control<T, U> {
+    endpoint: Arc<Endpoint>,
+    acc: Arc<Mutex<U>>,
+    /// Operation that combines the values received with the accumulated value.
+    #[allow(clippy::type_complexity)]
+    op: Arc<dyn Fn(T, &mut U, u32) + Send + Sync>,
+    /// Number of values aggregated.
+    received: Arc<AtomicU64>,
+}
+
+impl<T, U> Clone for Control<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoint: self.endpoint.clone(),
+            acc: self.acc.clone(),
+            op: self.op.clone(),
+            received: self.received.clone(),
+        }
+    }
+}
+
+impl<T, U: Debug> Debug for Control<T, U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Control")
+            .field("endpoint", &self.endpoint)
+            .field("acc", &self.acc)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, U> Control<T, U> {
+    /// Instantiates a [`Control`] object bound to the Unix domain socket at `path`.
+    ///
+    /// - `path` - path of the socket, which must not exist. It is removed when `self` and its clones are dropped.
+    /// - `acc_base` - initial value for accumulation.
+    /// - `op` - operation that combines the values received, together with the ID of the sending process, with the
+    ///   accumulated value.
+    ///
+    /// # Errors
+    /// Returns an error if the socket cannot be bound, e.g., if `path` exists.
+    pub fn new(
+        path: impl AsRef<Path>,
+        acc_base: U,
+        op: impl Fn(T, &mut U, u32) + 'static + Send + Sync,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let listener = UnixListener::bind(&path)?;
+        Ok(Self {
+            endpoint: Arc::new(Endpoint {
+                listener,
+                path,
+                stop: AtomicBool::new(false),
+                receiving: AtomicBool::new(false),
+            }),
+            acc: Arc::new(Mutex::new(acc_base)),
+            op: Arc::new(op),
+            received: Arc::default(),
+        })
+    }
+
+    /// Returns the path of the socket `self` is bound to.
+    pub fn path(&self) -> &Path {
+        &self.endpoint.path
+    }
+
+    /// Acquires a lock on the accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    fn lock(&self) -> MutexGuard<'_, U> {
+        self.acc.lock().expect(POISONED_CONTROL_MUTEX)
+    }
+
+    /// Invokes `f` on `self`'s accumulated value and returns its result. A lock is held while `f` is invoked.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn with_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V {
+        f(&self.lock())
+    }
+
+    /// Returns a clone of `self`'s accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn clone_acc(&self) -> U
+    where
+        U: Clone,
+    {
+        self.lock().clone()
+    }
+
+    /// Returns `self`'s accumulated value, using `replacement` to replace it.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_acc(&self, replacement: U) -> U {
+        std::mem::replace(self.lock().deref_mut(), replacement)
+    }
+
+    /// Returns the number of values aggregated since `self` was instantiated. As values are sent asynchronously,
+    /// this allows the aggregating process to wait for an expected number of values.
+    pub fn received_count(&self) -> u64 {
+        self.received.load(Ordering::Acquire)
+    }
+
+    /// Spawns a background thread that accepts connections on `self`'s socket and, for each connection, a thread
+    /// that aggregates the values received on it with `self`'s accumulated value. Does nothing if a background
+    /// thread is already accepting connections.
+    ///
+    /// # Errors
+    /// Returns an error if a background thread cannot be spawned.
+    pub fn start_receiving(&self) -> io::Result<()>
+    where
+        T: DeserializeOwned + 'static,
+        U: Send + 'static,
+    {
+        if self.endpoint.receiving.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        self.endpoint.stop.store(false, Ordering::Release);
+        let control = self.clone();
+        let res = thread::Builder::new().spawn(move || {
+            for stream in control.endpoint.listener.incoming() {
+                if control.endpoint.stop.load(Ordering::Acquire) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                // The connection's thread does not hold the endpoint, so the socket file is removed when the
+                // control objects are dropped even if connections remain open.
+                let (acc, op, received) = (
+                    control.acc.clone(),
+                    control.op.clone(),
+                    control.received.clone(),
+                );
+                // A connection whose thread cannot be spawned is dropped, closing it.
+                _ = thread::Builder::new()
+                    .spawn(move || receive(stream, &acc, op.as_ref(), &received));
+            }
+            control.endpoint.receiving.store(false, Ordering::Release);
+        });
+        if let Err(e) = res {
+            self.endpoint.receiving.store(false, Ordering::Release);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Signals the background thread spawned by [`Self::start_receiving`] to stop accepting connections, and wakes
+    /// it up. The threads of the connections already accepted keep receiving until their senders disconnect.
+    pub fn stop_receiving(&self) {
+        if self.endpoint.receiving.load(Ordering::Acquire) {
+            self.endpoint.stop.store(true, Ordering::Release);
+            // Unblocks the pending `accept`; the connection is closed right away.
+            _ = UnixStream::connect(&self.endpoint.path);
+        }
+    }
+
+    /// Returns `true` if a background thread started with [`Self::start_receiving`] is accepting connections. A
+    /// stopped background thread remains active until it observes the stop signal.
+    pub fn is_receiving(&self) -> bool {
+        self.endpoint.receiving.load(Ordering::Acquire)
+    }
+}
+
+/// Aggregates the values received on `stream` with `acc` until `stream` is closed or a frame is malformed.
+///
+/// # Panics
+/// If `acc`'s mutex is poisoned.
+fn receive<T: DeserializeOwned, U>(
+    stream: UnixStream,
+    acc: &Mutex<U>,
+    op: &(dyn Fn(T, &mut U, u32) + Send + Sync),
+    received: &AtomicU64,
+) {
+    let mut reader = BufReader::new(stream);
+    let Ok(pid) = read_u32(&mut reader) else {
+        return;
+    };
+    while let Ok(data) = read_frame::<T>(&mut reader) {
+        op(data, &mut acc.lock().expect(POISONED_CONTROL_MUTEX), pid);
+        received.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Reads a little-endian `u32`.
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Reads a length-prefixed frame and deserializes it.
+fn read_frame<T: DeserializeOwned>(reader: &mut impl Read) -> io::Result<T> {
+    let len = read_u32(reader)?;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame length {len} exceeds the maximum of {MAX_FRAME_LEN}"),
+        ));
+    }
+    let mut buf = vec![0; len as usize];
+    reader.read_exact(&mut buf)?;
+    bincode::deserialize(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Sends values of type `T` to a [`Control`], typically in another process, over its Unix domain socket. Clones
+/// share the connection, so a single sender can be used by all the threads of a worker process.
+pub struct Sender<T> {
+    stream: Arc<Mutex<BufWriter<UnixStream>>>,
+    _t: PhantomData<fn(&T)>,
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            stream: self.stream.clone(),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+impl<T: Serialize> Sender<T> {
+    /// Connects to the socket at `path`, bound by a [`Control`].
+    ///
+    /// # Errors
+    /// Returns an error if the connection fails.
+    pub fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut stream = BufWriter::new(UnixStream::connect(path)?);
+        stream.write_all(&process::id().to_le_bytes())?;
+        Ok(Self {
+            stream: Arc::new(Mutex::new(stream)),
+            _t: PhantomData,
+        })
+    }
+
+    /// Sends `data` to be aggregated by the [`Control`] `self` is connected to.
+    ///
+    /// # Errors
+    /// Returns an error if `data` cannot be serialized or sent, e.g., if the control object has been dropped.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn send_data(&self, data: &T) -> io::Result<()> {
+        let buf =
+            bincode::serialize(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let len = u32::try_from(buf.len())
+            .ok()
+            .filter(|&len| len <= MAX_FRAME_LEN)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("serialized value exceeds the maximum of {MAX_FRAME_LEN} bytes"),
+                )
+            })?;
+        let mut stream = self.stream.lock().expect(POISONED_SENDER_MUTEX);
+        stream.write_all(&len.to_le_bytes())?;
+        stream.write_all(&buf)?;
+        stream.flush()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{Control, Sender};
+    use crate::dev_support::assert_eq_and_println;
+    use std::{
+        collections::HashSet,
+        io::Write,
+        os::unix::net::UnixStream,
+        process, thread,
+        time::{Duration, Instant},
+    };
+
+    fn wait_received<T, U>(control: &Control<T, U>, count: u64) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while control.received_count() < count {
+            assert!(Instant::now() < deadline, "timed out waiting for values");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn send_and_receive() {
+        let path = std::env::temp_dir().join(format!("tlc-ipc-test-{}.sock", process::id()));
+        let control = Control::new(
+            &path,
+            (0, HashSet::new()),
+            |(key, n): (String, u64), acc: &mut (u64, HashSet<u32>), pid| {
+                acc.0 += n * key.len() as u64;
+                acc.1.insert(pid);
+            },
+        )
+        .unwrap();
+        control.start_receiving().unwrap();
+
+        thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| {
+                    let sender = Sender::<(String, u64)>::connect(control.path()).unwrap();
+                    (1..=10).for_each(|n| sender.send_data(&("ab".to_owned(), n)).unwrap());
+                });
+            }
+        });
+
+        // A malformed frame closes its connection without affecting the accumulated value.
+        let mut stream = UnixStream::connect(control.path()).unwrap();
+        stream.write_all(&0u32.to_le_bytes()).unwrap();
+        stream.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        drop(stream);
+
+        wait_received(&control, 20);
+        let acc = control.take_acc((0, HashSet::new()));
+        assert_eq_and_println(&acc.0, &220, "accumulated value");
+        assert_eq_and_println(&acc.1, &HashSet::from([process::id()]), "sender pids");
+
+        control.stop_receiving();
+        drop(control);
+        // The background thread drops its clone of the control object after it observes the stop signal.
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while path.exists() {
+            assert!(Instant::now() < deadline, "socket file removed");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
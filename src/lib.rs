@@ -2,6 +2,91 @@
 #![allow(clippy::type_complexity, clippy::new_without_default)]
 #![doc = include_str!("lib.md")]
 
+pub mod accumulator;
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "tlcr"
+))]
+pub mod activity;
+pub mod alias;
+#[cfg(feature = "probed")]
+pub mod breakdown;
+pub mod diag;
+pub mod export;
+#[cfg(any(
+    all(feature = "restr", feature = "joined"),
+    all(feature = "restr", feature = "probed"),
+    feature = "channeled",
+    feature = "tlcr"
+))]
+pub mod facade;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal"
+))]
+mod fork;
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal",
+    feature = "tlcr"
+))]
+mod gate;
+pub mod handles;
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "tlcr"
+))]
+mod history;
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal",
+    feature = "tlcr"
+))]
+mod instr;
+#[cfg(all(unix, feature = "ipc"))]
+pub mod ipc;
+#[cfg(any(
+    feature = "joined",
+    feature = "probed",
+    feature = "channeled",
+    feature = "signal"
+))]
+mod lock_order;
+#[cfg(feature = "test-util")]
+pub mod mock;
+#[cfg(all(
+    feature = "overhead-stats",
+    any(
+        feature = "joined",
+        feature = "probed",
+        feature = "channeled",
+        feature = "tlcr"
+    )
+))]
+pub mod overhead;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+mod pool;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod registry;
+#[cfg(all(unix, target_has_atomic = "64", feature = "shm"))]
+pub mod shm;
+pub mod sink;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+mod spill;
+
 pub mod tlm;
 
 #[cfg(feature = "tlcr")]
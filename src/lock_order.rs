@@ -0,0 +1,149 @@
+//! Optional detection of lock-order violations, enabled by the **"lock-order"** feature.
+//!
+//! The library's lock-ordering protocol requires that a control object's state lock be acquired before a holder's
+//! data lock, and never the other way around (see [`crate::tlm::probed`]). A violation typically happens when
+//! user code passed to the library (e.g., the accumulation operation `op` or a closure passed to `with_data_mut`)
+//! calls back into a method of the control object, which deadlocks.
+//!
+//! When the feature is enabled, each thread keeps track of the library locks it holds and panics with a diagnostic
+//! message, instead of deadlocking, when it is about to acquire a lock that violates the protocol:
+//! - a control state lock while holding a holder data lock; or
+//! - a lock that the thread already holds.
+//!
+//! When the feature is disabled, [`Tracked`] is a zero-cost wrapper of the lock guard.
+
+use std::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(feature = "lock-order")]
+use std::cell::RefCell;
+
+/// Kinds of locks tracked by this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockKind {
+    /// Lock on the state of a control object.
+    Control,
+    /// Lock on the data of a thread-local holder.
+    #[cfg_attr(not(feature = "probed"), allow(dead_code))]
+    Data,
+}
+
+/// Lock guard of type `G` whose acquisition is tracked by this module.
+#[doc(hidden)]
+pub struct Tracked<G> {
+    // Declared before `_held` so that the lock is released before it stops being tracked.
+    guard: G,
+    #[cfg(feature = "lock-order")]
+    _held: Held,
+}
+
+impl<G> Tracked<G> {
+    /// Acquires the lock of kind `kind` located at `addr` by invoking `acquire`.
+    ///
+    /// # Panics
+    /// With the **"lock-order"** feature, if acquiring the lock would violate the lock-ordering protocol.
+    #[cfg_attr(not(feature = "lock-order"), allow(unused_variables))]
+    pub(crate) fn acquire<L: ?Sized>(
+        kind: LockKind,
+        addr: &L,
+        acquire: impl FnOnce() -> G,
+    ) -> Self {
+        #[cfg(feature = "lock-order")]
+        let held = Held::enter(kind, addr as *const L as *const () as usize);
+        Self {
+            guard: acquire(),
+            #[cfg(feature = "lock-order")]
+            _held: held,
+        }
+    }
+}
+
+impl<G: Debug> Debug for Tracked<G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.guard.fmt(f)
+    }
+}
+
+impl<G: Deref> Deref for Tracked<G> {
+    type Target = G::Target;
+
+    fn deref(&self) -> &Self::Target {
+        self.guard.deref()
+    }
+}
+
+impl<G: DerefMut> DerefMut for Tracked<G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.guard.deref_mut()
+    }
+}
+
+#[cfg(feature = "lock-order")]
+thread_local! {
+    /// Locks held by the current thread.
+    static HELD: RefCell<Vec<(LockKind, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Record of a lock held by the current thread, which is removed when this object is dropped.
+#[cfg(feature = "lock-order")]
+struct Held {
+    kind: LockKind,
+    addr: usize,
+}
+
+#[cfg(feature = "lock-order")]
+impl Held {
+    fn enter(kind: LockKind, addr: usize) -> Self {
+        // Tracking is skipped if the thread-local has already been destroyed, e.g., when a holder is dropped
+        // during thread termination.
+        let violation = HELD
+            .try_with(|held| {
+                let mut held = held.borrow_mut();
+                let violation = held.iter().find_map(|&(k, a)| {
+                    if k == kind && a == addr {
+                        Some(format!(
+                            "attempted to acquire a {kind:?} lock that is already held by this thread"
+                        ))
+                    } else if kind == LockKind::Control && k == LockKind::Data {
+                        Some(
+                            "attempted to acquire a Control lock while holding a Data lock".to_owned(),
+                        )
+                    } else {
+                        None
+                    }
+                });
+                if violation.is_none() {
+                    held.push((kind, addr));
+                }
+                violation
+            })
+            .ok()
+            .flatten();
+
+        if let Some(violation) = violation {
+            panic!(
+                "lock-order violation: {violation}; this would deadlock, which usually means that a closure \
+                passed to the library calls back into a method of the control object"
+            );
+        }
+
+        Self { kind, addr }
+    }
+}
+
+#[cfg(feature = "lock-order")]
+impl Drop for Held {
+    fn drop(&mut self) {
+        let _ = HELD.try_with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(i) = held
+                .iter()
+                .rposition(|&(k, a)| k == self.kind && a == self.addr)
+            {
+                held.remove(i);
+            }
+        });
+    }
+}
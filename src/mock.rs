@@ -0,0 +1,298 @@
+//! Test double for the `Control` types of this library, enabled with the **"test-util"** feature, so that
+//! applications can unit-test their instrumentation without spawning threads or declaring thread-local statics.
+//!
+//! A [`MockControl`] provides the contribution methods of the real `Control` types, i.e., `send_data` as in
+//! [`tlm::channeled`](crate::tlm::channeled) and `aggregate_data`, `absorb_data`, and `add_data` as in the
+//! `restr` and `tlcr` modules. It records every call, with the [`ThreadId`] of the calling thread, and also
+//! aggregates the data immediately into an accumulated value, so that tests can assert either on the exact sequence
+//! of contributions or on their result:
+//!
+//! ```rust
+//! use thread_local_collect::mock::{Call, MockControl};
+//!
+//! fn handle_request(control: &MockControl<u64, u64>, bytes: u64) {
+//!     control.send_data(bytes);
+//! }
+//!
+//! let control = MockControl::new(0, |data, acc: &mut u64, _| *acc += data);
+//! handle_request(&control, 10);
+//! handle_request(&control, 5);
+//!
+//! assert_eq!(control.sent_data(), vec![10, 5]);
+//! assert_eq!(control.calls()[0], Call::send(std::thread::current().id(), 10));
+//! assert_eq!(control.clone_acc(), 15);
+//! ```
+//!
+//! Clones of a [`MockControl`] share its record and accumulated value.
+
+use crate::accumulator::Accumulator;
+use std::{
+    fmt::Debug,
+    mem::{replace, take},
+    ops::{AddAssign, DerefMut},
+    sync::{Arc, Mutex, MutexGuard},
+    thread::{self, ThreadId},
+};
+
+const POISONED_MOCK_MUTEX: &str = "poisoned mock mutex";
+
+/// Contribution method recorded by a [`MockControl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// [`MockControl::send_data`].
+    Send,
+    /// [`MockControl::aggregate_data`], [`MockControl::absorb_data`], or [`MockControl::add_data`].
+    Aggregate,
+}
+
+/// Contribution recorded by a [`MockControl`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call<T> {
+    /// Contribution method.
+    pub kind: CallKind,
+    /// Thread from which the method was called.
+    pub tid: ThreadId,
+    /// Data contributed.
+    pub data: T,
+}
+
+impl<T> Call<T> {
+    /// Instantiates a record of a call to [`MockControl::send_data`].
+    pub fn send(tid: ThreadId, data: T) -> Self {
+        Self {
+            kind: CallKind::Send,
+            tid,
+            data,
+        }
+    }
+
+    /// Instantiates a record of a call to [`MockControl::aggregate_data`] or one of the methods based on it.
+    pub fn aggregate(tid: ThreadId, data: T) -> Self {
+        Self {
+            kind: CallKind::Aggregate,
+            tid,
+            data,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State<T, U> {
+    calls: Vec<Call<T>>,
+    acc: U,
+}
+
+/// Test double for the `Control` types of this library, which records the data of type `T` contributed to it and
+/// aggregates it into an accumulated value of type `U`. See the [module](self) documentation.
+pub struct MockControl<T, U> {
+    state: Arc<Mutex<State<T, U>>>,
+    /// Operation that combines data sent with [`Self::send_data`] with the accumulated value.
+    op: Arc<dyn Fn(T, &mut U, ThreadId) + Send + Sync>,
+}
+
+impl<T, U> Clone for MockControl<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            op: self.op.clone(),
+        }
+    }
+}
+
+impl<T: Debug, U: Debug> Debug for MockControl<T, U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockControl")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, U> MockControl<T, U> {
+    /// Instantiates a [`MockControl`] object.
+    ///
+    /// - `acc_base` - initial value for accumulation.
+    /// - `op` - operation that combines data sent with [`Self::send_data`] with the accumulated value.
+    pub fn new(acc_base: U, op: impl Fn(T, &mut U, ThreadId) + 'static + Send + Sync) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                calls: Vec::new(),
+                acc: acc_base,
+            })),
+            op: Arc::new(op),
+        }
+    }
+
+    /// Acquires a lock on `self`'s state.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    fn lock(&self) -> MutexGuard<'_, State<T, U>> {
+        self.state.lock().expect(POISONED_MOCK_MUTEX)
+    }
+
+    /// Records `data` as sent from the current thread and combines it with the accumulated value with the `op`
+    /// passed to [`Self::new`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn send_data(&self, data: T)
+    where
+        T: Clone,
+    {
+        self.record(CallKind::Send, data, self.op.as_ref());
+    }
+
+    /// Records `data` as aggregated from the current thread and combines it with the accumulated value with `op`.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn aggregate_data(&self, data: T, op: impl FnOnce(T, &mut U, ThreadId))
+    where
+        T: Clone,
+    {
+        self.record(CallKind::Aggregate, data, op);
+    }
+
+    /// Same as [`Self::aggregate_data`], with [`Accumulator::absorb`] as the operation.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn absorb_data(&self, data: T)
+    where
+        T: Clone,
+        U: Accumulator<Dat = T>,
+    {
+        self.aggregate_data(data, |data, acc, tid| acc.absorb(data, tid));
+    }
+
+    /// Same as [`Self::aggregate_data`], with `+=` as the operation.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn add_data(&self, data: T)
+    where
+        T: Clone,
+        U: AddAssign<T>,
+    {
+        self.aggregate_data(data, |data, acc, _| *acc += data);
+    }
+
+    fn record(&self, kind: CallKind, data: T, op: impl FnOnce(T, &mut U, ThreadId))
+    where
+        T: Clone,
+    {
+        let tid = thread::current().id();
+        let mut state = self.lock();
+        state.calls.push(Call {
+            kind,
+            tid,
+            data: data.clone(),
+        });
+        op(data, &mut state.acc, tid);
+    }
+
+    /// Returns the calls recorded so far, in the order they were made.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn calls(&self) -> Vec<Call<T>>
+    where
+        T: Clone,
+    {
+        self.lock().calls.clone()
+    }
+
+    /// Returns the data of the calls recorded so far, in the order they were made, regardless of method and thread.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn sent_data(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.lock()
+            .calls
+            .iter()
+            .map(|call| call.data.clone())
+            .collect()
+    }
+
+    /// Returns the calls recorded so far and clears the record, leaving the accumulated value unchanged.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_calls(&self) -> Vec<Call<T>> {
+        take(&mut self.lock().calls)
+    }
+
+    /// Invokes `f` on `self`'s accumulated value and returns its result.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn with_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V {
+        f(&self.lock().acc)
+    }
+
+    /// Returns a clone of `self`'s accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn clone_acc(&self) -> U
+    where
+        U: Clone,
+    {
+        self.lock().acc.clone()
+    }
+
+    /// Returns `self`'s accumulated value, using `replacement` to replace it, and leaving the record unchanged.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_acc(&self, replacement: U) -> U {
+        replace(&mut self.lock().deref_mut().acc, replacement)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{Call, MockControl};
+    use crate::dev_support::assert_eq_and_println;
+    use std::thread;
+
+    #[test]
+    fn records_calls() {
+        let control = MockControl::new(0, |data, acc: &mut u64, _| *acc += data);
+        let tid = thread::current().id();
+
+        control.send_data(1);
+        control.aggregate_data(2, |data, acc, _| *acc += 10 * data);
+        control.add_data(3);
+
+        let expected = vec![
+            Call::send(tid, 1),
+            Call::aggregate(tid, 2),
+            Call::aggregate(tid, 3),
+        ];
+        assert_eq_and_println(&control.calls(), &expected, "calls");
+        assert_eq_and_println(&control.clone_acc(), &24, "acc");
+
+        let clone = control.clone();
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                clone.send_data(4);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        assert_eq_and_println(&control.sent_data(), &vec![1, 2, 3, 4], "sent data");
+        assert_eq_and_println(
+            &control.take_calls()[3],
+            &Call::send(tid_spawned, 4),
+            "clone call",
+        );
+        assert!(control.calls().is_empty(), "calls cleared");
+        assert_eq_and_println(&control.take_acc(0), &28, "acc after clear");
+    }
+}
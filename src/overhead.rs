@@ -0,0 +1,168 @@
+//! Optional measurement of the latency of contributions, enabled with the **"overhead-stats"** feature. It allows the
+//! collection overhead to be measured in the user's own workload, with the statistics retrieved with the
+//! `overhead_stats` methods of the `Control` types.
+//!
+//! A contribution is timed from the call of the contributing method to its return: `with_data_mut` in the
+//! [`crate::tlm`] direct sub-modules, `send_data` in [`crate::tlm::channeled`], and `with_tl_acc_mut` and the methods
+//! based on it in the [`crate::tlm::restr`] and `tlcr` sub-modules. For the methods that take a closure, the time
+//! spent in the closure is included.
+//!
+//! Recording a latency takes a few relaxed atomic operations and two reads of the monotonic clock, which is itself an
+//! overhead, so the feature should only be enabled for measurement.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Number of buckets of the latency histogram in [`OverheadStats`].
+pub const BUCKETS: usize = 32;
+
+/// Snapshot of the latency statistics of the contributions to a control object.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OverheadStats {
+    /// Number of contributions.
+    pub count: u64,
+    /// Total latency of the contributions.
+    pub total: Duration,
+    /// Maximum latency of a contribution.
+    pub max: Duration,
+    /// Histogram of the latencies in log2 nanosecond buckets: bucket `0` counts the latencies under 2ns, bucket `i`
+    /// counts the latencies in `[2^i, 2^(i+1))` nanoseconds, and the last bucket also counts all longer latencies.
+    pub buckets: [u64; BUCKETS],
+}
+
+impl OverheadStats {
+    /// Returns the mean latency of the contributions, or `None` if there are none.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count).ok()?;
+        (count > 0).then(|| self.total / count)
+    }
+
+    /// Returns an upper bound of the `q`-quantile of the latencies (e.g., `0.99` for the 99th percentile), namely the
+    /// upper bound of the histogram bucket that contains it, capped at [`Self::max`], or `None` if there are no
+    /// contributions.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((self.count as f64 * q.clamp(0.0, 1.0)).ceil() as u64).max(1);
+        let mut seen = 0;
+        let bucket = self
+            .buckets
+            .iter()
+            .position(|&n| {
+                seen += n;
+                seen >= rank
+            })
+            .unwrap_or(BUCKETS - 1);
+        let upper = Duration::from_nanos((2u64 << bucket) - 1);
+        Some(upper.min(self.max))
+    }
+}
+
+/// Records the latencies of the contributions to a control object and its clones.
+#[derive(Debug)]
+pub(crate) struct OverheadRecorder {
+    count: AtomicU64,
+    total_ns: AtomicU64,
+    max_ns: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Default for OverheadRecorder {
+    fn default() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            total_ns: AtomicU64::new(0),
+            max_ns: AtomicU64::new(0),
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl OverheadRecorder {
+    /// Records a contribution with the given latency.
+    fn record(&self, latency: Duration) {
+        let ns = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        let bucket = (ns.max(1).ilog2() as usize).min(BUCKETS - 1);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_ns.fetch_add(ns, Ordering::Relaxed);
+        self.max_ns.fetch_max(ns, Ordering::Relaxed);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns a timer that records the latency of a contribution when dropped.
+    pub(crate) fn timer(&self) -> Timer<'_> {
+        Timer {
+            recorder: self,
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the statistics recorded so far. As the fields are read independently, a snapshot taken while
+    /// contributions are being made may be slightly inconsistent.
+    pub(crate) fn snapshot(&self) -> OverheadStats {
+        OverheadStats {
+            count: self.count.load(Ordering::Relaxed),
+            total: Duration::from_nanos(self.total_ns.load(Ordering::Relaxed)),
+            max: Duration::from_nanos(self.max_ns.load(Ordering::Relaxed)),
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Guard that records the time elapsed since its creation when dropped, so the latency is recorded on every exit
+/// path of the timed method.
+pub(crate) struct Timer<'a> {
+    recorder: &'a OverheadRecorder,
+    start: Instant,
+}
+
+impl Drop for Timer<'_> {
+    fn drop(&mut self) {
+        self.recorder.record(self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{OverheadRecorder, OverheadStats, BUCKETS};
+    use crate::dev_support::assert_eq_and_println;
+    use std::time::Duration;
+
+    #[test]
+    fn buckets_and_quantiles() {
+        let recorder = OverheadRecorder::default();
+        assert_eq_and_println(
+            &recorder.snapshot(),
+            &OverheadStats::default(),
+            "empty snapshot",
+        );
+        assert_eq_and_println(&recorder.snapshot().mean(), &None, "empty mean");
+
+        for ns in [0, 1, 3, 1000, 1 << 40] {
+            recorder.record(Duration::from_nanos(ns));
+        }
+        let stats = recorder.snapshot();
+        assert_eq_and_println(&stats.count, &5, "count");
+        assert_eq_and_println(&stats.max, &Duration::from_nanos(1 << 40), "max");
+        let mut expected = [0; BUCKETS];
+        expected[0] = 2;
+        expected[1] = 1;
+        expected[9] = 1;
+        expected[BUCKETS - 1] = 1;
+        assert_eq_and_println(&stats.buckets, &expected, "buckets");
+        assert_eq_and_println(
+            &stats.quantile(0.5),
+            &Some(Duration::from_nanos(3)),
+            "median",
+        );
+        assert_eq_and_println(
+            &stats.quantile(0.8),
+            &Some(Duration::from_nanos(1023)),
+            "80th percentile",
+        );
+    }
+}
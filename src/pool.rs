@@ -0,0 +1,57 @@
+//! Pool of accumulated values returned to a control object with the `recycle_acc` methods of the `tlm` `Control`
+//! types, for reuse by their `take_acc_recycled` methods.
+
+use crate::accumulator::Recycle;
+use std::{fmt::Debug, sync::Mutex};
+
+const POISONED_POOL_MUTEX: &str = "poisoned pool mutex";
+
+/// Maximum number of pooled values. Values recycled when the pool is full are dropped, so that recycling more
+/// values than are taken does not grow the pool indefinitely.
+const POOL_CAPACITY: usize = 4;
+
+/// Pool of recycled accumulated values, shared by a control object and its clones.
+pub(crate) struct AccPool<U>(Mutex<Vec<U>>);
+
+impl<U> Default for AccPool<U> {
+    fn default() -> Self {
+        Self(Mutex::new(Vec::new()))
+    }
+}
+
+impl<U> AccPool<U> {
+    /// Resets `acc` with [`Recycle::recycle`], without holding the pool's lock, and adds it to the pool unless the
+    /// pool is full.
+    ///
+    /// # Panics
+    /// If the pool's mutex is poisoned.
+    pub(crate) fn put(&self, mut acc: U)
+    where
+        U: Recycle,
+    {
+        acc.recycle();
+        let mut pool = self.0.lock().expect(POISONED_POOL_MUTEX);
+        if pool.len() < POOL_CAPACITY {
+            pool.push(acc);
+        }
+    }
+
+    /// Removes a value from the pool, or returns `U::default()` if the pool is empty.
+    ///
+    /// # Panics
+    /// If the pool's mutex is poisoned.
+    pub(crate) fn take(&self) -> U
+    where
+        U: Default,
+    {
+        let pooled = self.0.lock().expect(POISONED_POOL_MUTEX).pop();
+        pooled.unwrap_or_default()
+    }
+}
+
+impl<U> Debug for AccPool<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self.0.lock().map(|pool| pool.len()).unwrap_or_default();
+        f.debug_struct("AccPool").field("len", &len).finish()
+    }
+}
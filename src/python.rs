@@ -0,0 +1,147 @@
+//! Python bindings, enabled with the **"python"** feature, for Python programs that embed Rust collection in their
+//! threading or multiprocessing workloads.
+//!
+//! [`PyControl`] is exposed to Python as class `Control`, which wraps a
+//! [`tlm::channeled::Control`](crate::tlm::channeled::Control) that sums `float` values per `str` key. Python
+//! worker threads, which are native threads, contribute with `send`, and the accumulated sums are retrieved as a
+//! `dict` with `probe`, which leaves them in place, or `drain`, which resets them. With multiprocessing, each worker
+//! process has its own `Control`, whose drained `dict`s can be returned to the parent process like any other
+//! picklable result.
+//!
+//! The class is added to a PyO3 extension module with [`register`]:
+//!
+//! ```rust,ignore
+//! use pyo3::prelude::*;
+//!
+//! #[pymodule]
+//! fn my_metrics(m: &Bound<'_, PyModule>) -> PyResult<()> {
+//!     thread_local_collect::python::register(m)
+//! }
+//! ```
+//!
+//! ```python
+//! import threading
+//! from my_metrics import Control
+//!
+//! control = Control()
+//! workers = [threading.Thread(target=lambda: control.send("hits", 1.0)) for _ in range(4)]
+//! for w in workers: w.start()
+//! for w in workers: w.join()
+//! assert control.drain() == {"hits": 4.0}
+//! ```
+
+use crate::tlm::{
+    channeled::{Control, Holder},
+    AccessError,
+};
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+use std::collections::HashMap;
+
+thread_local! {
+    static PY_TL: Holder<(String, f64)> = const { Holder::new() };
+}
+
+/// Control object exposed to Python as class `Control`, which sums `float` values per `str` key.
+#[pyclass(name = "Control", frozen)]
+pub struct PyControl(Control<(String, f64), HashMap<String, f64>>);
+
+#[pymethods]
+impl PyControl {
+    /// Instantiates a control object with no accumulated values.
+    #[new]
+    pub fn new() -> Self {
+        Self(Control::new(
+            &PY_TL,
+            HashMap::new(),
+            |(key, value), acc: &mut HashMap<String, f64>, _| *acc.entry(key).or_default() += value,
+        ))
+    }
+
+    /// Adds `value` to the sum for `key`, from the current thread.
+    ///
+    /// # Errors
+    /// Raises `RuntimeError` if the current thread is terminating, in which case the value is dropped.
+    pub fn send(&self, key: String, value: f64) -> PyResult<()> {
+        match self.0.try_send_data((key, value)) {
+            // Collection may be compiled out, see `crate::gate`.
+            Ok(()) | Err(AccessError::Disabled(_)) => Ok(()),
+            Err(e) => Err(PyRuntimeError::new_err(e.to_string())),
+        }
+    }
+
+    /// Returns the sums of the values sent so far, without resetting them.
+    pub fn probe(&self, py: Python<'_>) -> HashMap<String, f64> {
+        py.allow_threads(|| {
+            self.0.drain_tls();
+            self.0.clone_acc()
+        })
+    }
+
+    /// Returns the sums of the values sent so far and resets them.
+    pub fn drain(&self, py: Python<'_>) -> HashMap<String, f64> {
+        py.allow_threads(|| self.0.drain_and_reset(HashMap::new()))
+    }
+}
+
+impl Default for PyControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adds class `Control` (see [`PyControl`]) to the Python module `m`.
+///
+/// # Errors
+/// Returns an error if the class cannot be added to `m`.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyControl>()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{register, PyControl};
+    use crate::dev_support::assert_eq_and_println;
+    use pyo3::{prelude::*, types::PyDict};
+    use std::collections::HashMap;
+
+    #[test]
+    fn send_from_python_threads() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "metrics").unwrap();
+            register(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("metrics", module).unwrap();
+            py.run(
+                cr#"
+import threading
+control = metrics.Control()
+def work(i):
+    control.send("hits", 1.0)
+    control.send("bytes", float(i))
+workers = [threading.Thread(target=work, args=(i,)) for i in range(4)]
+for w in workers: w.start()
+for w in workers: w.join()
+probed = control.probe()
+drained = control.drain()
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+
+            let expected = HashMap::from([("hits".to_owned(), 4.0), ("bytes".to_owned(), 6.0)]);
+            let get = |name| {
+                let value = globals.get_item(name).unwrap().unwrap();
+                value.extract::<HashMap<String, f64>>().unwrap()
+            };
+            assert_eq_and_println(&get("probed"), &expected, "probe");
+            assert_eq_and_println(&get("drained"), &expected, "drain");
+
+            let control = globals.get_item("control").unwrap().unwrap();
+            let control = control.downcast::<PyControl>().unwrap();
+            assert_eq_and_println(&control.get().probe(py), &HashMap::new(), "after drain");
+        });
+    }
+}
@@ -0,0 +1,372 @@
+//! Object-safe [`DynControl`] trait and a global registry of control objects keyed by name, which allows a
+//! framework to enumerate and drain all the control objects registered by its plugins, e.g., at shutdown, without
+//! knowing their data and accumulated value types.
+//!
+//! [`DynControl`] erases the accumulated value type of a control object behind a `Box<dyn Any + Send>`, which the
+//! framework can downcast to the concrete type, if known, or pass along as is. It is implemented by the `Control`
+//! types of the [`crate::tlm`] and `tlcr` sub-modules. The [`crate::tlm`] direct sub-modules require the
+//! accumulated value type to implement [`Default`], whose value replaces the drained value.
+//!
+//! A control object is registered with [`register`] (typically by moving a clone of it into the registry) and
+//! drained with [`drain`] or [`drain_all`]. As with the control objects' own methods, a control object should only
+//! be drained when its participating threads have terminated, and from the thread responsible for
+//! collection/aggregation in the case of the `joined` sub-modules.
+//!
+//! ```rust
+//! use thread_local_collect::{registry, tlm::probed::{Control, Holder}};
+//!
+//! thread_local! {
+//!     static REQUESTS_TL: Holder<u64, u64> = Holder::new();
+//! }
+//!
+//! let control = Control::<u64, u64>::new_additive(&REQUESTS_TL);
+//! registry::register("plugin.requests", control.clone()).unwrap();
+//!
+//! std::thread::spawn(move || control.with_data_mut(|data| *data += 3))
+//!     .join()
+//!     .unwrap();
+//!
+//! for (name, acc) in registry::drain_all() {
+//!     if let Some(requests) = acc.and_then(|acc| acc.downcast::<u64>().ok()) {
+//!         println!("{name}: {requests}");
+//!         assert_eq!(*requests, 3);
+//!     }
+//! }
+//! # registry::unregister("plugin.requests");
+//! ```
+
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    sync::{Mutex, MutexGuard},
+};
+
+#[cfg(feature = "tlcr")]
+use crate::tlcr;
+#[cfg(feature = "channeled")]
+use crate::tlm::channeled;
+#[cfg(feature = "joined")]
+use crate::tlm::joined;
+#[cfg(feature = "probed")]
+use crate::tlm::probed;
+#[cfg(all(feature = "restr", feature = "probed"))]
+use crate::tlm::restr::probed as restr_probed;
+#[cfg(all(feature = "restr", feature = "joined"))]
+use crate::tlm::restr::{joined as restr_joined, simple_joined as restr_simple_joined};
+
+const POISONED_REGISTRY_MUTEX: &str = "poisoned registry mutex";
+
+/// Type-erased accumulated value.
+pub type AnyAcc = Box<dyn Any + Send>;
+
+/// Object-safe interface of a control object, with its accumulated value type erased.
+pub trait DynControl: Send {
+    /// Returns the accumulated value, as the control object's drain operation would, or `None` if the control object
+    /// cannot currently be drained (a `tlcr` control object while clones of it exist).
+    fn drain_any(&mut self) -> Option<AnyAcc>;
+
+    /// Returns the current accumulated value without draining it, or `None` if the control object does not
+    /// support this.
+    fn probe_any(&self) -> Option<AnyAcc>;
+}
+
+#[cfg(feature = "joined")]
+impl<T, U> DynControl for joined::Control<T, U>
+where
+    T: Send + 'static,
+    U: Default + Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        self.take_own_tl();
+        Some(Box::new(self.take_acc(U::default())))
+    }
+
+    fn probe_any(&self) -> Option<AnyAcc> {
+        None
+    }
+}
+
+#[cfg(feature = "probed")]
+impl<T, U> DynControl for probed::Control<T, U>
+where
+    T: Clone + Send + 'static,
+    U: Clone + Default + Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        self.take_tls();
+        Some(Box::new(self.take_acc(U::default())))
+    }
+
+    fn probe_any(&self) -> Option<AnyAcc> {
+        Some(Box::new(self.probe_tls()))
+    }
+}
+
+#[cfg(feature = "channeled")]
+impl<T, U> DynControl for channeled::Control<T, U>
+where
+    T: Send + 'static,
+    U: Clone + Default + Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        Some(Box::new(self.drain_and_reset(U::default())))
+    }
+
+    /// Receives the pending values before returning a clone of the accumulated value.
+    fn probe_any(&self) -> Option<AnyAcc> {
+        self.drain_tls();
+        Some(Box::new(self.clone_acc()))
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "joined"))]
+impl<U> DynControl for restr_joined::Control<U>
+where
+    U: Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        Some(Box::new(self.drain_tls()))
+    }
+
+    fn probe_any(&self) -> Option<AnyAcc> {
+        None
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "joined"))]
+impl<U> DynControl for restr_simple_joined::Control<U>
+where
+    U: Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        Some(Box::new(self.drain_tls()))
+    }
+
+    fn probe_any(&self) -> Option<AnyAcc> {
+        None
+    }
+}
+
+#[cfg(all(feature = "restr", feature = "probed"))]
+impl<U> DynControl for restr_probed::Control<U>
+where
+    U: Clone + Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        Some(Box::new(self.drain_tls()))
+    }
+
+    fn probe_any(&self) -> Option<AnyAcc> {
+        Some(Box::new(self.probe_tls()))
+    }
+}
+
+#[cfg(feature = "tlcr")]
+impl<U> DynControl for tlcr::joined::Control<U>
+where
+    U: Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        self.drain_tls().ok().map(|acc| Box::new(acc) as AnyAcc)
+    }
+
+    fn probe_any(&self) -> Option<AnyAcc> {
+        None
+    }
+}
+
+#[cfg(feature = "tlcr")]
+impl<U> DynControl for tlcr::probed::Control<U>
+where
+    U: Clone + Send + 'static,
+{
+    fn drain_any(&mut self) -> Option<AnyAcc> {
+        self.drain_tls().ok().map(|acc| Box::new(acc) as AnyAcc)
+    }
+
+    fn probe_any(&self) -> Option<AnyAcc> {
+        Some(Box::new(self.probe_tls()))
+    }
+}
+
+/// Indicates the attempt to register a control object under a name that is already registered.
+#[derive(Debug)]
+pub struct NameTakenError(pub String);
+
+impl Display for NameTakenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "a control object is already registered under name {:?}",
+            self.0
+        )
+    }
+}
+
+impl Error for NameTakenError {}
+
+/// Global registry of control objects.
+static REGISTRY: Mutex<BTreeMap<String, Box<dyn DynControl>>> = Mutex::new(BTreeMap::new());
+
+/// Locks the registry.
+fn lock() -> MutexGuard<'static, BTreeMap<String, Box<dyn DynControl>>> {
+    REGISTRY.lock().expect(POISONED_REGISTRY_MUTEX)
+}
+
+/// Registers `control` under `name`.
+///
+/// # Errors
+/// Returns an error if a control object is already registered under `name`.
+///
+/// # Panics
+/// If the registry's mutex is poisoned.
+pub fn register(
+    name: impl Into<String>,
+    control: impl DynControl + 'static,
+) -> Result<(), NameTakenError> {
+    let name = name.into();
+    let mut registry = lock();
+    if registry.contains_key(&name) {
+        return Err(NameTakenError(name));
+    }
+    registry.insert(name, Box::new(control));
+    Ok(())
+}
+
+/// Removes and returns the control object registered under `name`, if any.
+///
+/// # Panics
+/// If the registry's mutex is poisoned.
+pub fn unregister(name: &str) -> Option<Box<dyn DynControl>> {
+    lock().remove(name)
+}
+
+/// Returns the names of the registered control objects, in order.
+///
+/// # Panics
+/// If the registry's mutex is poisoned.
+pub fn names() -> Vec<String> {
+    lock().keys().cloned().collect()
+}
+
+/// Drains the control object registered under `name`. Returns `None` if there is no such control object, or
+/// `Some(None)` if it cannot currently be drained (see [`DynControl::drain_any`]).
+///
+/// # Panics
+/// If the registry's mutex is poisoned.
+pub fn drain(name: &str) -> Option<Option<AnyAcc>> {
+    lock().get_mut(name).map(|control| control.drain_any())
+}
+
+/// Drains all registered control objects, returning their names, in order, with the results of
+/// [`DynControl::drain_any`]. The control objects remain registered.
+///
+/// # Panics
+/// If the registry's mutex is poisoned.
+pub fn drain_all() -> Vec<(String, Option<AnyAcc>)> {
+    lock()
+        .iter_mut()
+        .map(|(name, control)| (name.clone(), control.drain_any()))
+        .collect()
+}
+
+/// Probes all registered control objects, returning their names, in order, with the results of
+/// [`DynControl::probe_any`].
+///
+/// # Panics
+/// If the registry's mutex is poisoned.
+pub fn probe_all() -> Vec<(String, Option<AnyAcc>)> {
+    lock()
+        .iter()
+        .map(|(name, control)| (name.clone(), control.probe_any()))
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    #[cfg(all(feature = "restr", feature = "probed", feature = "channeled"))]
+    #[test]
+    fn register_and_drain() {
+        use super::{drain, names, probe_all, register, unregister};
+        use crate::{
+            dev_support::assert_eq_and_println,
+            tlm::{channeled, restr::probed},
+        };
+        use std::thread;
+
+        thread_local! {
+            static PROBED_TL: probed::Holder<u64> = probed::Holder::new();
+            static CHANNELED_TL: channeled::Holder<&'static str> = const { channeled::Holder::new() };
+        }
+
+        let probed = probed::Control::<u64>::new_additive(&PROBED_TL);
+        let channeled =
+            channeled::Control::new(&CHANNELED_TL, Vec::new(), |s, acc: &mut Vec<_>, _| {
+                acc.push(s)
+            });
+        register("test.probed", probed.clone()).unwrap();
+        register("test.channeled", channeled.clone()).unwrap();
+        assert!(
+            register("test.probed", probed.clone()).is_err(),
+            "duplicate name"
+        );
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                probed.add_data(5);
+                channeled.send_data("a");
+            });
+        });
+
+        let names = names();
+        assert!(names.contains(&"test.probed".to_owned()), "names");
+
+        let probes = probe_all();
+        let probe = probes
+            .into_iter()
+            .find(|(name, _)| name == "test.probed")
+            .and_then(|(_, acc)| acc?.downcast::<u64>().ok());
+        assert_eq_and_println(&probe.as_deref(), &Some(&5), "probe_any");
+
+        let acc = drain("test.channeled").unwrap().unwrap();
+        assert_eq_and_println(
+            &acc.downcast::<Vec<&str>>().ok().as_deref(),
+            &Some(&vec!["a"]),
+            "drain channeled",
+        );
+
+        let mut control = unregister("test.probed").unwrap();
+        let acc = control.drain_any().unwrap();
+        assert_eq_and_println(
+            &acc.downcast::<u64>().ok().as_deref(),
+            &Some(&5),
+            "drain probed",
+        );
+        assert!(drain("test.probed").is_none(), "unregistered");
+        unregister("test.channeled");
+    }
+
+    #[cfg(feature = "tlcr")]
+    #[test]
+    fn tlcr_with_clones() {
+        use super::{drain, register, unregister};
+        use crate::{dev_support::assert_eq_and_println, tlcr::probed::Control};
+
+        let control = Control::<u64>::new_additive();
+        register("test.tlcr", control.clone()).unwrap();
+        control.add_data(1);
+
+        assert!(drain("test.tlcr").unwrap().is_none(), "clone exists");
+        drop(control);
+        let acc = drain("test.tlcr").unwrap().unwrap();
+        assert_eq_and_println(
+            &acc.downcast::<u64>().ok().as_deref(),
+            &Some(&1),
+            "drain tlcr",
+        );
+        unregister("test.tlcr");
+    }
+}
@@ -0,0 +1,317 @@
+//! Accumulation across the processes of a fork-based server, enabled with the **"shm"** feature on Unix platforms.
+//!
+//! A [`Control`] is instantiated in the parent process before it forks its workers. It maps a shared-memory region
+//! with a fixed number of atomic `u64` slots, which the child processes inherit. Each worker process (or each
+//! thread of a worker process) claims a [`Slot`] with [`Control::claim_slot`] and contributes to it with
+//! lock-free atomic operations. The parent process aggregates the slots into its accumulated value with
+//! [`Control::drain_slots`], with the same `op` model as the other `Control` types, except that the operation
+//! receives the index of the slot instead of a [`ThreadId`](std::thread::ThreadId).
+//!
+//! ```rust
+//! use thread_local_collect::shm::Control;
+//!
+//! let control = Control::new(4, 0, |value, acc: &mut u64, _slot| *acc += value).unwrap();
+//!
+//! // Typically in child processes created with `fork()` after `control` was instantiated.
+//! let slot = control.claim_slot().unwrap();
+//! slot.add(3);
+//! slot.add(4);
+//!
+//! control.drain_slots();
+//! assert_eq!(control.take_acc(0), 7);
+//! ```
+//!
+//! Only the slots are shared: the accumulated value is private to each process, so it must be drained and taken
+//! in the parent process. Slots are not released when their processes terminate, so the number of slots must
+//! cover all the processes or threads that contribute over the lifetime of `self`.
+
+use std::{
+    fmt::Debug,
+    io,
+    mem::{replace, size_of},
+    ops::DerefMut,
+    ptr::{self, NonNull},
+    slice,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+const POISONED_CONTROL_MUTEX: &str = "poisoned control mutex";
+
+/// Shared-memory mapping holding the number of claimed slots followed by the slots.
+struct Region {
+    ptr: NonNull<u8>,
+    len: usize,
+    slots: usize,
+}
+
+// SAFETY: the mapping is only accessed through atomics.
+unsafe impl Send for Region {}
+// SAFETY: the mapping is only accessed through atomics.
+unsafe impl Sync for Region {}
+
+impl Region {
+    /// Maps an anonymous shared region for `slots` slots, which is inherited by child processes created with
+    /// `fork()`.
+    fn new(slots: usize) -> io::Result<Self> {
+        let len = size_of::<AtomicU64>()
+            .checked_mul(slots)
+            .and_then(|len| len.checked_add(size_of::<AtomicU64>()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "too many slots"))?;
+        // SAFETY: an anonymous mapping with no requested address has no preconditions.
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let ptr = NonNull::new(ptr.cast()).ok_or_else(io::Error::last_os_error)?;
+        Ok(Self { ptr, len, slots })
+    }
+
+    /// Returns the number of claimed slots, which is stored at the start of the region and may exceed the number
+    /// of slots after failed claims.
+    fn claimed(&self) -> &AtomicUsize {
+        // SAFETY: the region is page-aligned, zero-initialized, at least as large as an `AtomicU64` (which is not
+        // smaller than an `AtomicUsize`), and lives as long as `self`.
+        unsafe { &*self.ptr.as_ptr().cast::<AtomicUsize>() }
+    }
+
+    /// Returns the slots.
+    fn slots(&self) -> &[AtomicU64] {
+        // SAFETY: the slots follow the first `AtomicU64` of the page-aligned, zero-initialized region, which holds
+        // exactly `self.slots` more of them and lives as long as `self`.
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr().cast::<AtomicU64>().add(1), self.slots) }
+    }
+
+    /// Returns the claimed slots.
+    fn claimed_slots(&self) -> &[AtomicU64] {
+        let claimed = self.claimed().load(Ordering::Acquire).min(self.slots);
+        &self.slots()[..claimed]
+    }
+}
+
+impl Drop for Region {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` and `len` describe a mapping created by `Region::new` that is no longer referenced.
+        unsafe {
+            libc::munmap(self.ptr.as_ptr().cast(), self.len);
+        }
+    }
+}
+
+impl Debug for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Region")
+            .field("slots", &self.slots)
+            .field("claimed", &self.claimed().load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// Slot of a [`Control`]'s shared-memory region, claimed by a process or thread for its contributions.
+#[derive(Debug, Clone)]
+pub struct Slot {
+    region: Arc<Region>,
+    index: usize,
+}
+
+impl Slot {
+    fn value(&self) -> &AtomicU64 {
+        &self.region.slots()[self.index]
+    }
+
+    /// Returns the index of `self` in the region, which is passed to the aggregation operation.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Adds `n` to the value of `self`, wrapping around on overflow.
+    pub fn add(&self, n: u64) {
+        self.value().fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Replaces the value of `self` with the maximum of it and `n`, e.g., to track a high-water mark.
+    pub fn max(&self, n: u64) {
+        self.value().fetch_max(n, Ordering::Relaxed);
+    }
+
+    /// Replaces the value of `self` with `n`, e.g., to publish the latest value of a gauge.
+    pub fn store(&self, n: u64) {
+        self.value().store(n, Ordering::Relaxed);
+    }
+}
+
+/// Controls the accumulation of the values of the slots of a shared-memory region, contributed by the processes
+/// forked after this object was instantiated.
+///
+/// `U` is the type of the accumulated value.
+pub struct Control<U> {
+    region: Arc<Region>,
+    acc: Arc<Mutex<U>>,
+    /// Operation that combines the value of a slot with the accumulated value.
+    #[allow(clippy::type_complexity)]
+    op: Arc<dyn Fn(u64, &mut U, usize) + Send + Sync>,
+}
+
+impl<U> Clone for Control<U> {
+    fn clone(&self) -> Self {
+        Self {
+            region: self.region.clone(),
+            acc: self.acc.clone(),
+            op: self.op.clone(),
+        }
+    }
+}
+
+impl<U: Debug> Debug for Control<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Control")
+            .field("region", &self.region)
+            .field("acc", &self.acc)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<U> Control<U> {
+    /// Instantiates a [`Control`] object with a shared-memory region of `slots` slots.
+    ///
+    /// - `slots` - number of slots, i.e., of processes or threads that can contribute.
+    /// - `acc_base` - initial value for accumulation.
+    /// - `op` - operation that combines the value of a slot, together with the slot's index, with the accumulated
+    ///   value.
+    ///
+    /// # Errors
+    /// Returns an error if the shared-memory region cannot be mapped.
+    pub fn new(
+        slots: usize,
+        acc_base: U,
+        op: impl Fn(u64, &mut U, usize) + 'static + Send + Sync,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            region: Arc::new(Region::new(slots)?),
+            acc: Arc::new(Mutex::new(acc_base)),
+            op: Arc::new(op),
+        })
+    }
+
+    /// Claims an unused slot of `self`'s region, in any process that shares it. Returns `None` if all slots have
+    /// been claimed.
+    pub fn claim_slot(&self) -> Option<Slot> {
+        let index = self.region.claimed().fetch_add(1, Ordering::AcqRel);
+        (index < self.region.slots).then(|| Slot {
+            region: self.region.clone(),
+            index,
+        })
+    }
+
+    /// Acquires a lock on the accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    fn lock(&self) -> MutexGuard<'_, U> {
+        self.acc.lock().expect(POISONED_CONTROL_MUTEX)
+    }
+
+    /// Resets the claimed slots to zero and aggregates their values with `self`'s accumulated value. Contributions
+    /// made concurrently are aggregated either by this call or by the next one.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn drain_slots(&self) {
+        let mut acc = self.lock();
+        for (index, slot) in self.region.claimed_slots().iter().enumerate() {
+            (self.op)(slot.swap(0, Ordering::Relaxed), &mut acc, index);
+        }
+    }
+
+    /// Returns the aggregation of the values of the claimed slots with a clone of `self`'s accumulated value,
+    /// without changing the slots or the accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn probe_slots(&self) -> U
+    where
+        U: Clone,
+    {
+        let mut acc = self.lock().clone();
+        for (index, slot) in self.region.claimed_slots().iter().enumerate() {
+            (self.op)(slot.load(Ordering::Relaxed), &mut acc, index);
+        }
+        acc
+    }
+
+    /// Invokes `f` on `self`'s accumulated value and returns its result. A lock is held while `f` is invoked.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn with_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V {
+        f(&self.lock())
+    }
+
+    /// Returns `self`'s accumulated value, using `replacement` to replace it.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_acc(&self, replacement: U) -> U {
+        replace(self.lock().deref_mut(), replacement)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::Control;
+    use crate::dev_support::assert_eq_and_println;
+
+    #[test]
+    fn forked_contributions() {
+        const NPROCS: u64 = 3;
+
+        let control = Control::new(
+            NPROCS as usize,
+            Vec::new(),
+            |value, acc: &mut Vec<_>, slot| acc.push((slot, value)),
+        )
+        .unwrap();
+
+        for i in 1..=NPROCS {
+            // SAFETY: the child process only performs atomic operations on the shared region before exiting.
+            let pid = unsafe { libc::fork() };
+            assert!(pid >= 0, "fork failed");
+            if pid == 0 {
+                let slot = control.claim_slot();
+                if let Some(slot) = slot {
+                    slot.add(i);
+                    slot.add(i);
+                }
+                // SAFETY: `_exit` skips the parent's atexit handlers and destructors, which must not run here.
+                unsafe { libc::_exit(0) };
+            }
+            let mut status = 0;
+            // SAFETY: `pid` is a child of the current process.
+            unsafe { libc::waitpid(pid, &mut status, 0) };
+        }
+
+        assert!(control.claim_slot().is_none(), "all slots claimed");
+        let mut probed = control.probe_slots();
+        probed.sort();
+        assert_eq_and_println(&probed, &vec![(0, 2), (1, 4), (2, 6)], "probe_slots");
+
+        control.drain_slots();
+        control.drain_slots();
+        let mut acc = control.take_acc(Vec::new());
+        acc.sort();
+        let expected = vec![(0, 0), (0, 2), (1, 0), (1, 4), (2, 0), (2, 6)];
+        assert_eq_and_println(&acc, &expected, "drained once");
+    }
+}
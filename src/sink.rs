@@ -0,0 +1,31 @@
+//! Trait that defines a stateful destination for the collected data, e.g., a file writer or a network exporter, as
+//! an alternative to an accumulated value combined with an `op` closure.
+//!
+//! A type that implements [`Sink`] accepts data contributed from threads one item at a time and is finished once
+//! all the data has been accepted, e.g., to flush buffered output:
+//! - The [`crate::tlm`] direct sub-modules use the sink as the accumulated value: their `from_sink` constructors use
+//!   [`Sink::accept`] to combine data from thread-locals with it, and their `take_sink` (or `drain_sink`) methods
+//!   call [`Sink::finish`] on it before returning it.
+//! - The `tlcr` sub-modules keep accumulating per thread, and their `drain_into_sink` methods pass the thread-local
+//!   accumulated values, together with the [`ThreadId`]s of their threads, to a sink instead of reducing them.
+//!
+//! `Vec<(ThreadId, T)>` implements [`Sink<T>`] by recording the data it accepts, which is useful in tests.
+
+use std::thread::ThreadId;
+
+/// Destination for data of type `T` contributed from threads.
+pub trait Sink<T> {
+    /// Accepts `data` contributed from thread `tid`.
+    fn accept(&mut self, data: T, tid: ThreadId);
+
+    /// Called once all the data has been accepted, e.g., to flush buffered output.
+    fn finish(&mut self);
+}
+
+impl<T> Sink<T> for Vec<(ThreadId, T)> {
+    fn accept(&mut self, data: T, tid: ThreadId) {
+        self.push((tid, data));
+    }
+
+    fn finish(&mut self) {}
+}
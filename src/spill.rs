@@ -0,0 +1,50 @@
+//! Overflow hook that hands chunks of the accumulated value to a user callback, e.g., to spill them to disk, when
+//! the accumulated value exceeds a configured size. Enabled with the `with_spill` methods of the `Control` types.
+
+use std::{mem::replace, sync::Arc};
+
+/// Spills the accumulated value when its size exceeds `max_size`.
+pub(crate) struct Spill<U> {
+    max_size: usize,
+    size: Box<dyn Fn(&U) -> usize + Send + Sync>,
+    spill: Box<dyn Fn(U) + Send + Sync>,
+    zero: Box<dyn Fn() -> U + Send + Sync>,
+}
+
+impl<U> Spill<U> {
+    /// Instantiates a spill hook.
+    ///
+    /// - `max_size` - size above which the accumulated value is spilled.
+    /// - `size` - computes the size of an accumulated value, in units chosen by the user.
+    /// - `spill` - receives the spilled accumulated value.
+    /// - `zero` - produces the value that replaces the spilled accumulated value.
+    pub(crate) fn new(
+        max_size: usize,
+        size: impl Fn(&U) -> usize + 'static + Send + Sync,
+        spill: impl Fn(U) + 'static + Send + Sync,
+        zero: impl Fn() -> U + 'static + Send + Sync,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            max_size,
+            size: Box::new(size),
+            spill: Box::new(spill),
+            zero: Box::new(zero),
+        })
+    }
+
+    /// Spills `acc`, replacing it with the zero value, if its size exceeds the maximum.
+    pub(crate) fn check(&self, acc: &mut U) {
+        if (self.size)(acc) > self.max_size {
+            let chunk = replace(acc, (self.zero)());
+            (self.spill)(chunk);
+        }
+    }
+}
+
+/// Spills `acc` per `spill`, if any.
+#[cfg(any(feature = "joined", feature = "probed"))]
+pub(crate) fn check<U>(spill: Option<&Arc<Spill<U>>>, acc: &mut U) {
+    if let Some(spill) = spill {
+        spill.check(acc);
+    }
+}
@@ -20,12 +20,31 @@
 //!
 //! See another example at [`examples/tlcr_joined_map_accumulator`](https://github.com/pvillela/rust-thread-local-collect/blob/main/examples/tlcr_joined_map_accumulator.rs).
 
+#[cfg(feature = "overhead-stats")]
+use crate::overhead::{OverheadRecorder, OverheadStats};
+use crate::{
+    accumulator::Accumulator,
+    activity::{self, ActivityTracker, ThreadActivity},
+    diag::{DiagEvent, DiagHook},
+    gate::COLLECT_ENABLED,
+    handles::{ControlSender, FinishedControl},
+    history::{self, History},
+    instr::OpInstr,
+    sink::Sink,
+    tlcr::{
+        reduce::{reduce_grouped, Grouped, Grouping},
+        Reduction,
+    },
+};
 use std::{
     cell::RefCell,
+    collections::HashMap,
     fmt::Debug,
     mem::replace,
+    ops::{Add, AddAssign},
     sync::Arc,
     thread::{self, ThreadId},
+    time::Duration,
 };
 use thiserror::Error;
 use thread_local::ThreadLocal;
@@ -48,11 +67,29 @@ where
     U: Send,
 {
     /// Keeps track of registered threads and accumulated value.
-    state: Arc<ThreadLocal<RefCell<U>>>,
+    state: Arc<ThreadLocal<Grouped<RefCell<U>>>>,
     /// Produces a zero value of type `U`, which is needed to obtain consistent aggregation results.
     acc_zero: Arc<dyn Fn() -> U + Send + Sync>,
     /// Binary operation that reduces two accumulated values into one.
     op_r: Arc<dyn Fn(U, U) -> U + Send + Sync>,
+    /// Optional diagnostics hook.
+    diag: Option<DiagHook>,
+    /// Number of threads for which space is preallocated in the state.
+    capacity: usize,
+    /// Optional bounded history of drained accumulated values.
+    history: Option<Arc<History<U>>>,
+    /// Optional tracker of the contribution activity of each thread.
+    activity: Option<Arc<ActivityTracker>>,
+    /// Recorder of the latencies of contributions.
+    #[cfg(feature = "overhead-stats")]
+    overhead: Arc<OverheadRecorder>,
+    /// Optional per-thread replacement for `acc_zero` for the thread-local accumulated values.
+    #[allow(clippy::type_complexity)]
+    tl_zero: Option<Arc<dyn Fn(ThreadId) -> U + Send + Sync>>,
+    /// Strategy used to reduce the thread-local values.
+    reduction: Reduction,
+    /// Optional assignment of threads to groups for grouped reduction.
+    grouping: Option<Grouping>,
 }
 
 impl<U> Clone for Control<U>
@@ -64,6 +101,15 @@ where
             state: self.state.clone(),
             op_r: self.op_r.clone(),
             acc_zero: self.acc_zero.clone(),
+            diag: self.diag.clone(),
+            capacity: self.capacity,
+            history: self.history.clone(),
+            activity: self.activity.clone(),
+            #[cfg(feature = "overhead-stats")]
+            overhead: self.overhead.clone(),
+            tl_zero: self.tl_zero.clone(),
+            reduction: self.reduction,
+            grouping: self.grouping.clone(),
         }
     }
 }
@@ -94,56 +140,396 @@ where
             state: Arc::new(ThreadLocal::new()),
             acc_zero: Arc::new(acc_zero),
             op_r: Arc::new(op_r),
+            diag: None,
+            capacity: 0,
+            history: None,
+            activity: None,
+            #[cfg(feature = "overhead-stats")]
+            overhead: Arc::default(),
+            tl_zero: None,
+            reduction: Reduction::Fold,
+            grouping: None,
+        }
+    }
+
+    /// Instantiates a [`Control`] object whose accumulation is defined by the [`Accumulator`] implementation of `U`,
+    /// with [`Accumulator::zero`] as `acc_zero` and [`Accumulator::merge`] as `op_r`. See [`Control::new`].
+    pub fn from_accumulator() -> Self
+    where
+        U: Accumulator + 'static,
+    {
+        Self::new(U::zero, U::merge)
+    }
+
+    /// Instantiates a [`Control`] object for the common case of additive accumulations, e.g., counting or summing,
+    /// with `U::default` as `acc_zero` and `+` as `op_r`. See [`Control::new`].
+    pub fn new_additive() -> Self
+    where
+        U: Default + Add<Output = U> + 'static,
+    {
+        Self::new(U::default, |acc1, acc2| acc1 + acc2)
+    }
+
+    /// Instantiates a [`Control`] object for accumulators that implement [`Extend`] with their own items, e.g.,
+    /// [`Vec`] or [`HashMap`], with `U::default` as `acc_zero` and [`Extend::extend`] as `op_r`. Data can then be
+    /// contributed with [`Self::extend_data`] without an `op` closure. See [`Control::new`].
+    pub fn from_extend() -> Self
+    where
+        U: Default + Extend<U::Item> + IntoIterator + 'static,
+    {
+        Self::new(U::default, |mut acc1, acc2| {
+            acc1.extend(acc2);
+            acc1
+        })
+    }
+
+    /// Attaches a diagnostics hook that receives structured events about `self`'s internal operations.
+    /// See [`crate::diag`].
+    pub fn with_diagnostics(mut self, hook: impl Fn(&DiagEvent) + 'static + Send + Sync) -> Self {
+        self.diag = Some(Arc::new(hook));
+        self
+    }
+
+    /// Enables a bounded history that keeps clones of the last `capacity` accumulated values drained from `self`,
+    /// which can be retrieved with [`Self::history`]. The history is shared by all clones of `self`.
+    pub fn with_history(mut self, capacity: usize) -> Self
+    where
+        U: Clone,
+    {
+        self.history = Some(History::new(capacity));
+        self
+    }
+
+    /// Enables the tracking of the contribution activity of each thread, which can be retrieved with
+    /// [`Self::thread_activity`]. See [`crate::activity`].
+    pub fn with_activity_tracking(mut self) -> Self {
+        self.activity = Some(Arc::default());
+        self
+    }
+
+    /// Uses `tl_zero` instead of `acc_zero` (see [`Self::new`]) to produce the initial value of each thread's local
+    /// accumulated value, given the [`ThreadId`] of the thread, e.g., to pre-size or label it per worker. The values
+    /// produced by `tl_zero` are reduced into the accumulated value, so they must behave as zero values for
+    /// consistent aggregation results.
+    pub fn with_tl_init(mut self, tl_zero: impl Fn(ThreadId) -> U + 'static + Send + Sync) -> Self {
+        self.tl_zero = Some(Arc::new(tl_zero));
+        self
+    }
+
+    /// Sets the strategy used to reduce the thread-local values into a single accumulated value, which defaults to
+    /// [`Reduction::Fold`].
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    /// Reduces the thread-local values in groups (e.g., by NUMA node or core group) when they are drained or probed,
+    /// each group on its own thread, before reducing the group results. This is a grouped reduction, not sharding
+    /// of the state: the thread-local values are still held in a single `ThreadLocal` and contributions are not
+    /// affected, but the reduction of each group can run close to the memory of its values, e.g., to avoid
+    /// cross-socket traffic when reducing large accumulated values of threads on multiple sockets.
+    ///
+    /// - `group_of` - returns the group of the current thread; it is invoked when a thread first accesses its
+    ///   thread-local value, so it should reflect the thread's placement at that time (e.g., threads pinned to a
+    ///   NUMA node with an affinity library). A thread that reuses the thread-local value of a terminated thread
+    ///   keeps that value's group.
+    /// - `enter_group` - invoked with the group number on a dedicated scoped thread before that thread reduces the
+    ///   values of the group, e.g., to pin itself to the group's NUMA node.
+    ///
+    /// The values of each group are reduced with the strategy set with [`Self::with_reduction`] and the per-group
+    /// results are then reduced in group order, so the reduction operation must be commutative as well as
+    /// associative. Thread-local values created before this call are assigned to group 0, so this method should be
+    /// called immediately after `self` is instantiated.
+    pub fn with_grouped_reduction(
+        mut self,
+        group_of: impl Fn() -> usize + 'static + Send + Sync,
+        enter_group: impl Fn(usize) + 'static + Send + Sync,
+    ) -> Self {
+        self.grouping = Some(Grouping {
+            group_of: Arc::new(group_of),
+            enter_group: Arc::new(enter_group),
+        });
+        self
+    }
+
+    /// Produces the initial value of the current thread's local accumulated value.
+    fn tl_zero(&self) -> U {
+        match &self.tl_zero {
+            Some(tl_zero) => tl_zero(thread::current().id()),
+            None => (self.acc_zero)(),
         }
     }
 
+    /// Returns the last accumulated values drained from `self`, from oldest to newest, if history is enabled
+    /// with [`Self::with_history`], or an empty vector otherwise.
+    ///
+    /// # Panics
+    /// If the history's mutex is poisoned.
+    pub fn history(&self) -> Vec<U> {
+        history::snapshot(self.history.as_ref())
+    }
+
+    /// Returns the contribution activity of each thread that has contributed to `self` since activity tracking was
+    /// enabled with [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn thread_activity(&self) -> HashMap<ThreadId, ThreadActivity> {
+        activity::snapshot(self.activity.as_ref())
+    }
+
+    /// Returns the number of contributions made by each thread to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn contribution_counts(&self) -> HashMap<ThreadId, u64> {
+        activity::counts(self.activity.as_ref())
+    }
+
+    /// Returns the threads that have contributed to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`] but have not contributed in the last `since`, e.g., so that a supervisor can
+    /// detect hung workers, or an empty vector if tracking is not enabled. Threads that have terminated are included,
+    /// since the tracker does not observe thread termination.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn idle_threads(&self, since: Duration) -> Vec<ThreadId> {
+        activity::idle(self.activity.as_ref(), since)
+    }
+
+    /// Returns the latency statistics of the contributions made to `self` and its clones. See [`crate::overhead`].
+    /// Requires the **"overhead-stats"** feature.
+    #[cfg(feature = "overhead-stats")]
+    pub fn overhead_stats(&self) -> OverheadStats {
+        self.overhead.snapshot()
+    }
+
+    /// Preallocates space in `self`'s state for `capacity` participating threads, avoiding repeated internal
+    /// reallocation when the number of participating threads is known up front. The capacity is retained when
+    /// the state is replaced by [`Control::drain_tls`].
+    ///
+    /// Thread-local values already collected by `self` are discarded, so this method should be called immediately
+    /// after `self` is instantiated.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.state = Arc::new(ThreadLocal::with_capacity(capacity));
+        self
+    }
+
     /// Called from a thread to access the thread's local accumulated value.
     pub fn with_tl_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V {
-        let cell = self.state.get_or(|| RefCell::new((self.acc_zero)()));
+        let cell = self.state.get_or(|| Grouped {
+            group: Grouping::current(&self.grouping),
+            tid: thread::current().id(),
+            cell: RefCell::new(self.tl_zero()),
+        });
         let u = cell.borrow();
         f(&u)
     }
 
-    /// Called from a thread to mutably access the thread's local accumulated value.
+    /// Called from a thread to mutably access the thread's local accumulated value. If collection is compiled out
+    /// with the `thread_local_collect_off` or `thread_local_collect_release_off` option, `f` is instead invoked on a
+    /// scratch zero value that is then dropped.
     pub fn with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> V {
-        let cell = self.state.get_or(|| RefCell::new((self.acc_zero)()));
+        if !COLLECT_ENABLED {
+            return f(&mut self.tl_zero());
+        }
+        #[cfg(feature = "overhead-stats")]
+        let _timer = self.overhead.timer();
+        activity::record(self.activity.as_ref(), thread::current().id());
+        let cell = self.state.get_or(|| Grouped {
+            group: Grouping::current(&self.grouping),
+            tid: thread::current().id(),
+            cell: RefCell::new(self.tl_zero()),
+        });
         let mut u = cell.borrow_mut();
         f(&mut u)
     }
 
-    /// Called from a thread to aggregate data with aggregation operation `op`.
+    /// Called from a thread to aggregate data with aggregation operation `op`. Does nothing if collection is
+    /// compiled out with the `thread_local_collect_off` or `thread_local_collect_release_off` option.
     pub fn aggregate_data<T>(&self, data: T, op: impl FnOnce(T, &mut U, ThreadId)) {
+        if !COLLECT_ENABLED {
+            return;
+        }
         self.with_tl_acc_mut(|acc| op(data, acc, thread::current().id()))
     }
 
+    /// Called from a thread to absorb data into the thread's local accumulated value with
+    /// [`Accumulator::absorb`].
+    pub fn absorb_data(&self, data: U::Dat)
+    where
+        U: Accumulator,
+    {
+        self.aggregate_data(data, |data, acc, tid| acc.absorb(data, tid))
+    }
+
+    /// Called from a thread to add data to the thread's local accumulated value with `+=`.
+    pub fn add_data<T>(&self, data: T)
+    where
+        U: AddAssign<T>,
+    {
+        self.aggregate_data(data, |data, acc, _| *acc += data)
+    }
+
+    /// Called from a thread to extend the thread's local accumulated value with a `(ThreadId, T)` pair, where the
+    /// [`ThreadId`] is the current thread's.
+    pub fn extend_data<T>(&self, data: T)
+    where
+        U: Extend<(ThreadId, T)>,
+    {
+        self.aggregate_data(data, |data, acc, tid| acc.extend([(tid, data)]))
+    }
+
+    /// Wraps `self` in a [`FinishedControl`] whose [`drain_tls`](FinishedControl::drain_tls) method returns the
+    /// accumulation of the thread-local values post-processed with `finish`.
+    pub fn with_finisher<V, F>(self, finish: F) -> FinishedControl<Self, F>
+    where
+        F: Fn(U) -> V,
+    {
+        FinishedControl::new(self, finish)
+    }
+
+    /// Returns a send-only handle to `self`. Note that [`Control::drain_tls`] fails while the handle, which holds a
+    /// clone of `self`, is alive on another thread.
+    pub fn sender(&self) -> ControlSender<Self> {
+        ControlSender(self.clone())
+    }
+
+    /// Returns whether `self` and `other` are the same control object or clones of each other, i.e., whether they
+    /// aggregate into the same accumulated value.
+    pub fn same_control(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+
+    /// Replaces `state` with a new state with space preallocated for `capacity` threads and returns the old one.
+    ///
+    /// # Errors
+    /// Returns an error, leaving `state` unchanged, if it is shared, i.e., if any thread other than the current one
+    /// holds a clone of the control object.
+    fn take_state(
+        state: &mut Arc<ThreadLocal<Grouped<RefCell<U>>>>,
+        capacity: usize,
+    ) -> Result<ThreadLocal<Grouped<RefCell<U>>>, ActiveThreadLocalsError> {
+        let old_state = replace(state, Arc::new(ThreadLocal::with_capacity(capacity)));
+        Arc::try_unwrap(old_state).map_err(|old_state| {
+            _ = replace(state, old_state); // put it back
+            ActiveThreadLocalsError
+        })
+    }
+
+    /// Same as [`Self::drain_tls`], except that the thread-local accumulated values are passed to `sink`, together
+    /// with the [`ThreadId`]s of their threads, instead of being reduced, after which [`Sink::finish`] is called on
+    /// `sink`, e.g., to write each thread's values to a file.
+    ///
+    /// # Errors
+    /// - Returns an error if any thread, other than the thread where this function is called from,
+    ///   holds a clone of `self`. In this case, the state of `self` is left unchanged and `sink` is not used.
+    pub fn drain_into_sink(
+        &mut self,
+        sink: &mut impl Sink<U>,
+    ) -> Result<(), ActiveThreadLocalsError> {
+        let mut instr = OpInstr::start("drain_into_sink", self.diag.as_ref());
+        let unwr_state = Self::take_state(&mut self.state, self.capacity)?;
+        for x in unwr_state {
+            instr.cell();
+            sink.accept(x.cell.into_inner(), x.tid);
+        }
+        sink.finish();
+        Ok(())
+    }
+
     /// Returns the accumulation of the thread-local values, restoring `self`'s state to what it was when
     /// it was instantiated with [`Control::new`].
     ///
     /// # Errors
     /// - Returns an error if any thread, other than the thread where this function is called from,
-    /// holds a clone of `self`. In this case, the state of `self` is left unchanged.
+    ///   holds a clone of `self`. In this case, the state of `self` is left unchanged.
     pub fn drain_tls(&mut self) -> Result<U, ActiveThreadLocalsError> {
-        let state = replace(&mut self.state, Arc::new(ThreadLocal::new()));
-        let unwr_state = match Arc::try_unwrap(state) {
-            Ok(unwr_state) => unwr_state,
-            Err(state) => {
-                _ = replace(&mut self.state, state); // put it back
-                return Err(ActiveThreadLocalsError);
-            }
-        };
-        let res = unwr_state
-            .into_iter()
-            .map(|x| x.into_inner())
-            .fold((self.acc_zero)(), self.op_r.as_ref());
+        let mut instr = OpInstr::start("drain_tls", self.diag.as_ref());
+        let unwr_state = Self::take_state(&mut self.state, self.capacity)?;
+        let values = unwr_state.into_iter().map(|x| {
+            instr.cell();
+            (x.group, x.cell.into_inner())
+        });
+        let res = reduce_grouped(
+            values,
+            self.acc_zero.as_ref(),
+            self.op_r.as_ref(),
+            self.reduction,
+            self.grouping.as_ref(),
+        );
+        history::record(self.history.as_ref(), &res);
         Ok(res)
     }
 }
 
+impl<U, F, V> FinishedControl<Control<U>, F>
+where
+    U: Send,
+    F: Fn(U) -> V,
+{
+    /// Returns the accumulation of the thread-local values post-processed with the finisher.
+    /// See [`Control::drain_tls`].
+    ///
+    /// # Errors
+    /// - Returns an error if any thread, other than the thread where this function is called from,
+    ///   holds a clone of the control object. In this case, the state of the control object is left unchanged.
+    pub fn drain_tls(&mut self) -> Result<V, ActiveThreadLocalsError> {
+        self.control.drain_tls().map(self.finish.as_ref())
+    }
+}
+
+impl<U> ControlSender<Control<U>>
+where
+    U: Send,
+{
+    /// Called from a thread to mutably access the thread's local accumulated value.
+    /// See [`Control::with_tl_acc_mut`].
+    pub fn with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> V {
+        self.0.with_tl_acc_mut(f)
+    }
+
+    /// Called from a thread to aggregate data with aggregation operation `op`.
+    /// See [`Control::aggregate_data`].
+    pub fn aggregate_data<T>(&self, data: T, op: impl FnOnce(T, &mut U, ThreadId)) {
+        self.0.aggregate_data(data, op)
+    }
+
+    /// Called from a thread to absorb data into the thread's local accumulated value.
+    /// See [`Control::absorb_data`].
+    pub fn absorb_data(&self, data: U::Dat)
+    where
+        U: Accumulator,
+    {
+        self.0.absorb_data(data)
+    }
+
+    /// Called from a thread to add data to the thread's local accumulated value.
+    /// See [`Control::add_data`].
+    pub fn add_data<T>(&self, data: T)
+    where
+        U: AddAssign<T>,
+    {
+        self.0.add_data(data)
+    }
+
+    /// Called from a thread to extend the thread's local accumulated value with a `(ThreadId, T)` pair.
+    /// See [`Control::extend_data`].
+    pub fn extend_data<T>(&self, data: T)
+    where
+        U: Extend<(ThreadId, T)>,
+    {
+        self.0.extend_data(data)
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::{ActiveThreadLocalsError, Control};
-    use crate::dev_support::assert_eq_and_println;
+    use crate::{accumulator::per_thread, dev_support::assert_eq_and_println};
     use std::{
         collections::HashMap,
         fmt::Debug,
@@ -326,4 +712,215 @@ mod tests {
             "error expected due to active thread(s)"
         );
     }
+
+    #[test]
+    fn new_additive() {
+        let mut control = Control::<u64>::new_additive();
+
+        control.add_data(1u64);
+        thread::scope(|s| {
+            let sender = control.sender();
+            s.spawn(move || sender.add_data(2u64));
+        });
+
+        assert_eq_and_println(&control.drain_tls().unwrap(), &3, "drain_tls new_additive");
+    }
+
+    #[test]
+    fn from_extend() {
+        let mut control = Control::<HashMap<ThreadId, Vec<u64>>>::from_extend();
+
+        control.extend_data(vec![1u64]);
+        let tid_spawned = thread::scope(|s| {
+            let sender = control.sender();
+            s.spawn(move || {
+                sender.extend_data(vec![2u64]);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+
+        let expected = HashMap::from([(thread::current().id(), vec![1]), (tid_spawned, vec![2])]);
+        assert_eq_and_println(
+            &control.drain_tls().unwrap(),
+            &expected,
+            "drain_tls from_extend",
+        );
+    }
+
+    #[test]
+    fn history() {
+        let mut control = Control::<u64>::new_additive().with_history(2);
+        assert_eq_and_println(&control.history(), &vec![], "empty history");
+
+        for i in 1..=3 {
+            thread::scope(|s| {
+                let sender = control.sender();
+                s.spawn(move || sender.add_data(i));
+            });
+            control.drain_tls().unwrap();
+        }
+        assert_eq_and_println(&control.history(), &vec![2, 3], "history after drains");
+    }
+
+    #[test]
+    fn contribution_counts() {
+        let mut control = Control::<u64>::new_additive().with_activity_tracking();
+
+        let tid_count_pairs = thread::scope(|s| {
+            let hs = (1..=3)
+                .map(|n| {
+                    let sender = control.sender();
+                    s.spawn(move || {
+                        for _ in 0..n {
+                            sender.add_data(1);
+                        }
+                        (thread::current().id(), n)
+                    })
+                })
+                .collect::<Vec<_>>();
+            hs.into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let expected = HashMap::from_iter(tid_count_pairs);
+        assert_eq_and_println(
+            &control.contribution_counts(),
+            &expected,
+            "contribution_counts",
+        );
+        assert_eq_and_println(&control.drain_tls().unwrap(), &6, "drain_tls");
+    }
+
+    #[test]
+    fn per_thread_ops() {
+        let per_thread = per_thread::<u32>();
+        let mut control = Control::new(per_thread.zero, per_thread.op_r);
+
+        control.aggregate_data(1, per_thread.op);
+        control.aggregate_data(2, per_thread.op);
+        let tid_spawned = thread::scope(|s| {
+            let control = control.clone();
+            s.spawn(move || {
+                control.aggregate_data(3, per_thread.op);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+
+        let expected = HashMap::from([(thread::current().id(), 2), (tid_spawned, 3)]);
+        let acc = control.drain_tls().unwrap();
+        assert_eq_and_println(&acc.into_inner(), &expected, "latest value per thread");
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut control = Control::new(HashMap::new, op_r).with_capacity(NTHREADS);
+
+        let tid_map_pairs = thread::scope(|s| {
+            let hs = (0..NTHREADS as i32)
+                .map(|i| {
+                    let control = control.clone();
+                    s.spawn(move || {
+                        control.aggregate_data((i, Foo("a".to_owned())), op);
+                        let map = HashMap::from([(i, Foo("a".to_owned()))]);
+                        (thread::current().id(), map)
+                    })
+                })
+                .collect::<Vec<_>>();
+            hs.into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let map = HashMap::from_iter(tid_map_pairs);
+        assert_eq_and_println(&control.drain_tls(), &Ok(map), "drain_tls with capacity");
+        assert_eq_and_println(
+            &control.drain_tls(),
+            &Ok(HashMap::new()),
+            "drain_tls after drain_tls with capacity",
+        );
+    }
+    #[test]
+    fn with_tl_init() {
+        let mut control = Control::new(HashMap::new, op_r)
+            .with_tl_init(|tid| HashMap::from([(tid, HashMap::with_capacity(8))]));
+
+        let tid = thread::scope(|s| {
+            s.spawn(|| {
+                let tid = thread::current().id();
+                let capacity = control.with_tl_acc(|acc| acc[&tid].capacity());
+                assert!(capacity >= 8, "thread-local value labeled and pre-sized");
+                control.aggregate_data((1, Foo("a".to_owned())), op);
+                tid
+            })
+            .join()
+            .unwrap()
+        });
+
+        let map = HashMap::from([(tid, HashMap::from([(1, Foo("a".to_owned()))]))]);
+        assert_eq_and_println(&control.drain_tls(), &Ok(map), "drain_tls with_tl_init");
+    }
+
+    #[test]
+    fn with_grouped_reduction() {
+        use std::{
+            cell::Cell,
+            sync::{Arc, Mutex},
+        };
+
+        thread_local! {static GROUP: Cell<usize> = const { Cell::new(0) };}
+
+        let entered = Arc::new(Mutex::new(Vec::new()));
+        let mut control =
+            Control::<u64>::new_additive().with_grouped_reduction(|| GROUP.with(Cell::get), {
+                let entered = entered.clone();
+                move |group| entered.lock().unwrap().push(group)
+            });
+
+        thread::scope(|s| {
+            for i in 0..6 {
+                let control = control.clone();
+                s.spawn(move || {
+                    // E.g., the NUMA node the thread is pinned to.
+                    GROUP.with(|group| group.set(i % 2 + 1));
+                    control.add_data(i as u64);
+                });
+            }
+        });
+
+        assert_eq_and_println(&control.drain_tls(), &Ok(15), "grouped drain_tls");
+        let mut entered = entered.lock().unwrap().clone();
+        entered.sort();
+        // Exited threads' thread-local values may be reused by later threads, so not all groups may have values.
+        assert!(
+            !entered.is_empty() && entered.iter().all(|group| [1, 2].contains(group)),
+            "entered groups {entered:?}"
+        );
+    }
+
+    #[cfg(thread_local_collect_off)]
+    #[test]
+    fn collect_off() {
+        let mut control = Control::<u64>::new_additive();
+        control.add_data(1u64);
+        control.aggregate_data(2u64, |data, acc, _| *acc += data);
+        let res = control.with_tl_acc_mut(|acc| {
+            *acc += 3;
+            *acc
+        });
+        assert_eq_and_println(&res, &3, "closure invoked on scratch zero value");
+        thread::scope(|s| {
+            let sender = control.sender();
+            s.spawn(move || sender.add_data(4u64));
+        });
+        assert_eq_and_println(
+            &control.drain_tls().unwrap(),
+            &0,
+            "contributions compiled out",
+        );
+    }
 }
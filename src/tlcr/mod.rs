@@ -1,5 +1,19 @@
 //! Modules that use the [`thread_local`](https://docs.rs/thread_local/latest/thread_local/) crate. These
 //! modules require the **"tlcr"** feature.
+//!
+//! The `Control` types of [`joined`] and [`probed`] have the same API for contributing data from participating
+//! threads, so switching between the two modules does not require changes to call sites:
+//! - `with_tl_acc` and `with_tl_acc_mut` provide access to the current thread's partial accumulated value.
+//! - `aggregate_data` aggregates data with an operation supplied on each call.
+//! - `absorb_data` aggregates data with the operation defined by the accumulator type's
+//!   [`Accumulator`](crate::accumulator::Accumulator) implementation, and `add_data` aggregates data with `+=`.
+//!
+//! The modules differ in how the accumulated value is retrieved: both provide `drain_tls`, and [`probed`] also
+//! provides `probe_tls`. The strategy used to reduce the thread-local values into a single accumulated value can be
+//! set with `with_reduction`, see [`Reduction`].
 
 pub mod joined;
 pub mod probed;
+mod reduce;
+
+pub use reduce::Reduction;
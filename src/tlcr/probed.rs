@@ -21,12 +21,33 @@
 //!
 //! See another example at [`examples/tlcr_probed_map_accumulator`](https://github.com/pvillela/rust-thread-local-collect/blob/main/examples/tlcr_probed_map_accumulator.rs).
 
+#[cfg(feature = "overhead-stats")]
+use crate::overhead::{OverheadRecorder, OverheadStats};
+use crate::{
+    accumulator::Accumulator,
+    activity::{self, ActivityTracker, ThreadActivity},
+    diag::{DiagEvent, DiagHook},
+    gate::COLLECT_ENABLED,
+    handles::{ControlReader, ControlSender, FinishedControl},
+    history::{self, History},
+    instr::OpInstr,
+    sink::Sink,
+    tlcr::{
+        reduce::{reduce_grouped, Grouped, Grouping},
+        Reduction,
+    },
+};
 use std::{
+    collections::HashMap,
     fmt::Debug,
     mem::replace,
-    ops::DerefMut,
-    sync::{Arc, Mutex},
+    ops::{Add, AddAssign, DerefMut},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     thread::{self, ThreadId},
+    time::Duration,
 };
 use thiserror::Error;
 use thread_local::ThreadLocal;
@@ -52,11 +73,61 @@ where
     U: Send,
 {
     /// Keeps track of registered threads and accumulated value.
-    state: Arc<ThreadLocal<Mutex<U>>>,
+    state: Arc<ThreadLocal<Grouped<Mutex<U>>>>,
     /// Produces a zero value of type `U`, which is needed to obtain consistent aggregation results.
     acc_zero: Arc<dyn Fn() -> U + Send + Sync>,
     /// Binary operation that reduces two accumulated values into one.
     op_r: Arc<dyn Fn(U, U) -> U + Send + Sync>,
+    /// Optional diagnostics hook.
+    diag: Option<DiagHook>,
+    /// Number of threads for which space is preallocated in the state.
+    capacity: usize,
+    /// Optional bounded history of drained accumulated values.
+    history: Option<Arc<History<U>>>,
+    /// Optional tracker of the contribution activity of each thread.
+    activity: Option<Arc<ActivityTracker>>,
+    /// Recorder of the latencies of contributions.
+    #[cfg(feature = "overhead-stats")]
+    overhead: Arc<OverheadRecorder>,
+    /// Optional per-thread replacement for `acc_zero` for the thread-local accumulated values.
+    #[allow(clippy::type_complexity)]
+    tl_zero: Option<Arc<dyn Fn(ThreadId) -> U + Send + Sync>>,
+    /// Strategy used to reduce the thread-local values.
+    reduction: Reduction,
+    /// Optional assignment of threads to groups for grouped reduction.
+    grouping: Option<Grouping>,
+    /// Cached accumulation of the thread-local values, see [`Control::with_acc`].
+    merged: Arc<MergedCache<U>>,
+}
+
+/// Accumulation of the thread-local values cached by [`Control::with_acc`], shared by a control object and its
+/// clones.
+#[derive(Debug)]
+struct MergedCache<U> {
+    /// Set by contributions after they update a thread-local value, and cleared before the cache is recomputed.
+    stale: AtomicBool,
+    cache: Mutex<Option<U>>,
+}
+
+impl<U> MergedCache<U> {
+    fn new() -> Self {
+        Self {
+            stale: AtomicBool::new(true),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Marks the cache as stale. Only stores to the flag if it is clear, so that contributions do not contend on it
+    /// between reads of the cache.
+    ///
+    /// The flag is accessed with sequentially consistent ordering: a contribution that is missed by the computation
+    /// of the cache, which locks each thread-local value after clearing the flag, observes the cleared flag and sets
+    /// it again.
+    fn invalidate(&self) {
+        if !self.stale.load(Ordering::SeqCst) {
+            self.stale.store(true, Ordering::SeqCst);
+        }
+    }
 }
 
 impl<U> Clone for Control<U>
@@ -68,6 +139,16 @@ where
             state: self.state.clone(),
             op_r: self.op_r.clone(),
             acc_zero: self.acc_zero.clone(),
+            diag: self.diag.clone(),
+            capacity: self.capacity,
+            history: self.history.clone(),
+            activity: self.activity.clone(),
+            #[cfg(feature = "overhead-stats")]
+            overhead: self.overhead.clone(),
+            tl_zero: self.tl_zero.clone(),
+            reduction: self.reduction,
+            grouping: self.grouping.clone(),
+            merged: self.merged.clone(),
         }
     }
 }
@@ -98,62 +179,499 @@ where
             state: Arc::new(ThreadLocal::new()),
             acc_zero: Arc::new(acc_zero),
             op_r: Arc::new(op_r),
+            diag: None,
+            capacity: 0,
+            history: None,
+            activity: None,
+            #[cfg(feature = "overhead-stats")]
+            overhead: Arc::default(),
+            tl_zero: None,
+            reduction: Reduction::Fold,
+            grouping: None,
+            merged: Arc::new(MergedCache::new()),
         }
     }
 
+    /// Instantiates a [`Control`] object whose accumulation is defined by the [`Accumulator`] implementation of `U`,
+    /// with [`Accumulator::zero`] as `acc_zero` and [`Accumulator::merge`] as `op_r`. See [`Control::new`].
+    pub fn from_accumulator() -> Self
+    where
+        U: Accumulator + 'static,
+    {
+        Self::new(U::zero, U::merge)
+    }
+
+    /// Instantiates a [`Control`] object for the common case of additive accumulations, e.g., counting or summing,
+    /// with `U::default` as `acc_zero` and `+` as `op_r`. See [`Control::new`].
+    pub fn new_additive() -> Self
+    where
+        U: Default + Add<Output = U> + 'static,
+    {
+        Self::new(U::default, |acc1, acc2| acc1 + acc2)
+    }
+
+    /// Instantiates a [`Control`] object for accumulators that implement [`Extend`] with their own items, e.g.,
+    /// [`Vec`] or [`HashMap`], with `U::default` as `acc_zero` and [`Extend::extend`] as `op_r`. Data can then be
+    /// contributed with [`Self::extend_data`] without an `op` closure. See [`Control::new`].
+    pub fn from_extend() -> Self
+    where
+        U: Default + Extend<U::Item> + IntoIterator + 'static,
+    {
+        Self::new(U::default, |mut acc1, acc2| {
+            acc1.extend(acc2);
+            acc1
+        })
+    }
+
+    /// Attaches a diagnostics hook that receives structured events about `self`'s internal operations.
+    /// See [`crate::diag`].
+    pub fn with_diagnostics(mut self, hook: impl Fn(&DiagEvent) + 'static + Send + Sync) -> Self {
+        self.diag = Some(Arc::new(hook));
+        self
+    }
+
+    /// Enables a bounded history that keeps clones of the last `capacity` accumulated values drained from `self`,
+    /// which can be retrieved with [`Self::history`]. The history is shared by all clones of `self`.
+    pub fn with_history(mut self, capacity: usize) -> Self
+    where
+        U: Clone,
+    {
+        self.history = Some(History::new(capacity));
+        self
+    }
+
+    /// Enables the tracking of the contribution activity of each thread, which can be retrieved with
+    /// [`Self::thread_activity`]. See [`crate::activity`].
+    pub fn with_activity_tracking(mut self) -> Self {
+        self.activity = Some(Arc::default());
+        self
+    }
+
+    /// Uses `tl_zero` instead of `acc_zero` (see [`Self::new`]) to produce the initial value of each thread's local
+    /// accumulated value, given the [`ThreadId`] of the thread, e.g., to pre-size or label it per worker. The values
+    /// produced by `tl_zero` are reduced into the accumulated value, so they must behave as zero values for
+    /// consistent aggregation results.
+    pub fn with_tl_init(mut self, tl_zero: impl Fn(ThreadId) -> U + 'static + Send + Sync) -> Self {
+        self.tl_zero = Some(Arc::new(tl_zero));
+        self
+    }
+
+    /// Sets the strategy used to reduce the thread-local values into a single accumulated value, which defaults to
+    /// [`Reduction::Fold`].
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    /// Reduces the thread-local values in groups (e.g., by NUMA node or core group) when they are drained or probed,
+    /// each group on its own thread, before reducing the group results. This is a grouped reduction, not sharding
+    /// of the state: the thread-local values are still held in a single `ThreadLocal` and contributions are not
+    /// affected, but the reduction of each group can run close to the memory of its values, e.g., to avoid
+    /// cross-socket traffic when reducing large accumulated values of threads on multiple sockets.
+    ///
+    /// - `group_of` - returns the group of the current thread; it is invoked when a thread first accesses its
+    ///   thread-local value, so it should reflect the thread's placement at that time (e.g., threads pinned to a
+    ///   NUMA node with an affinity library). A thread that reuses the thread-local value of a terminated thread
+    ///   keeps that value's group.
+    /// - `enter_group` - invoked with the group number on a dedicated scoped thread before that thread reduces the
+    ///   values of the group, e.g., to pin itself to the group's NUMA node.
+    ///
+    /// The values of each group are reduced with the strategy set with [`Self::with_reduction`] and the per-group
+    /// results are then reduced in group order, so the reduction operation must be commutative as well as
+    /// associative. Thread-local values created before this call are assigned to group 0, so this method should be
+    /// called immediately after `self` is instantiated.
+    pub fn with_grouped_reduction(
+        mut self,
+        group_of: impl Fn() -> usize + 'static + Send + Sync,
+        enter_group: impl Fn(usize) + 'static + Send + Sync,
+    ) -> Self {
+        self.grouping = Some(Grouping {
+            group_of: Arc::new(group_of),
+            enter_group: Arc::new(enter_group),
+        });
+        self
+    }
+
+    /// Produces the initial value of the current thread's local accumulated value.
+    fn tl_zero(&self) -> U {
+        match &self.tl_zero {
+            Some(tl_zero) => tl_zero(thread::current().id()),
+            None => (self.acc_zero)(),
+        }
+    }
+
+    /// Returns the last accumulated values drained from `self`, from oldest to newest, if history is enabled
+    /// with [`Self::with_history`], or an empty vector otherwise.
+    ///
+    /// # Panics
+    /// If the history's mutex is poisoned.
+    pub fn history(&self) -> Vec<U> {
+        history::snapshot(self.history.as_ref())
+    }
+
+    /// Returns the contribution activity of each thread that has contributed to `self` since activity tracking was
+    /// enabled with [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn thread_activity(&self) -> HashMap<ThreadId, ThreadActivity> {
+        activity::snapshot(self.activity.as_ref())
+    }
+
+    /// Returns the number of contributions made by each thread to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn contribution_counts(&self) -> HashMap<ThreadId, u64> {
+        activity::counts(self.activity.as_ref())
+    }
+
+    /// Returns the threads that have contributed to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`] but have not contributed in the last `since`, e.g., so that a supervisor can
+    /// detect hung workers, or an empty vector if tracking is not enabled. Threads that have terminated are included,
+    /// since the tracker does not observe thread termination.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn idle_threads(&self, since: Duration) -> Vec<ThreadId> {
+        activity::idle(self.activity.as_ref(), since)
+    }
+
+    /// Returns the latency statistics of the contributions made to `self` and its clones. See [`crate::overhead`].
+    /// Requires the **"overhead-stats"** feature.
+    #[cfg(feature = "overhead-stats")]
+    pub fn overhead_stats(&self) -> OverheadStats {
+        self.overhead.snapshot()
+    }
+
+    /// Preallocates space in `self`'s state for `capacity` participating threads, avoiding repeated internal
+    /// reallocation when the number of participating threads is known up front. The capacity is retained when
+    /// the state is replaced by [`Control::drain_tls`].
+    ///
+    /// Thread-local values already collected by `self` are discarded, so this method should be called immediately
+    /// after `self` is instantiated.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.state = Arc::new(ThreadLocal::with_capacity(capacity));
+        self.merged.invalidate();
+        self
+    }
+
     /// Called from a thread to access the thread's local accumulated value.
     pub fn with_tl_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V {
-        let cell = self.state.get_or(|| Mutex::new((self.acc_zero)()));
+        let cell = self.state.get_or(|| Grouped {
+            group: Grouping::current(&self.grouping),
+            tid: thread::current().id(),
+            cell: Mutex::new(self.tl_zero()),
+        });
         let u = cell.lock().expect(POISONED_CONTROL_MUTEX);
         f(&u)
     }
 
-    /// Called from a thread to mutably access the thread's local accumulated value.
+    /// Called from a thread to mutably access the thread's local accumulated value. If collection is compiled out
+    /// with the `thread_local_collect_off` or `thread_local_collect_release_off` option, `f` is instead invoked on a
+    /// scratch zero value that is then dropped.
     pub fn with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> V {
-        let cell = self.state.get_or(|| Mutex::new((self.acc_zero)()));
+        if !COLLECT_ENABLED {
+            return f(&mut self.tl_zero());
+        }
+        #[cfg(feature = "overhead-stats")]
+        let _timer = self.overhead.timer();
+        activity::record(self.activity.as_ref(), thread::current().id());
+        let cell = self.state.get_or(|| Grouped {
+            group: Grouping::current(&self.grouping),
+            tid: thread::current().id(),
+            cell: Mutex::new(self.tl_zero()),
+        });
         let mut u = cell.lock().expect(POISONED_CONTROL_MUTEX);
-        f(&mut u)
+        let res = f(&mut u);
+        self.merged.invalidate();
+        res
     }
 
-    /// Called from a thread to aggregate data with aggregation operation `op`.
+    /// Called from a thread to aggregate data with aggregation operation `op`. Does nothing if collection is
+    /// compiled out with the `thread_local_collect_off` or `thread_local_collect_release_off` option.
     pub fn aggregate_data<T>(&self, data: T, op: impl FnOnce(T, &mut U, ThreadId)) {
+        if !COLLECT_ENABLED {
+            return;
+        }
         self.with_tl_acc_mut(|acc| op(data, acc, thread::current().id()))
     }
 
+    /// Called from a thread to absorb data into the thread's local accumulated value with
+    /// [`Accumulator::absorb`].
+    pub fn absorb_data(&self, data: U::Dat)
+    where
+        U: Accumulator,
+    {
+        self.aggregate_data(data, |data, acc, tid| acc.absorb(data, tid))
+    }
+
+    /// Called from a thread to add data to the thread's local accumulated value with `+=`.
+    pub fn add_data<T>(&self, data: T)
+    where
+        U: AddAssign<T>,
+    {
+        self.aggregate_data(data, |data, acc, _| *acc += data)
+    }
+
+    /// Called from a thread to extend the thread's local accumulated value with a `(ThreadId, T)` pair, where the
+    /// [`ThreadId`] is the current thread's.
+    pub fn extend_data<T>(&self, data: T)
+    where
+        U: Extend<(ThreadId, T)>,
+    {
+        self.aggregate_data(data, |data, acc, tid| acc.extend([(tid, data)]))
+    }
+
+    /// Wraps `self` in a [`FinishedControl`] whose [`drain_tls`](FinishedControl::drain_tls) method returns the
+    /// accumulation of the thread-local values post-processed with `finish`.
+    pub fn with_finisher<V, F>(self, finish: F) -> FinishedControl<Self, F>
+    where
+        F: Fn(U) -> V,
+    {
+        FinishedControl::new(self, finish)
+    }
+
+    /// Returns a send-only handle to `self`. Note that [`Control::drain_tls`] fails while the handle, which holds a
+    /// clone of `self`, is alive on another thread.
+    pub fn sender(&self) -> ControlSender<Self> {
+        ControlSender(self.clone())
+    }
+
+    /// Returns whether `self` and `other` are the same control object or clones of each other, i.e., whether they
+    /// aggregate into the same accumulated value.
+    pub fn same_control(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+
+    /// Replaces `state` with a new state with space preallocated for `capacity` threads and returns the old one.
+    ///
+    /// # Errors
+    /// Returns an error, leaving `state` unchanged, if it is shared, i.e., if any thread other than the current one
+    /// holds a clone of the control object.
+    fn take_state(
+        state: &mut Arc<ThreadLocal<Grouped<Mutex<U>>>>,
+        capacity: usize,
+    ) -> Result<ThreadLocal<Grouped<Mutex<U>>>, ActiveThreadLocalsError> {
+        let old_state = replace(state, Arc::new(ThreadLocal::with_capacity(capacity)));
+        Arc::try_unwrap(old_state).map_err(|old_state| {
+            _ = replace(state, old_state); // put it back
+            ActiveThreadLocalsError
+        })
+    }
+
+    /// Same as [`Self::drain_tls`], except that the thread-local accumulated values are passed to `sink`, together
+    /// with the [`ThreadId`]s of their threads, instead of being reduced, after which [`Sink::finish`] is called on
+    /// `sink`, e.g., to write each thread's values to a file.
+    ///
+    /// # Errors
+    /// - Returns an error if any thread, other than the thread where this function is called from,
+    ///   holds a clone of `self`. In this case, the state of `self` is left unchanged and `sink` is not used.
+    pub fn drain_into_sink(
+        &mut self,
+        sink: &mut impl Sink<U>,
+    ) -> Result<(), ActiveThreadLocalsError> {
+        let mut instr = OpInstr::start("drain_into_sink", self.diag.as_ref());
+        let unwr_state = Self::take_state(&mut self.state, self.capacity)?;
+        for x in unwr_state {
+            instr.cell();
+            sink.accept(x.cell.into_inner().expect(POISONED_CONTROL_MUTEX), x.tid);
+        }
+        sink.finish();
+        self.merged.invalidate();
+        Ok(())
+    }
+
     /// Returns the accumulation of the thread-local values, restoring `self`'s state to what it was when
     /// it was instantiated with [`Control::new`].
     ///
     /// # Errors
     /// - Returns an error if any thread, other than the thread where this function is called from,
-    /// holds a clone of `self`. In this case, the state of `self` is left unchanged.
+    ///   holds a clone of `self`. In this case, the state of `self` is left unchanged.
     pub fn drain_tls(&mut self) -> Result<U, ActiveThreadLocalsError> {
-        let state = replace(&mut self.state, Arc::new(ThreadLocal::new()));
-        let unwr_state = match Arc::try_unwrap(state) {
-            Ok(unwr_state) => unwr_state,
-            Err(state) => {
-                _ = replace(&mut self.state, state); // put it back
-                return Err(ActiveThreadLocalsError);
-            }
-        };
-        let res = unwr_state
-            .into_iter()
-            .map(|x| {
-                let mut data_guard = x.lock().expect(POISONED_CONTROL_MUTEX);
-                let data = replace(data_guard.deref_mut(), (self.acc_zero)());
-                data
-            })
-            .fold((self.acc_zero)(), self.op_r.as_ref());
+        let mut instr = OpInstr::start("drain_tls", self.diag.as_ref());
+        let unwr_state = Self::take_state(&mut self.state, self.capacity)?;
+        let values = unwr_state.into_iter().map(|x| {
+            instr.cell();
+            let mut data_guard = x.lock().expect(POISONED_CONTROL_MUTEX);
+            let data = replace(data_guard.deref_mut(), (self.acc_zero)());
+            (x.group, data)
+        });
+        let res = reduce_grouped(
+            values,
+            self.acc_zero.as_ref(),
+            self.op_r.as_ref(),
+            self.reduction,
+            self.grouping.as_ref(),
+        );
+        self.merged.invalidate();
+        history::record(self.history.as_ref(), &res);
         Ok(res)
     }
 
+    /// Returns a read-only handle to `self`.
+    pub fn reader(&self) -> ControlReader<Self> {
+        ControlReader(self.clone())
+    }
+
     /// Returns a clone of the current accumulation of the thread-local values, without changing the state of `self`.
     pub fn probe_tls(&self) -> U
     where
         U: Clone,
     {
-        let iter = self.state.iter();
-        iter.map(|x| x.lock().expect(POISONED_CONTROL_MUTEX).clone())
-            .fold((self.acc_zero)(), self.op_r.as_ref())
+        let mut instr = OpInstr::start("probe_tls", self.diag.as_ref());
+        let values = self.state.iter().map(|x| {
+            instr.cell();
+            (x.group, x.lock().expect(POISONED_CONTROL_MUTEX).clone())
+        });
+        reduce_grouped(
+            values,
+            self.acc_zero.as_ref(),
+            self.op_r.as_ref(),
+            self.reduction,
+            self.grouping.as_ref(),
+        )
+    }
+
+    /// Invokes `f` on the current accumulation of the thread-local values, as returned by [`Self::probe_tls`], and
+    /// returns its result. The accumulation is cached and is only recomputed after a thread has contributed or
+    /// `self` has been drained, so frequent readers do not pay for a full reduction on each call.
+    ///
+    /// The cache is shared by `self` and its clones, and is locked while `f` is invoked, which blocks other calls of
+    /// this method but not contributions, so `f` should be quick.
+    pub fn with_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V
+    where
+        U: Clone,
+    {
+        let mut cache = self.merged.cache.lock().expect(POISONED_CONTROL_MUTEX);
+        if self.merged.stale.swap(false, Ordering::SeqCst) {
+            *cache = None;
+        }
+        let acc = cache.get_or_insert_with(|| self.probe_tls());
+        f(acc)
+    }
+
+    /// Returns an iterator over clones of the thread-local accumulated values without changing the state of `self`.
+    /// There is one value per thread that has contributed since `self` was instantiated or last drained, except that
+    /// a thread may reuse the value of a thread that terminated before it started. Unlike
+    /// [`Self::probe_tls`], the values are not reduced, so the number of threads with state and the size of each
+    /// partial accumulated value can be inspected, e.g., before deciding to drain.
+    ///
+    /// Each thread-local value is locked only while it is cloned.
+    pub fn probe_iter(&self) -> impl Iterator<Item = U> + '_
+    where
+        U: Clone,
+    {
+        self.state
+            .iter()
+            .map(|x| x.lock().expect(POISONED_CONTROL_MUTEX).clone())
+    }
+
+    /// Invokes `f` on each thread-local accumulated value, without cloning it or changing the state of `self`.
+    /// See [`Self::probe_iter`].
+    ///
+    /// Each thread-local value is locked while `f` is invoked on it, which blocks contributions from its thread, so
+    /// `f` should be quick.
+    pub fn for_each_tl(&self, mut f: impl FnMut(&U)) {
+        for x in self.state.iter() {
+            f(&x.lock().expect(POISONED_CONTROL_MUTEX));
+        }
+    }
+}
+
+impl<U> ControlReader<Control<U>>
+where
+    U: Send,
+{
+    /// Returns a clone of the current accumulation of the thread-local values. See [`Control::probe_tls`].
+    pub fn probe_tls(&self) -> U
+    where
+        U: Clone,
+    {
+        self.0.probe_tls()
+    }
+
+    /// Invokes `f` on the cached accumulation of the thread-local values. See [`Control::with_acc`].
+    pub fn with_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V
+    where
+        U: Clone,
+    {
+        self.0.with_acc(f)
+    }
+
+    /// Returns an iterator over clones of the thread-local accumulated values. See [`Control::probe_iter`].
+    pub fn probe_iter(&self) -> impl Iterator<Item = U> + '_
+    where
+        U: Clone,
+    {
+        self.0.probe_iter()
+    }
+
+    /// Invokes `f` on each thread-local accumulated value. See [`Control::for_each_tl`].
+    pub fn for_each_tl(&self, f: impl FnMut(&U)) {
+        self.0.for_each_tl(f)
+    }
+}
+
+impl<U, F, V> FinishedControl<Control<U>, F>
+where
+    U: Send,
+    F: Fn(U) -> V,
+{
+    /// Returns the accumulation of the thread-local values post-processed with the finisher.
+    /// See [`Control::drain_tls`].
+    ///
+    /// # Errors
+    /// - Returns an error if any thread, other than the thread where this function is called from,
+    ///   holds a clone of the control object. In this case, the state of the control object is left unchanged.
+    pub fn drain_tls(&mut self) -> Result<V, ActiveThreadLocalsError> {
+        self.control.drain_tls().map(self.finish.as_ref())
+    }
+}
+
+impl<U> ControlSender<Control<U>>
+where
+    U: Send,
+{
+    /// Called from a thread to mutably access the thread's local accumulated value.
+    /// See [`Control::with_tl_acc_mut`].
+    pub fn with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> V {
+        self.0.with_tl_acc_mut(f)
+    }
+
+    /// Called from a thread to aggregate data with aggregation operation `op`.
+    /// See [`Control::aggregate_data`].
+    pub fn aggregate_data<T>(&self, data: T, op: impl FnOnce(T, &mut U, ThreadId)) {
+        self.0.aggregate_data(data, op)
+    }
+
+    /// Called from a thread to absorb data into the thread's local accumulated value.
+    /// See [`Control::absorb_data`].
+    pub fn absorb_data(&self, data: U::Dat)
+    where
+        U: Accumulator,
+    {
+        self.0.absorb_data(data)
+    }
+
+    /// Called from a thread to add data to the thread's local accumulated value.
+    /// See [`Control::add_data`].
+    pub fn add_data<T>(&self, data: T)
+    where
+        U: AddAssign<T>,
+    {
+        self.0.add_data(data)
+    }
+
+    /// Called from a thread to extend the thread's local accumulated value with a `(ThreadId, T)` pair.
+    /// See [`Control::extend_data`].
+    pub fn extend_data<T>(&self, data: T)
+    where
+        U: Extend<(ThreadId, T)>,
+    {
+        self.0.extend_data(data)
     }
 }
 
@@ -162,11 +680,15 @@ where
 mod tests {
     use super::{ActiveThreadLocalsError, Control};
     use crate::dev_support::{assert_eq_and_println, ThreadGater};
+    use crate::{diag::DiagEvent, tlcr::Reduction};
     use std::{
         collections::HashMap,
         fmt::Debug,
         iter::once,
-        sync::Mutex,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Barrier, Mutex,
+        },
         thread::{self, ThreadId},
         time::Duration,
     };
@@ -454,4 +976,192 @@ mod tests {
             "error expected due to active thread(s)"
         );
     }
+
+    #[test]
+    fn with_tl_acc_prune() {
+        let mut control = Control::new(HashMap::new, op_r);
+        let tid_own = thread::current().id();
+
+        control.aggregate_data((1, Foo("a".to_owned())), op);
+        control.aggregate_data((2, Foo("b".to_owned())), op);
+        let len = control.with_tl_acc(|acc| acc[&tid_own].len());
+        assert_eq!(len, 2, "partial accumulator before pruning");
+
+        // Prune the thread's own partial state before it is probed.
+        control.with_tl_acc_mut(|acc| acc.get_mut(&tid_own).unwrap().remove(&1));
+
+        let map = HashMap::from([(tid_own, HashMap::from([(2, Foo("b".to_owned()))]))]);
+        assert_eq_and_println(&control.probe_tls(), &map, "probe_tls after pruning");
+        assert_eq_and_println(&control.drain_tls(), &Ok(map), "drain_tls after pruning");
+    }
+
+    #[test]
+    fn probe_iter() {
+        let mut control = Control::<u64>::new_additive();
+
+        control.add_data(1);
+        // Keep both threads alive until both have contributed, so they do not share a thread-local slot.
+        let barrier = Barrier::new(2);
+        thread::scope(|s| {
+            s.spawn(|| {
+                control.add_data(10);
+                barrier.wait();
+            });
+            s.spawn(|| {
+                control.add_data(100);
+                control.add_data(100);
+                barrier.wait();
+            });
+        });
+
+        let mut values = control.probe_iter().collect::<Vec<_>>();
+        values.sort();
+        assert_eq_and_println(&values, &vec![1, 10, 200], "probe_iter");
+
+        let mut count = 0;
+        control.for_each_tl(|_| count += 1);
+        assert_eq_and_println(&count, &3, "for_each_tl");
+
+        assert_eq_and_println(&control.drain_tls(), &Ok(211), "drain_tls");
+        assert_eq_and_println(&control.probe_iter().count(), &0, "probe_iter after drain");
+    }
+    #[test]
+    fn with_acc() {
+        let probes = Arc::new(AtomicUsize::new(0));
+        let mut control = Control::<u64>::new_additive().with_diagnostics({
+            let probes = probes.clone();
+            move |event| {
+                if let DiagEvent::Completed {
+                    op: "probe_tls", ..
+                } = event
+                {
+                    probes.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+        let reader = control.reader();
+        let probes = || probes.load(Ordering::Relaxed);
+
+        control.add_data(1);
+        assert_eq_and_println(&control.with_acc(|acc| *acc), &1, "computed");
+        assert_eq_and_println(&reader.with_acc(|acc| *acc), &1, "cached");
+        assert_eq_and_println(&probes(), &1, "probes while cached");
+
+        thread::scope(|s| {
+            s.spawn(|| control.add_data(10));
+        });
+        assert_eq_and_println(
+            &reader.with_acc(|acc| *acc),
+            &11,
+            "invalidated by contribution",
+        );
+        assert_eq_and_println(&probes(), &2, "probes after contribution");
+
+        drop(reader);
+        assert_eq_and_println(&control.drain_tls(), &Ok(11), "drain_tls");
+        assert_eq_and_println(&control.with_acc(|acc| *acc), &0, "invalidated by drain");
+    }
+
+    #[test]
+    fn with_finisher() {
+        let mut control = Control::new(|| (0, 0), |(n1, s1), (n2, s2)| (n1 + n2, s1 + s2))
+            .with_finisher(|(n, sum): (u32, u32)| f64::from(sum) / f64::from(n));
+
+        thread::scope(|s| {
+            for i in 1..=4 {
+                let sender = control.sender();
+                s.spawn(move || {
+                    sender.aggregate_data(i, |data, acc, _| *acc = (acc.0 + 1, acc.1 + data))
+                });
+            }
+        });
+
+        assert_eq_and_println(&control.probe_tls(), &(4, 10), "probe_tls not finished");
+        assert_eq_and_println(&control.drain_tls(), &Ok(2.5), "average drain_tls");
+    }
+    #[test]
+    fn with_reduction() {
+        const NTHREADS: u64 = 100;
+
+        for reduction in [Reduction::Tree, Reduction::ParallelTree] {
+            let mut control = Control::<u64>::new_additive().with_reduction(reduction);
+            // Keep all threads alive until all have contributed, so that each has its own thread-local value.
+            let barrier = Barrier::new(NTHREADS as usize);
+            thread::scope(|s| {
+                for i in 0..NTHREADS {
+                    let (control, barrier) = (&control, &barrier);
+                    s.spawn(move || {
+                        control.add_data(i);
+                        barrier.wait();
+                    });
+                }
+            });
+
+            let expected = (0..NTHREADS).sum::<u64>();
+            assert_eq_and_println(
+                &control.probe_tls(),
+                &expected,
+                &format!("{reduction:?} probe_tls"),
+            );
+            assert_eq_and_println(
+                &control.drain_tls(),
+                &Ok(expected),
+                &format!("{reduction:?} drain_tls"),
+            );
+        }
+    }
+
+    #[test]
+    fn drain_into_sink() {
+        let mut control = Control::new(HashMap::new, op_r);
+
+        control.aggregate_data((1, Foo("a".to_owned())), op);
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                control.aggregate_data((2, Foo("b".to_owned())), op);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+
+        let mut sink = Vec::new();
+        control.drain_into_sink(&mut sink).unwrap();
+        let per_thread = sink.into_iter().collect::<HashMap<_, _>>();
+        let expected =
+            [(thread::current().id(), 1, "a"), (tid_spawned, 2, "b")].map(|(tid, k, v)| {
+                (
+                    tid,
+                    HashMap::from([(tid, HashMap::from([(k, Foo(v.to_owned()))]))]),
+                )
+            });
+        assert_eq_and_println(
+            &per_thread,
+            &HashMap::from(expected),
+            "per-thread values passed to sink",
+        );
+        assert_eq_and_println(&control.drain_tls(), &Ok(HashMap::new()), "drained");
+    }
+
+    #[cfg(thread_local_collect_off)]
+    #[test]
+    fn collect_off() {
+        let mut control = Control::<u64>::new_additive();
+        control.add_data(1u64);
+        control.aggregate_data(2u64, |data, acc, _| *acc += data);
+        let res = control.with_tl_acc_mut(|acc| {
+            *acc += 3;
+            *acc
+        });
+        assert_eq_and_println(&res, &3, "closure invoked on scratch zero value");
+        thread::scope(|s| {
+            let sender = control.sender();
+            s.spawn(move || sender.add_data(4u64));
+        });
+        assert_eq_and_println(
+            &control.drain_tls().unwrap(),
+            &0,
+            "contributions compiled out",
+        );
+    }
 }
@@ -0,0 +1,217 @@
+//! Strategies for reducing the thread-local accumulated values into a single accumulated value.
+
+use std::{
+    collections::BTreeMap,
+    mem::take,
+    ops::Deref,
+    sync::Arc,
+    thread::{self, ThreadId},
+};
+
+/// Number of values below which [`Reduction::ParallelTree`] reduces on the current thread.
+const PARALLEL_THRESHOLD: usize = 64;
+
+/// Strategy used by the `Control` types of the [`super`] sub-modules to reduce the thread-local accumulated values
+/// into a single accumulated value. Set with the `with_reduction` method of the control object.
+///
+/// The result does not depend on the strategy when the reduction operation is associative and commutative and the
+/// zero value is its identity, which is required for consistent aggregation results in any case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reduction {
+    /// Linear left fold of the thread-local values into the zero value. This is the default.
+    #[default]
+    Fold,
+    /// Pairwise reduction of the thread-local values in a balanced tree, so that each reduction combines values of
+    /// similar size, which reduces the peak size of temporaries and improves cache behavior when there are many
+    /// threads with large accumulated values.
+    Tree,
+    /// Like [`Self::Tree`], with the subtrees above a fixed size reduced in parallel on scoped threads.
+    ParallelTree,
+}
+
+/// Reduces `values` with `op_r` using the `reduction` strategy. Returns the zero value if `values` is empty.
+pub(crate) fn reduce<U: Send>(
+    values: impl Iterator<Item = U>,
+    zero: &(dyn Fn() -> U + Send + Sync),
+    op_r: &(dyn Fn(U, U) -> U + Send + Sync),
+    reduction: Reduction,
+) -> U {
+    let res = match reduction {
+        Reduction::Fold => return values.fold(zero(), op_r),
+        Reduction::Tree => tree(values.collect(), op_r),
+        Reduction::ParallelTree => parallel_tree(values.collect(), op_r),
+    };
+    res.unwrap_or_else(zero)
+}
+
+/// Reduces `values` level by level, combining adjacent pairs.
+fn tree<U>(mut values: Vec<U>, op_r: &(dyn Fn(U, U) -> U + Send + Sync)) -> Option<U> {
+    while values.len() > 1 {
+        let mut next = Vec::with_capacity(values.len().div_ceil(2));
+        let mut iter = take(&mut values).into_iter();
+        while let Some(a) = iter.next() {
+            match iter.next() {
+                Some(b) => next.push(op_r(a, b)),
+                None => next.push(a),
+            }
+        }
+        values = next;
+    }
+    values.pop()
+}
+
+/// Splits `values` in halves, reducing one half on a scoped thread, until the halves are small enough to be
+/// reduced with [`tree`].
+fn parallel_tree<U: Send>(
+    mut values: Vec<U>,
+    op_r: &(dyn Fn(U, U) -> U + Send + Sync),
+) -> Option<U> {
+    if values.len() <= PARALLEL_THRESHOLD {
+        return tree(values, op_r);
+    }
+    let right = values.split_off(values.len() / 2);
+    let (left, right) = thread::scope(|s| {
+        let h = s.spawn(|| parallel_tree(right, op_r));
+        let left = parallel_tree(values, op_r);
+        (left, h.join().expect("reduction thread panicked"))
+    });
+    match (left, right) {
+        (Some(left), Some(right)) => Some(op_r(left, right)),
+        (left, right) => left.or(right),
+    }
+}
+
+/// Assignment of threads to groups (e.g., NUMA nodes or core groups) for the grouped reduction of the thread-local
+/// values, set with the `with_grouped_reduction` method of the control object.
+#[derive(Clone)]
+pub(crate) struct Grouping {
+    /// Returns the group of the current thread. Invoked when the thread first accesses its thread-local value.
+    pub(crate) group_of: Arc<dyn Fn() -> usize + Send + Sync>,
+    /// Invoked on the thread that reduces the values of a group, before it does so, e.g., to pin the thread to the
+    /// group's NUMA node.
+    pub(crate) enter_group: Arc<dyn Fn(usize) + Send + Sync>,
+}
+
+impl Grouping {
+    /// Returns the group of the current thread, or 0 if `grouping` is `None`.
+    pub(crate) fn current(grouping: &Option<Grouping>) -> usize {
+        grouping
+            .as_ref()
+            .map(|grouping| (grouping.group_of)())
+            .unwrap_or(0)
+    }
+}
+
+/// Thread-local cell of type `C` tagged with the group of its thread.
+#[derive(Debug)]
+pub(crate) struct Grouped<C> {
+    pub(crate) group: usize,
+    /// Thread that owns the cell.
+    pub(crate) tid: ThreadId,
+    pub(crate) cell: C,
+}
+
+impl<C> Deref for Grouped<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.cell
+    }
+}
+
+/// Reduces `values`, each tagged with its group, with `op_r`. Without `grouping`, this is the same as [`reduce`].
+/// With `grouping`, the values of each group are reduced with the `reduction` strategy on a scoped thread that
+/// first invokes [`Grouping::enter_group`], and the per-group results are then reduced in group order.
+pub(crate) fn reduce_grouped<U: Send>(
+    values: impl Iterator<Item = (usize, U)>,
+    zero: &(dyn Fn() -> U + Send + Sync),
+    op_r: &(dyn Fn(U, U) -> U + Send + Sync),
+    reduction: Reduction,
+    grouping: Option<&Grouping>,
+) -> U {
+    let Some(grouping) = grouping else {
+        return reduce(values.map(|(_, u)| u), zero, op_r, reduction);
+    };
+    let mut groups = BTreeMap::<usize, Vec<U>>::new();
+    for (group, u) in values {
+        groups.entry(group).or_default().push(u);
+    }
+    let partials = thread::scope(|s| {
+        let hs = groups
+            .into_iter()
+            .map(|(group, values)| {
+                s.spawn(move || {
+                    (grouping.enter_group)(group);
+                    reduce(values.into_iter(), zero, op_r, reduction)
+                })
+            })
+            .collect::<Vec<_>>();
+        hs.into_iter()
+            .map(|h| h.join().expect("reduction thread panicked"))
+            .collect::<Vec<_>>()
+    });
+    reduce(partials.into_iter(), zero, op_r, Reduction::Fold)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{reduce, reduce_grouped, Grouping, Reduction};
+    use crate::dev_support::assert_eq_and_println;
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    #[test]
+    fn reductions_agree() {
+        let concat = |mut a: Vec<u32>, b: Vec<u32>| {
+            a.extend(b);
+            a
+        };
+
+        for n in [0, 1, 2, 7, 300] {
+            let values = || (0..n).map(|i| vec![i]);
+            let expected = (0..n).collect::<Vec<_>>();
+            for reduction in [Reduction::Fold, Reduction::Tree, Reduction::ParallelTree] {
+                // Concatenation is associative but not commutative, so this also checks that order is preserved.
+                let res = reduce(values(), &Vec::new, &concat, reduction);
+                assert_eq_and_println(&res, &expected, &format!("{reduction:?} of {n} values"));
+            }
+        }
+    }
+
+    #[test]
+    fn grouped() {
+        let caller = thread::current().id();
+        let entered = Arc::new(Mutex::new(Vec::new()));
+        let grouping = Grouping {
+            group_of: Arc::new(|| 0),
+            enter_group: {
+                let entered = entered.clone();
+                Arc::new(move |group| {
+                    assert_ne!(
+                        thread::current().id(),
+                        caller,
+                        "group reduced on scoped thread"
+                    );
+                    entered.lock().unwrap().push(group)
+                })
+            },
+        };
+
+        let values = (0..10u32).map(|i| (i as usize % 3, i));
+        let res = reduce_grouped(
+            values,
+            &|| 0,
+            &|a, b| a + b,
+            Reduction::Tree,
+            Some(&grouping),
+        );
+        assert_eq_and_println(&res, &45, "grouped reduction");
+
+        let mut entered = entered.lock().unwrap().clone();
+        entered.sort();
+        assert_eq_and_println(&entered, &vec![0, 1, 2], "entered groups");
+    }
+}
@@ -0,0 +1,141 @@
+//! Errors returned by the fallible contribution methods of the [`crate::tlm`] `Control` types.
+
+use super::{DisabledError, SealedError};
+use std::{error::Error, fmt::Display};
+
+/// Indicates the attempt to access a thread-local variable during or after its destruction, e.g., from the destructor
+/// of another thread-local variable during thread teardown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsDestroyedError;
+
+impl Display for TlsDestroyedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Thread-local variable accessed during or after its destruction.")
+    }
+}
+
+impl Error for TlsDestroyedError {}
+
+/// Indicates the attempt to access a control object's thread-local data, or to contribute to it, from code that is
+/// already running inside an access to the same control object on the same thread: the closure passed to
+/// `with_data`/`with_data_mut` or the `op` that combines thread-local data with the accumulated value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReentrantAccessError;
+
+impl Display for ReentrantAccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(
+            "Reentrant access to a control object from inside an access to it on the same thread.",
+        )
+    }
+}
+
+impl Error for ReentrantAccessError {}
+
+/// Indicates the attempt to access the current thread's thread-local variable through a control object other than
+/// the one the variable is linked to. A thread-local variable is linked to the first control object through which it
+/// is accessed on its thread; use the control object's `relink` method to link it to another one. Only returned by
+/// the `try_` access methods: the other ones relink the variable implicitly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlMismatchError;
+
+impl Display for ControlMismatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Thread-local variable accessed through a control object other than the one it is linked to.")
+    }
+}
+
+impl Error for ControlMismatchError {}
+
+/// Indicates that the registration of the current thread's thread-local variable with a control object was rejected
+/// because a thread-local variable is already registered for the same thread and thread-local static, under the
+/// `Reject` reregistration policy of the `probed` module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReregistrationError;
+
+impl Display for ReregistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Thread-local variable is already registered with the control object.")
+    }
+}
+
+impl Error for ReregistrationError {}
+
+/// Indicates why a contribution to a control object was rejected.
+#[derive(Debug, PartialEq)]
+pub enum AccessError {
+    /// The control object has been finalized.
+    Sealed(SealedError),
+    /// The control object has been disabled.
+    Disabled(DisabledError),
+    /// The current thread's thread-local variable has been destroyed.
+    TlsDestroyed(TlsDestroyedError),
+    /// The control object is already being accessed by the current thread.
+    Reentrant(ReentrantAccessError),
+    /// The current thread's thread-local variable is linked to a different control object.
+    Mismatch(ControlMismatchError),
+    /// The registration of the current thread's thread-local variable was rejected.
+    Reregistration(ReregistrationError),
+}
+
+impl Display for AccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sealed(e) => e.fmt(f),
+            Self::Disabled(e) => e.fmt(f),
+            Self::TlsDestroyed(e) => e.fmt(f),
+            Self::Reentrant(e) => e.fmt(f),
+            Self::Mismatch(e) => e.fmt(f),
+            Self::Reregistration(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for AccessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Sealed(e) => Some(e),
+            Self::Disabled(e) => Some(e),
+            Self::TlsDestroyed(e) => Some(e),
+            Self::Reentrant(e) => Some(e),
+            Self::Mismatch(e) => Some(e),
+            Self::Reregistration(e) => Some(e),
+        }
+    }
+}
+
+impl From<SealedError> for AccessError {
+    fn from(e: SealedError) -> Self {
+        Self::Sealed(e)
+    }
+}
+
+impl From<DisabledError> for AccessError {
+    fn from(e: DisabledError) -> Self {
+        Self::Disabled(e)
+    }
+}
+
+impl From<TlsDestroyedError> for AccessError {
+    fn from(e: TlsDestroyedError) -> Self {
+        Self::TlsDestroyed(e)
+    }
+}
+
+impl From<ReentrantAccessError> for AccessError {
+    fn from(e: ReentrantAccessError) -> Self {
+        Self::Reentrant(e)
+    }
+}
+
+impl From<ControlMismatchError> for AccessError {
+    fn from(e: ControlMismatchError) -> Self {
+        Self::Mismatch(e)
+    }
+}
+
+impl From<ReregistrationError> for AccessError {
+    fn from(e: ReregistrationError) -> Self {
+        Self::Reregistration(e)
+    }
+}
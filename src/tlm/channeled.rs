@@ -23,39 +23,79 @@
 //!
 //! See another example at [`examples/tlm_channeled_map_accumulator`](https://github.com/pvillela/rust-thread-local-collect/blob/main/examples/tlm_channeled_map_accumulator.rs).
 
-use crate::tlm::common::POISONED_CONTROL_MUTEX;
+#[cfg(feature = "overhead-stats")]
+use crate::overhead::{OverheadRecorder, OverheadStats};
+use crate::{
+    accumulator::{Accumulator, Recycle, Tabular},
+    activity::{self, ActivityTracker, ThreadActivity},
+    diag::{DiagEvent, DiagHook, OpRecord},
+    fork::ForkGuard,
+    handles::{ControlReader, ControlSender, FinishedControl, MappedGuard, MappedSender},
+    history::{self, History},
+    instr::{self, OpInstr},
+    lock_order::{LockKind, Tracked},
+    pool::AccPool,
+    sink::Sink,
+    spill::Spill,
+    tlm::{AccessError, Seal, Switch, TlsDestroyedError},
+};
 use std::{
     cell::RefCell,
+    collections::HashMap,
     error::Error,
-    fmt::Display,
+    fmt::{Debug, Display},
     mem::replace,
-    ops::Deref,
+    ops::{AddAssign, Deref},
     sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         mpsc::{channel, Receiver, Sender},
-        Arc, Mutex, MutexGuard,
+        Arc, Barrier, Mutex, MutexGuard, Weak,
     },
     thread::{self, LocalKey, ThreadId},
+    time::Duration,
 };
 
 // Error consts
+const POISONED_CONTROL_MUTEX: &str = "poisoned control mutex";
 const RECEIVER_DISCONNECTED: &str = "receiver disconnected";
 
+/// Source of unique [`Control`] identifiers.
+static NEXT_CONTROL_ID: AtomicU64 = AtomicU64::new(0);
+
 /// Data structure transmitted on channel.
-enum ChannelItem<T> {
-    StopReceiving,
-    Payload(ThreadId, T),
-}
+type ChannelItem<T> = (ThreadId, T);
+
+/// Callback registered with [`Control::subscribe`].
+type Subscriber<T> = Box<dyn Fn(&T, ThreadId) + Send + Sync>;
 
 /// Status of background thread receiving on channel. from thread-locals.
 enum ReceiveStatus {
     Stopped,
+    /// All pending values have been received.
     CycleCompleted,
+    /// The batch size has been reached with values possibly still pending.
+    BatchCompleted,
 }
 
 /// Modes of receiving thread-local values on channel.
 enum ReceiveMode {
     Drain,
-    Background,
+    /// Receives at most `batch_size` values per cycle.
+    Background {
+        batch_size: usize,
+    },
+}
+
+/// Priority class of a value sent with [`Control::send_data_with_priority`]. When values are pending in both
+/// classes, the receiver receives the [`Priority::High`] values first, e.g., error reports ahead of bulk telemetry.
+/// Within a class, the values sent by each thread are received in the order in which they were sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    /// Priority of the values sent with [`Control::send_data`].
+    #[default]
+    Normal,
+    /// Priority of values that are received ahead of the pending [`Priority::Normal`] values.
+    High,
 }
 
 /// Indicates the illegal attempt to spawn multiple concurrent background receiving threads.
@@ -77,15 +117,46 @@ impl Error for MultipleReceiverThreadsError {}
 struct ChanneledState<T, U> {
     acc: U,
     receiver: Receiver<ChannelItem<T>>,
+    /// Receiver of the [`Priority::High`] values, which is drained before `receiver`.
+    high_receiver: Receiver<ChannelItem<T>>,
     bkgd_recv_exists: bool,
+    /// Set, without holding the lock, to signal the background receiver thread to terminate itself. Checked by that
+    /// thread before receiving each value, so it is not delayed by the values pending in the channel.
+    bkgd_stop: Arc<AtomicBool>,
+    /// Number of payloads sent but not yet received, shared with the senders.
+    depth: Arc<AtomicUsize>,
+    /// Optional buffer of the last payloads received, see [`Control::with_replay`].
+    replay: Option<Arc<History<ChannelItem<T>>>>,
+    /// Callbacks invoked for each payload received, see [`Control::subscribe`].
+    subscribers: Subscribers<T>,
+}
+
+/// Callbacks registered with [`Control::subscribe`].
+struct Subscribers<T>(Vec<Subscriber<T>>);
+
+impl<T> Debug for Subscribers<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Subscribers({})", self.0.len())
+    }
 }
 
 impl<T, U> ChanneledState<T, U> {
-    fn new(acc: U, receiver: Receiver<ChannelItem<T>>) -> Self {
+    fn new(
+        acc: U,
+        receiver: Receiver<ChannelItem<T>>,
+        high_receiver: Receiver<ChannelItem<T>>,
+        bkgd_stop: Arc<AtomicBool>,
+        depth: Arc<AtomicUsize>,
+    ) -> Self {
         Self {
             acc,
             receiver,
+            high_receiver,
             bkgd_recv_exists: false,
+            bkgd_stop,
+            depth,
+            replay: None,
+            subscribers: Subscribers(Vec::new()),
         }
     }
 
@@ -101,23 +172,41 @@ impl<T, U> ChanneledState<T, U> {
         &mut self,
         mode: ReceiveMode,
         op: &(dyn Fn(T, &mut U, ThreadId) + Send + Sync),
+        instr: &mut OpInstr,
     ) -> ReceiveStatus {
-        while let Ok(payload) = self.receiver.try_recv() {
-            match payload {
-                ChannelItem::Payload(tid, data) => op(data, &mut self.acc, tid),
-                ChannelItem::StopReceiving => match mode {
-                    ReceiveMode::Background => return ReceiveStatus::Stopped,
-                    ReceiveMode::Drain => continue,
-                },
+        let (stoppable, batch_size) = match mode {
+            ReceiveMode::Background { batch_size } => (true, batch_size),
+            ReceiveMode::Drain => (false, usize::MAX),
+        };
+        for _ in 0..batch_size {
+            if stoppable && self.bkgd_stop.load(Ordering::Acquire) {
+                return ReceiveStatus::Stopped;
+            }
+            let item = self
+                .high_receiver
+                .try_recv()
+                .or_else(|_| self.receiver.try_recv());
+            let Ok(item) = item else {
+                return ReceiveStatus::CycleCompleted;
+            };
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+            if let Some(replay) = &self.replay {
+                replay.record(&item);
             }
+            let (tid, data) = item;
+            instr.item(tid);
+            for subscriber in &self.subscribers.0 {
+                subscriber(&data, tid);
+            }
+            op(data, &mut self.acc, tid)
         }
-        ReceiveStatus::CycleCompleted
+        ReceiveStatus::BatchCompleted
     }
 }
 
 /// Guard object of a [`Control`]'s `acc` field. A lock is held during the guard's lifetime.
 #[derive(Debug)]
-struct AccGuard<'a, T, U>(MutexGuard<'a, ChanneledState<T, U>>);
+struct AccGuard<'a, T, U>(Tracked<MutexGuard<'a, ChanneledState<T, U>>>);
 
 impl<'a, T, U> Deref for AccGuard<'a, T, U> {
     type Target = U;
@@ -135,24 +224,68 @@ pub struct Control<T, U>
 where
     T: 'static,
 {
+    /// Unique identifier shared by all clones of this object.
+    id: u64,
     /// Reference to thread-local
     pub(crate) tl: &'static LocalKey<Holder<T>>,
     /// Keeps track of registered threads and accumulated value.
     state: Arc<Mutex<ChanneledState<T, U>>>,
     /// Sender on channel that is received by control.
     sender: Sender<ChannelItem<T>>,
+    /// Sender on the channel of [`Priority::High`] values.
+    high_sender: Sender<ChannelItem<T>>,
+    /// Stop signal of the background receiver thread, shared with `state`.
+    bkgd_stop: Arc<AtomicBool>,
+    /// Number of payloads sent on the channel but not yet received.
+    depth: Arc<AtomicUsize>,
     /// Operation that combines data from thread-locals with accumulated value.
     #[allow(clippy::type_complexity)]
     op: Arc<dyn Fn(T, &mut U, ThreadId) + Send + Sync>,
+    /// Optional diagnostics hook.
+    diag: Option<DiagHook>,
+    /// Optional bounded history of taken accumulated values.
+    history: Option<Arc<History<U>>>,
+    /// Maximum number of values received by the background receiver thread while holding the lock.
+    batch_size: usize,
+    /// Optional hook that configures the background receiver thread.
+    receiver_setup: Option<Arc<dyn Fn() + Send + Sync>>,
+    /// Optional tracker of the contribution activity of each thread.
+    activity: Option<Arc<ActivityTracker>>,
+    /// Accumulated values recycled for reuse as replacements of the accumulated value.
+    pool: Arc<AccPool<U>>,
+    /// Recorder of the latencies of contributions.
+    #[cfg(feature = "overhead-stats")]
+    overhead: Arc<OverheadRecorder>,
+    /// Set by [`Control::finalize`] to reject further contributions.
+    seal: Seal,
+    /// Enabled state, toggled with [`Control::set_enabled`].
+    enabled: Switch,
+    /// Process in which `self` was instantiated.
+    fork: ForkGuard,
 }
 
 impl<T, U> Clone for Control<T, U> {
     fn clone(&self) -> Self {
         Self {
+            id: self.id,
             tl: self.tl,
             state: self.state.clone(),
             sender: self.sender.clone(),
+            high_sender: self.high_sender.clone(),
+            bkgd_stop: self.bkgd_stop.clone(),
+            depth: self.depth.clone(),
             op: self.op.clone(),
+            diag: self.diag.clone(),
+            history: self.history.clone(),
+            batch_size: self.batch_size,
+            receiver_setup: self.receiver_setup.clone(),
+            activity: self.activity.clone(),
+            pool: self.pool.clone(),
+            #[cfg(feature = "overhead-stats")]
+            overhead: self.overhead.clone(),
+            seal: self.seal.clone(),
+            enabled: self.enabled.clone(),
+            fork: self.fork,
         }
     }
 }
@@ -169,20 +302,231 @@ impl<T, U> Control<T, U> {
         op: impl Fn(T, &mut U, ThreadId) + 'static + Send + Sync,
     ) -> Self {
         let (sender, receiver) = channel();
+        let (high_sender, high_receiver) = channel();
+        let bkgd_stop = Arc::new(AtomicBool::new(false));
+        let depth = Arc::new(AtomicUsize::new(0));
         Control {
+            id: NEXT_CONTROL_ID.fetch_add(1, Ordering::Relaxed),
             tl,
-            state: Arc::new(Mutex::new(ChanneledState::new(acc_base, receiver))),
+            state: Arc::new(Mutex::new(ChanneledState::new(
+                acc_base,
+                receiver,
+                high_receiver,
+                bkgd_stop.clone(),
+                depth.clone(),
+            ))),
             sender,
+            high_sender,
+            bkgd_stop,
+            depth,
             op: Arc::new(op),
+            diag: None,
+            history: None,
+            batch_size: usize::MAX,
+            receiver_setup: None,
+            activity: None,
+            pool: Arc::default(),
+            #[cfg(feature = "overhead-stats")]
+            overhead: Arc::default(),
+            seal: Seal::default(),
+            enabled: Switch::default(),
+            fork: ForkGuard::new(),
         }
     }
 
-    /// Acquires a lock on [`Control`]'s internal mutex.
+    /// Instantiates a *control* object whose accumulation is defined by the [`Accumulator`] implementation of `U`,
+    /// with [`Accumulator::zero`] as the initial value for accumulation and [`Accumulator::absorb`] as the
+    /// operation that combines data from thread-locals with the accumulated value.
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn from_accumulator(tl: &'static LocalKey<Holder<T>>) -> Self
+    where
+        U: Accumulator<Dat = T>,
+    {
+        Self::new(tl, U::zero(), |data, acc, tid| acc.absorb(data, tid))
+    }
+
+    /// Instantiates a *control* object whose accumulated value is `sink`, which accepts the data sent from threads
+    /// with [`Sink::accept`]. The sink is finished and returned with [`Self::drain_sink`].
+    ///
+    /// - `tl` - reference to thread-local static.
+    /// - `sink` - destination of the data sent from threads.
+    pub fn from_sink(tl: &'static LocalKey<Holder<T>>, sink: U) -> Self
+    where
+        U: Sink<T>,
+    {
+        Self::new(tl, sink, |data, sink, tid| sink.accept(data, tid))
+    }
+
+    /// Instantiates a *control* object for the common case of additive accumulations, e.g., counting or summing.
+    /// The initial value for accumulation is `U::default()` and data from thread-locals is combined with the
+    /// accumulated value with `+=`.
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn new_additive(tl: &'static LocalKey<Holder<T>>) -> Self
+    where
+        U: Default + AddAssign<T>,
+    {
+        Self::new(tl, U::default(), |data, acc, _| *acc += data)
+    }
+
+    /// Instantiates a *control* object for accumulators that implement [`Extend`], e.g., [`Vec`] or [`HashMap`],
+    /// without an `op` closure. The initial value for accumulation is `U::default()` and data from thread-locals is
+    /// combined with the accumulated value by extending it with a `(ThreadId, T)` pair.
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn from_extend(tl: &'static LocalKey<Holder<T>>) -> Self
+    where
+        U: Default + Extend<(ThreadId, T)>,
+    {
+        Self::new(tl, U::default(), |data, acc, tid| acc.extend([(tid, data)]))
+    }
+
+    /// Attaches a diagnostics hook that receives structured events about `self`'s internal operations.
+    /// See [`crate::diag`].
+    pub fn with_diagnostics(mut self, hook: impl Fn(&DiagEvent) + 'static + Send + Sync) -> Self {
+        self.diag = Some(Arc::new(hook));
+        self
+    }
+
+    /// Enables a bounded history that keeps clones of the last `capacity` accumulated values taken from `self`,
+    /// which can be retrieved with [`Self::history`]. The history is shared by all clones of `self`.
+    pub fn with_history(mut self, capacity: usize) -> Self
+    where
+        U: Clone,
+    {
+        self.history = Some(History::new(capacity));
+        self
+    }
+
+    /// Enables a replay buffer that keeps clones of the last `capacity` values received by `self`, together with the
+    /// [`ThreadId`]s of their senders, so that they can be aggregated again, into a separate value, with
+    /// [`Self::replay_tls`]. The buffer is shared by all clones of `self`.
     ///
     /// # Panics
     /// If `self`'s mutex is poisoned.
-    fn lock(&self) -> MutexGuard<'_, ChanneledState<T, U>> {
-        self.state.lock().expect(POISONED_CONTROL_MUTEX)
+    pub fn with_replay(self, capacity: usize) -> Self
+    where
+        T: Clone,
+    {
+        self.lock().replay = Some(History::new(capacity));
+        self
+    }
+
+    /// Sets the maximum number of values that the background receiver thread receives in each cycle, while holding
+    /// the lock on `self`'s state. The lock is released between cycles, so a smaller batch size lets readers of the
+    /// accumulated value, e.g., [`Self::acc`], proceed under load, at the cost of more lock acquisitions. By default,
+    /// each cycle receives all pending values. [`Self::drain_tls`] always receives all pending values.
+    ///
+    /// # Panics
+    /// If `batch_size` is zero.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Attaches a hook that is invoked at the start of each background receiver thread spawned by
+    /// [`Self::start_receiving_tls`], before any values are received. It can be used to configure the thread,
+    /// e.g., to lower its OS scheduling priority (niceness) with a platform-specific crate so that aggregation does
+    /// not compete with latency-critical worker threads.
+    pub fn with_receiver_setup(mut self, setup: impl Fn() + 'static + Send + Sync) -> Self {
+        self.receiver_setup = Some(Arc::new(setup));
+        self
+    }
+
+    /// Attaches a predicate that is applied to each value sent to `self`, together with the [`ThreadId`] of the
+    /// sending thread, before the value is combined with the accumulated value. Values for which the predicate
+    /// returns `false` are discarded. The predicate applies to all clones of `self` created afterwards.
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&T, ThreadId) -> bool + 'static + Send + Sync,
+    ) -> Self
+    where
+        T: 'static,
+        U: 'static,
+    {
+        let op = self.op;
+        self.op = Arc::new(move |data, acc, tid| {
+            if filter(&data, tid) {
+                op(data, acc, tid)
+            }
+        });
+        self
+    }
+
+    /// Wraps `op`, as configured so far, so that each invocation is logged with `logger`, which receives an
+    /// [`OpRecord`] with the sending thread, a summary of the data sent, and the time `op` took. This shows the exact
+    /// sequence in which data is combined with the accumulated value, e.g., to debug an accumulated value that comes
+    /// out wrong. The wrapper applies to all clones of `self` created afterwards.
+    ///
+    /// `logger` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    pub fn instrumented(mut self, logger: impl Fn(&OpRecord) + 'static + Send + Sync) -> Self
+    where
+        T: Debug + 'static,
+        U: 'static,
+    {
+        self.op = instr::instrument_op(self.op, logger);
+        self
+    }
+
+    /// Attaches an overflow hook: whenever, after data sent to `self` has been combined with the accumulated value,
+    /// the size of the accumulated value as computed by `size` exceeds `max_size`, the accumulated value is
+    /// passed to `spill` (e.g., to write it to disk) and replaced with `U::default()`. This keeps long-running
+    /// collections from growing in memory indefinitely. The hook applies to all clones of `self`
+    /// created afterwards.
+    ///
+    /// `spill` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    pub fn with_spill(
+        mut self,
+        max_size: usize,
+        size: impl Fn(&U) -> usize + 'static + Send + Sync,
+        spill: impl Fn(U) + 'static + Send + Sync,
+    ) -> Self
+    where
+        T: 'static,
+        U: Default + 'static,
+    {
+        let spill = Spill::new(max_size, size, spill, U::default);
+        let op = self.op;
+        self.op = Arc::new(move |data, acc, tid| {
+            op(data, acc, tid);
+            spill.check(acc);
+        });
+        self
+    }
+
+    /// Enables the tracking of the contribution activity of each thread, which can be retrieved with
+    /// [`Self::thread_activity`]. See [`crate::activity`].
+    pub fn with_activity_tracking(mut self) -> Self {
+        self.activity = Some(Arc::default());
+        self
+    }
+
+    /// Returns a clone of `self` that is linked to thread-local static `tl` instead of the thread-local static
+    /// `self` was instantiated with. The returned object shares `self`'s channel and accumulated value, so that
+    /// multiple thread-local statics (e.g., defined by separate subsystems) can contribute to a single aggregate.
+    /// Each thread-local static must be accessed through a control object linked to it.
+    pub fn for_tl(&self, tl: &'static LocalKey<Holder<T>>) -> Self {
+        Self { tl, ..self.clone() }
+    }
+
+    /// Returns whether `self` and `other` are the same control object or clones of each other, including clones
+    /// obtained with [`Self::for_tl`], i.e., whether they aggregate into the same accumulated value.
+    pub fn same_control(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+
+    /// Acquires a lock on [`Control`]'s internal mutex.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - With the **"fork-aware"** feature, if `self` is used in a child process created with `fork()`.
+    fn lock(&self) -> Tracked<MutexGuard<'_, ChanneledState<T, U>>> {
+        self.fork.check();
+        Tracked::acquire(LockKind::Control, self.state.as_ref(), || {
+            self.state.lock().expect(POISONED_CONTROL_MUTEX)
+        })
     }
 
     /// Returns a guard object that dereferences to `self`'s accumulated value. A lock is held during the guard's
@@ -194,6 +538,18 @@ impl<T, U> Control<T, U> {
         AccGuard(self.lock())
     }
 
+    /// Returns a guard object that dereferences to the part of `self`'s accumulated value selected by `f`, e.g., a
+    /// field, as in `control.acc_map(|acc| &acc.latency_hist)`. A lock is held during the guard's lifetime.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn acc_map<'a, V: ?Sized + 'a>(
+        &'a self,
+        f: impl Fn(&U) -> &V + 'a,
+    ) -> impl Deref<Target = V> + 'a {
+        MappedGuard::new(self.acc(), f)
+    }
+
     /// Provides access to `self`'s accumulated value.
     ///
     /// # Panics
@@ -203,6 +559,14 @@ impl<T, U> Control<T, U> {
         f(&acc)
     }
 
+    /// Provides mutable access to `self`'s accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub(super) fn with_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> V {
+        f(self.lock().acc_mut())
+    }
+
     /// Returns a clone of `self`'s accumulated value.
     ///
     /// # Panics
@@ -214,6 +578,18 @@ impl<T, U> Control<T, U> {
         self.acc().clone()
     }
 
+    /// Renders `self`'s accumulated value as a table with one `key  value` row per entry, e.g., for a debugging dump
+    /// of a per-thread map accumulator at shutdown. See [`Tabular`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn format_acc(&self) -> String
+    where
+        U: Tabular,
+    {
+        self.with_acc(Tabular::format_table)
+    }
+
     /// Returns `self`'s accumulated value, using a value of the same type to replace
     /// the existing accumulated value.
     ///
@@ -221,8 +597,120 @@ impl<T, U> Control<T, U> {
     /// If `self`'s mutex is poisoned.
     pub fn take_acc(&self, replacement: U) -> U {
         let mut lock = self.lock();
-        let acc = lock.acc_mut();
-        replace(acc, replacement)
+        let acc = replace(lock.acc_mut(), replacement);
+        history::record(self.history.as_ref(), &acc);
+        acc
+    }
+
+    /// Same as [`Self::take_acc`], except that the replacement is a value previously returned to `self` or one of
+    /// its clones with [`Self::recycle_acc`], if any, or `U::default()` otherwise. This avoids reallocating, e.g.,
+    /// large map accumulators that are taken at high frequency.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_acc_recycled(&self) -> U
+    where
+        U: Default,
+    {
+        self.take_acc(self.pool.take())
+    }
+
+    /// Returns `acc`, typically a value taken from `self` that is no longer needed, to `self` for reuse by
+    /// [`Self::take_acc_recycled`] and [`Self::drain_and_reset_recycled`]. `acc` is reset with
+    /// [`Recycle::recycle`], which retains its allocations. At most a few values are kept; further ones are dropped.
+    ///
+    /// # Panics
+    /// If the mutex of `self`'s pool is poisoned.
+    pub fn recycle_acc(&self, acc: U)
+    where
+        U: Recycle,
+    {
+        self.pool.put(acc)
+    }
+
+    /// Returns the last accumulated values taken from `self` with [`Self::take_acc`] or [`Self::drain_and_reset`],
+    /// from oldest to newest, if history is enabled with [`Self::with_history`], or an empty vector otherwise.
+    ///
+    /// # Panics
+    /// If the history's mutex is poisoned.
+    pub fn history(&self) -> Vec<U> {
+        history::snapshot(self.history.as_ref())
+    }
+
+    /// Returns the contribution activity of each thread that has contributed to `self` since activity tracking was
+    /// enabled with [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn thread_activity(&self) -> HashMap<ThreadId, ThreadActivity> {
+        activity::snapshot(self.activity.as_ref())
+    }
+
+    /// Returns the number of contributions made by each thread to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn contribution_counts(&self) -> HashMap<ThreadId, u64> {
+        activity::counts(self.activity.as_ref())
+    }
+
+    /// Returns the threads that have contributed to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`] but have not contributed in the last `since`, e.g., so that a supervisor can
+    /// detect hung workers, or an empty vector if tracking is not enabled. Threads that have terminated are included,
+    /// since the tracker does not observe thread termination.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn idle_threads(&self, since: Duration) -> Vec<ThreadId> {
+        activity::idle(self.activity.as_ref(), since)
+    }
+
+    /// Returns the latency statistics of the contributions made to `self` and its clones. See [`crate::overhead`].
+    /// Requires the **"overhead-stats"** feature.
+    #[cfg(feature = "overhead-stats")]
+    pub fn overhead_stats(&self) -> OverheadStats {
+        self.overhead.snapshot()
+    }
+
+    /// Returns the approximate number of values sent to `self` and its clones that have not yet been received by
+    /// [`Self::drain_tls`] or the background receiver thread, e.g., to alert when the aggregation falls behind the
+    /// participating threads. The count is updated without locking, so it may be momentarily off while values are
+    /// being sent or received.
+    pub fn queue_depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// Returns a read-only handle to `self`.
+    pub fn reader(&self) -> ControlReader<Self> {
+        ControlReader(self.clone())
+    }
+
+    /// Returns a send-only handle to `self`.
+    pub fn sender(&self) -> ControlSender<Self> {
+        ControlSender(self.clone())
+    }
+
+    /// Returns a send-only handle to `self` that accepts values of type `T2` and converts them to `T` with `f`
+    /// before sending them.
+    pub fn map_input<T2, F>(&self, f: F) -> MappedSender<Self, F>
+    where
+        F: Fn(T2) -> T,
+    {
+        MappedSender {
+            control: self.clone(),
+            f: Arc::new(f),
+        }
+    }
+
+    /// Wraps `self` in a [`FinishedControl`] whose [`take_acc`](FinishedControl::take_acc) and
+    /// [`drain_and_reset`](FinishedControl::drain_and_reset) methods return the accumulated value post-processed with
+    /// `finish`.
+    pub fn with_finisher<V, F>(self, finish: F) -> FinishedControl<Self, F>
+    where
+        F: Fn(U) -> V,
+    {
+        FinishedControl::new(self, finish)
     }
 
     /// Spawns a background thread to receive thread-local values and aggregate them with this object's
@@ -235,6 +723,39 @@ impl<T, U> Control<T, U> {
     /// # Panics
     /// If `self`'s mutex is poisoned.
     pub fn start_receiving_tls(&self) -> Result<(), MultipleReceiverThreadsError>
+    where
+        T: 'static + Send,
+        U: 'static + Send,
+    {
+        self.spawn_receiver(None)
+    }
+
+    /// Same as [`Self::start_receiving_tls`], except that the background thread also terminates itself, after
+    /// receiving the pending messages, once `cancel` is set to `true`. This allows the background thread to
+    /// participate in application-wide shutdown without an in-band call to [`Self::stop_receiving_tls`].
+    ///
+    /// # Errors
+    /// Returns an error if there is already an active background receiver thread.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn start_receiving_tls_until(
+        &self,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<(), MultipleReceiverThreadsError>
+    where
+        T: 'static + Send,
+        U: 'static + Send,
+    {
+        self.spawn_receiver(Some(cancel))
+    }
+
+    /// Spawns the background receiver thread, which terminates itself when it receives a stop message or, if
+    /// `cancel` is provided, when `cancel` is set.
+    fn spawn_receiver(
+        &self,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<(), MultipleReceiverThreadsError>
     where
         T: 'static + Send,
         U: 'static + Send,
@@ -245,16 +766,33 @@ impl<T, U> Control<T, U> {
             return Err(MultipleReceiverThreadsError);
         }
         state.bkgd_recv_exists = true;
+        // Discard a stop signal sent when there was no background receiver thread.
+        self.bkgd_stop.store(false, Ordering::Release);
         drop(state);
 
         let control = self.clone();
         thread::spawn(move || {
+            if let Some(setup) = &control.receiver_setup {
+                setup();
+            }
             loop {
+                // Checked before receiving, so that messages sent before cancellation are received.
+                let cancelled = cancel
+                    .as_ref()
+                    .is_some_and(|cancel| cancel.load(Ordering::Acquire));
                 let mut state = control.lock();
-                let res = state.receive_tls(ReceiveMode::Background, control.op.as_ref());
-                if let ReceiveStatus::Stopped = res {
+                let mode = ReceiveMode::Background {
+                    batch_size: control.batch_size,
+                };
+                let mut instr = OpInstr::start_batch("receive_tls", control.diag.as_ref());
+                let res = state.receive_tls(mode, control.op.as_ref(), &mut instr);
+                drop(instr);
+                if let (ReceiveStatus::Stopped, _) | (ReceiveStatus::CycleCompleted, true) =
+                    (res, cancelled)
+                {
                     // Restore background thread status.
                     state.bkgd_recv_exists = false;
+                    state.bkgd_stop.store(false, Ordering::Release);
                     break;
                 }
                 drop(state); // release lock before yielding!
@@ -264,11 +802,61 @@ impl<T, U> Control<T, U> {
         Ok(())
     }
 
-    /// Signals the background receiving thread to terminate itself.
+    /// Returns `true` if a background receiver thread started with [`Self::start_receiving_tls`] or
+    /// [`Self::start_receiving_tls_until`] is active, in which case another one cannot be started. A stopped
+    /// background receiver thread remains active until it observes the stop signal.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn is_receiving(&self) -> bool {
+        self.lock().bkgd_recv_exists
+    }
+
+    /// Signals the background receiving thread to terminate itself. The signal is sent out of band, so it takes
+    /// effect before the next value is received, rather than after all the values already sent, which may take long
+    /// under load. The values still pending in the channel are received by the next call to [`Self::drain_tls`] or
+    /// by the next background receiver thread. Does nothing if there is no background receiver thread.
+    ///
+    /// This method cannot fail: unlike the former in-band stop message, the signal does not depend on the channel's
+    /// receiver, so it does not panic when it races with the termination of the background receiver thread or
+    /// the dropping of other clones of `self`, e.g., in shutdown sequences.
     pub fn stop_receiving_tls(&self) {
-        self.sender
-            .send(ChannelItem::StopReceiving)
-            .expect(RECEIVER_DISCONNECTED);
+        self.bkgd_stop.store(true, Ordering::Release);
+    }
+
+    /// Aggregates the values kept in the replay buffer enabled with [`Self::with_replay`], from oldest to newest,
+    /// with `acc_base`, and returns the result. `self`'s accumulated value, which already includes the replayed
+    /// values, and the buffer are left unchanged. Returns `acc_base` if the buffer is not enabled.
+    ///
+    /// Values sent before values start being received are not lost, as they remain in the channel until they are
+    /// received. The replay buffer is for a consumer that starts after values have already been received and
+    /// aggregated, e.g., to seed its own view with the recent history.
+    ///
+    /// # Panics
+    /// If `self`'s mutex or the replay buffer's mutex is poisoned.
+    pub fn replay_tls(&self, acc_base: U) -> U {
+        let replay = self.lock().replay.clone();
+        let mut acc = acc_base;
+        if let Some(replay) = replay {
+            for (tid, data) in replay.snapshot() {
+                (self.op)(data, &mut acc, tid);
+            }
+        }
+        acc
+    }
+
+    /// Registers `subscriber`, which is invoked with each value received by `self` or its clones, together with the
+    /// [`ThreadId`] of the sending thread, before the value is combined with the accumulated value, e.g., to stream
+    /// the values live to a console viewer while they are also accumulated. Values received by the background
+    /// receiver thread and by [`Self::drain_tls`] are passed to the subscribers alike, in the order in which they are
+    /// received, but values aggregated again with [`Self::replay_tls`] are not.
+    ///
+    /// `subscriber` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn subscribe(&self, subscriber: impl Fn(&T, ThreadId) + 'static + Send + Sync) {
+        self.lock().subscribers.0.push(Box::new(subscriber));
     }
 
     /// Receives all pending messages in channel and aggregates the corresponding values,
@@ -278,64 +866,388 @@ impl<T, U> Control<T, U> {
     /// # Panics
     /// If `self`'s mutex is poisoned.
     pub fn drain_tls(&self) {
+        let mut instr = OpInstr::start("drain_tls", self.diag.as_ref());
         self.stop_receiving_tls();
         self.lock()
-            .receive_tls(ReceiveMode::Drain, self.op.as_ref());
+            .receive_tls(ReceiveMode::Drain, self.op.as_ref(), &mut instr);
+    }
+
+    /// Receives all pending messages in channel, aggregates the corresponding values, and returns `self`'s
+    /// accumulated value, using `replacement` to replace the existing accumulated value. The background thread
+    /// is terminated if it exists.
+    ///
+    /// Unlike a call to [`Self::drain_tls`] followed by a call to [`Self::take_acc`], the lock on `self`'s state is
+    /// held throughout, so no other thread can observe or update the accumulated value in between.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn drain_and_reset(&self, replacement: U) -> U {
+        let mut instr = OpInstr::start("drain_and_reset", self.diag.as_ref());
+        self.stop_receiving_tls();
+        let mut lock = self.lock();
+        lock.receive_tls(ReceiveMode::Drain, self.op.as_ref(), &mut instr);
+        let acc = replace(lock.acc_mut(), replacement);
+        history::record(self.history.as_ref(), &acc);
+        acc
+    }
+
+    /// Same as [`Self::drain_and_reset`], except that [`Sink::finish`] is called on the accumulated value, e.g., a
+    /// sink passed to [`Self::from_sink`], before it is returned.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn drain_sink(&self, replacement: U) -> U
+    where
+        U: Sink<T>,
+    {
+        let mut sink = self.drain_and_reset(replacement);
+        sink.finish();
+        sink
+    }
+
+    /// Same as [`Self::drain_and_reset`], with a replacement obtained as with [`Self::take_acc_recycled`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn drain_and_reset_recycled(&self) -> U
+    where
+        U: Default,
+    {
+        self.drain_and_reset(self.pool.take())
+    }
+
+    /// Coordinates with the participating threads through `barrier` to take a consistent cut of their
+    /// contributions, for phase-based programs. `barrier` must be shared by the participating threads and the thread
+    /// calling this method, each of which must call [`Barrier::wait`] twice: once when it has finished contributing
+    /// to the current phase, and again before it starts contributing to the next phase.
+    ///
+    /// This method waits at `barrier` until all participating threads have finished the current phase,
+    /// takes the accumulated value as with [`Self::drain_and_reset`] (which includes terminating
+    /// the background thread if it exists), and then waits at `barrier` again to release the participating threads. The taken value is returned.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    ///
+    /// If this method panics, the participating threads will block at their second wait on `barrier`.
+    pub fn take_at_barrier(&self, barrier: &Barrier, replacement: U) -> U {
+        barrier.wait();
+        let acc = self.drain_and_reset(replacement);
+        barrier.wait();
+        acc
+    }
+
+    /// Finalizes `self`: receives all pending messages in channel and returns the accumulated value as with
+    /// [`Self::drain_and_reset`], after sealing `self` and its clones so that data sent afterwards is rejected
+    /// rather than left in the channel or aggregated into `replacement`: [`Self::try_send_data`] returns
+    /// [`AccessError::Sealed`], and [`Self::send_data`] panics.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn finalize(&self, replacement: U) -> U {
+        self.seal.seal();
+        self.drain_and_reset(replacement)
+    }
+
+    /// Returns `true` if `self` has been finalized with [`Self::finalize`].
+    pub fn is_sealed(&self) -> bool {
+        self.seal.is_sealed()
+    }
+
+    /// Enables or disables contributions to `self` and its clones. While disabled, data passed to
+    /// [`Self::send_data`] and [`Self::send_data_with_priority`] is dropped, and data passed to their `try_`
+    /// counterparts is rejected with [`AccessError::Disabled`], at the cost of a single relaxed atomic load, before
+    /// any locking or allocation. Data already sent is not affected. Control objects are enabled when instantiated,
+    /// unless collection is compiled out with the `thread_local_collect_off` or `thread_local_collect_release_off`
+    /// option, in which case they are always disabled.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled)
+    }
+
+    /// Returns whether contributions to `self` are enabled. See [`Self::set_enabled`].
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.is_enabled()
     }
 
-    /// Sends data from the thread where it is called to be accumulated by the [`Control`] instance;
+    /// Sends data from the thread where it is called to be accumulated by the [`Control`] instance; does nothing if
+    /// `self` is disabled (see [`Self::set_enabled`]) or collection is compiled out with the
+    /// `thread_local_collect_off` or `thread_local_collect_release_off` option.
+    ///
+    /// # Panics
+    /// If `self` has been finalized with [`Self::finalize`], in which case [`Self::try_send_data`] returns
+    /// [`AccessError::Sealed`] instead, or the current thread's thread-local variable has been destroyed.
     pub fn send_data(&self, data: T) {
-        self.tl.with(|h| {
+        if !self.is_enabled() {
+            return;
+        }
+        self.try_send_data(data).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Sends data from the thread where it is called to be accumulated by the [`Control`] instance, unless
+    /// `self` has been finalized with [`Self::finalize`] or the current thread's thread-local variable has been
+    /// destroyed, e.g., when called from the destructor of another thread-local variable during thread teardown.
+    ///
+    /// # Errors
+    /// Returns an [`AccessError`] if `self` has been finalized or the thread-local variable has been destroyed, in
+    /// which case `data` is dropped.
+    pub fn try_send_data(&self, data: T) -> Result<(), AccessError> {
+        self.try_send_data_with_priority(data, Priority::Normal)
+    }
+
+    /// Same as [`Self::send_data`], with the given [`Priority`].
+    ///
+    /// # Panics
+    /// If `self` has been finalized with [`Self::finalize`], in which case [`Self::try_send_data_with_priority`]
+    /// returns [`AccessError::Sealed`] instead, or the current thread's thread-local variable has been destroyed.
+    pub fn send_data_with_priority(&self, data: T, priority: Priority) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.try_send_data_with_priority(data, priority)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Same as [`Self::try_send_data`], with the given [`Priority`].
+    ///
+    /// # Errors
+    /// Returns an [`AccessError`] if `self` has been finalized or the thread-local variable has been destroyed, in
+    /// which case `data` is dropped.
+    pub fn try_send_data_with_priority(
+        &self,
+        data: T,
+        priority: Priority,
+    ) -> Result<(), AccessError> {
+        self.enabled.check()?;
+        self.seal.check()?;
+        #[cfg(feature = "overhead-stats")]
+        let _timer = self.overhead.timer();
+        activity::record(self.activity.as_ref(), thread::current().id());
+        let res = self.tl.try_with(|h| {
             h.ensure_linked(self);
-            h.send_data(data, self)
-        })
+            h.send_data(data, priority, self)
+        });
+        Ok(res.map_err(|_| TlsDestroyedError)?)
+    }
+}
+
+impl<T, U> ControlReader<Control<T, U>> {
+    /// Returns a guard object that dereferences to the accumulated value. A lock is held during the guard's
+    /// lifetime.
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn acc(&self) -> impl Deref<Target = U> + '_ {
+        self.0.acc()
+    }
+
+    /// Returns a guard object that dereferences to the part of the accumulated value selected by `f`. A lock is held
+    /// during the guard's lifetime. See [`Control::acc_map`].
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn acc_map<'a, V: ?Sized + 'a>(
+        &'a self,
+        f: impl Fn(&U) -> &V + 'a,
+    ) -> impl Deref<Target = V> + 'a {
+        self.0.acc_map(f)
+    }
+
+    /// Provides access to the accumulated value.
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn with_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V {
+        self.0.with_acc(f)
+    }
+
+    /// Returns a clone of the accumulated value.
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn clone_acc(&self) -> U
+    where
+        U: Clone,
+    {
+        self.0.clone_acc()
+    }
+
+    /// Returns the approximate number of values sent to the control object that have not yet been received. See
+    /// [`Control::queue_depth`].
+    pub fn queue_depth(&self) -> usize {
+        self.0.queue_depth()
+    }
+}
+
+impl<T, U> ControlSender<Control<T, U>>
+where
+    T: Send + 'static,
+    U: 'static,
+{
+    /// Sends data from the thread where it is called to be accumulated by the [`Control`] instance.
+    /// See [`Control::send_data`].
+    ///
+    /// # Panics
+    /// If the [`Control`] instance has been finalized or the current thread's thread-local variable has been
+    /// destroyed. See [`Self::try_send_data`].
+    pub fn send_data(&self, data: T) {
+        self.0.send_data(data)
+    }
+
+    /// Sends data from the thread where it is called to be accumulated by the [`Control`] instance, unless it has
+    /// been finalized or the current thread's thread-local variable has been destroyed.
+    /// See [`Control::try_send_data`].
+    ///
+    /// # Errors
+    /// Returns an [`AccessError`] if the [`Control`] instance has been finalized or the thread-local variable has
+    /// been destroyed.
+    pub fn try_send_data(&self, data: T) -> Result<(), AccessError> {
+        self.0.try_send_data(data)
+    }
+
+    /// Sends data with the given [`Priority`]. See [`Control::send_data_with_priority`].
+    ///
+    /// # Panics
+    /// If the [`Control`] instance has been finalized or the current thread's thread-local variable has been
+    /// destroyed. See [`Control::try_send_data_with_priority`].
+    pub fn send_data_with_priority(&self, data: T, priority: Priority) {
+        self.0.send_data_with_priority(data, priority)
+    }
+
+    /// Returns a send-only handle that accepts values of type `T2` and converts them to `T` with `f` before
+    /// sending them. See [`Control::map_input`].
+    pub fn map_input<T2, F>(&self, f: F) -> MappedSender<Control<T, U>, F>
+    where
+        F: Fn(T2) -> T,
+    {
+        self.0.map_input(f)
+    }
+}
+
+impl<T, U, F, V> FinishedControl<Control<T, U>, F>
+where
+    F: Fn(U) -> V,
+{
+    /// Returns the control object's accumulated value post-processed with the finisher, using `replacement` to
+    /// replace the existing accumulated value. See [`Control::take_acc`].
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn take_acc(&self, replacement: U) -> V {
+        (self.finish)(self.control.take_acc(replacement))
+    }
+
+    /// Receives all pending messages in channel and returns the control object's accumulated value post-processed
+    /// with the finisher, using `replacement` to replace the existing accumulated value.
+    /// See [`Control::drain_and_reset`].
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn drain_and_reset(&self, replacement: U) -> V {
+        (self.finish)(self.control.drain_and_reset(replacement))
+    }
+}
+
+impl<T, U, F> MappedSender<Control<T, U>, F>
+where
+    T: Send + 'static,
+    U: 'static,
+{
+    /// Converts `data` with the handle's conversion function and sends the result from the thread where it is
+    /// called to be accumulated by the [`Control`] instance. See [`Control::send_data`].
+    ///
+    /// # Panics
+    /// If the [`Control`] instance has been finalized or the current thread's thread-local variable has been
+    /// destroyed. See [`Control::try_send_data`].
+    pub fn send_data<T2>(&self, data: T2)
+    where
+        F: Fn(T2) -> T,
+    {
+        self.control.send_data((self.f)(data))
     }
 }
 
 /// Inner state of [`Holder`].
 struct HolderInner<T> {
+    control_id: u64,
     tid: ThreadId,
     sender: Sender<ChannelItem<T>>,
+    high_sender: Sender<ChannelItem<T>>,
+    /// Weak reference to the control object's channel depth, which is dropped with the last clone of the control
+    /// object and its receiver. Used to prune the slots of control objects that no longer exist.
+    depth: Weak<AtomicUsize>,
+}
+
+impl<T> HolderInner<T> {
+    /// Returns whether the control object of this slot still exists.
+    fn is_live(&self) -> bool {
+        self.depth.strong_count() > 0
+    }
 }
 
-/// Holds a thread-local [`Sender`], enabling the linkage of the thread-local with the control object.
+/// Holds thread-local [`Sender`]s, enabling the linkage of the thread-local with control objects.
+///
+/// A single thread-local static of this type may be shared by multiple [`Control`] objects (possibly with
+/// different accumulated value types), in which case the holder keeps a separate slot, keyed by the identity of the
+/// control object, for each control object linked to it. The slots of control objects that have been dropped are
+/// pruned when the holder is linked to another control object.
 ///
 /// `T` is the type of data sent on the channel.
-pub struct Holder<T>(RefCell<Option<HolderInner<T>>>)
+pub struct Holder<T>(RefCell<Vec<HolderInner<T>>>)
 where
     T: 'static;
 
-impl<T> Holder<T> {
-    /// Instantiates a holder object.
-    pub fn new() -> Self {
-        Self(RefCell::new(None))
+impl<T> Holder<T> {
+    /// Instantiates a holder object. As this is a `const fn`, the thread-local can be declared with a `const`
+    /// initializer, e.g., `static MY_TL: Holder<i32> = const { Holder::new() };`.
+    pub const fn new() -> Self {
+        Self(RefCell::new(Vec::new()))
+    }
+
+    /// Returns whether `self` has been linked to any control object, which happens on the first call to
+    /// [`Control::send_data`] in the current thread. Call it through the thread-local static, e.g.,
+    /// `MY_TL.with(Holder::is_linked)`.
+    pub fn is_linked(&self) -> bool {
+        self.0.borrow().iter().any(HolderInner::is_live)
+    }
+
+    /// Returns whether `self` has been linked to `control`.
+    pub fn is_linked_to<U>(&self, control: &Control<T, U>) -> bool {
+        self.0
+            .borrow()
+            .iter()
+            .any(|slot| slot.control_id == control.id)
     }
 
     /// Ensures `self` is linked to control.
     fn ensure_linked<U>(&self, control: &Control<T, U>) {
         let mut inner = self.0.borrow_mut();
-        if inner.is_none() {
-            let sender = control.sender.clone();
-            *inner = Some(HolderInner {
+        if !inner.iter().any(|slot| slot.control_id == control.id) {
+            inner.retain(HolderInner::is_live);
+            inner.push(HolderInner {
+                control_id: control.id,
                 tid: thread::current().id(),
-                sender,
+                sender: control.sender.clone(),
+                high_sender: control.high_sender.clone(),
+                depth: Arc::downgrade(&control.depth),
             })
         }
     }
 
-    /// Send data to be aggregated in the `control` object. Returns an error if [`Holder`] is
-    /// not initialized.
-    fn send_data<U>(&self, data: T, control: &Control<T, U>) {
+    /// Send data to be aggregated in the `control` object.
+    fn send_data<U>(&self, data: T, priority: Priority, control: &Control<T, U>) {
         self.ensure_linked(control);
-        let inner_opt = self.0.borrow();
-        match inner_opt.deref() {
-            Some(inner) => {
-                inner
-                    .sender
-                    .send(ChannelItem::Payload(inner.tid, data))
-                    .expect(RECEIVER_DISCONNECTED);
+        let inner = self.0.borrow();
+        match inner.iter().find(|slot| slot.control_id == control.id) {
+            Some(slot) => {
+                // Incremented before sending so that the receiver never decrements it below zero.
+                control.depth.fetch_add(1, Ordering::Relaxed);
+                let sender = match priority {
+                    Priority::Normal => &slot.sender,
+                    Priority::High => &slot.high_sender,
+                };
+                sender.send((slot.tid, data)).expect(RECEIVER_DISCONNECTED);
             }
-            None => unreachable!("Holder should be initialized by now"),
+            None => unreachable!("Holder should be linked by now"),
         }
     }
 }
@@ -343,13 +1255,18 @@ impl<T> Holder<T> {
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use super::{Control, Holder, MultipleReceiverThreadsError};
+    use super::{Control, Holder, MultipleReceiverThreadsError, Priority};
     use crate::dev_support::{assert_eq_and_println, ThreadGater};
+    use crate::diag::DiagEvent;
+    use crate::tlm::{AccessError, DisabledError, SealedError};
     use std::{
         collections::HashMap,
         fmt::Debug,
         ops::Deref,
-        sync::Mutex,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc, Arc, Barrier, Mutex,
+        },
         thread::{self, ThreadId},
         time::Duration,
     };
@@ -362,7 +1279,8 @@ mod tests {
     type AccValue = HashMap<ThreadId, HashMap<i32, Foo>>;
 
     thread_local! {
-        static MY_TL: Holder<Data> = Holder::new();
+        static MY_TL: Holder<Data> = const { Holder::new() };
+        static MY_TL2: Holder<Data> = const { Holder::new() };
     }
 
     fn op(data: Data, acc: &mut AccValue, tid: ThreadId) {
@@ -572,4 +1490,813 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn drain_and_reset() {
+        let control = Control::new(&MY_TL, HashMap::new(), op);
+
+        let (tid_spawned, map_spawned) = thread::scope(|s| {
+            s.spawn(|| {
+                control.send_data((1, Foo("a".to_owned())));
+                control.send_data((2, Foo("b".to_owned())));
+                let map = HashMap::from([(1, Foo("a".to_owned())), (2, Foo("b".to_owned()))]);
+                (thread::current().id(), map)
+            })
+            .join()
+            .unwrap()
+        });
+
+        let acc = control.drain_and_reset(HashMap::new());
+        let map = HashMap::from([(tid_spawned, map_spawned)]);
+        assert_eq_and_println(&acc, &map, "drain_and_reset");
+
+        control.with_acc(|acc| {
+            assert_eq_and_println(acc, &HashMap::new(), "Accumulator after drain_and_reset");
+        });
+
+        let acc = control.drain_and_reset(HashMap::new());
+        assert_eq_and_println(&acc, &HashMap::new(), "2nd drain_and_reset");
+    }
+
+    #[test]
+    fn queue_depth() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        let reader = control.reader();
+        assert_eq_and_println(&control.queue_depth(), &0, "empty queue");
+
+        let sender = control.sender();
+        thread::spawn(move || (1..=3).for_each(|i| sender.send_data(i)))
+            .join()
+            .unwrap();
+        assert_eq_and_println(&reader.queue_depth(), &3, "values pending");
+
+        control.drain_tls();
+        assert_eq_and_println(&control.queue_depth(), &0, "values received");
+        assert_eq_and_println(&control.clone_acc(), &6, "accumulated value");
+    }
+
+    #[test]
+    fn format_acc() {
+        use crate::accumulator::{PerThread, Summary};
+
+        thread_local! {
+            static U64_TL: Holder<u64> = const { Holder::new() };
+            static SUMMARY_TL: Holder<u64> = const { Holder::new() };
+        }
+
+        let control = Control::<u64, PerThread<u64>>::from_accumulator(&U64_TL);
+        let tids = (1..=2)
+            .map(|i| {
+                let sender = control.sender();
+                thread::spawn(move || {
+                    sender.send_data(i);
+                    thread::current().id()
+                })
+                .join()
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        control.drain_tls();
+
+        let mut expected = tids
+            .iter()
+            .zip(1..=2)
+            .map(|(tid, i)| format!("{tid:?}  {i}\n"))
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq_and_println(&control.format_acc(), &expected.concat(), "format_acc");
+        assert_eq_and_println(
+            &control.clone_acc().to_string(),
+            &expected.concat(),
+            "Display",
+        );
+
+        let control = Control::<u64, Summary<u64>>::from_accumulator(&SUMMARY_TL);
+        assert_eq_and_println(
+            &control.clone_acc().to_string(),
+            &"count=0 sum=0 min=- max=- mean=-".to_owned(),
+            "empty summary",
+        );
+        control.send_data(1);
+        control.send_data(4);
+        control.drain_tls();
+        assert_eq_and_println(
+            &control.clone_acc().to_string(),
+            &"count=2 sum=5 min=1 max=4 mean=2.5".to_owned(),
+            "summary",
+        );
+    }
+
+    #[test]
+    fn send_data_with_priority() {
+        thread_local! {static STR_TL: Holder<&'static str> = const { Holder::new() };}
+
+        let control = Control::new(&STR_TL, Vec::new(), |s, acc: &mut Vec<_>, _| acc.push(s));
+        let sender = control.sender();
+        thread::spawn(move || {
+            sender.send_data("bulk1");
+            sender.send_data_with_priority("error1", Priority::High);
+            sender.send_data("bulk2");
+            sender.send_data_with_priority("error2", Priority::High);
+        })
+        .join()
+        .unwrap();
+
+        assert_eq_and_println(&control.queue_depth(), &4, "values pending");
+        control.drain_tls();
+        assert_eq_and_println(
+            &control.clone_acc(),
+            &vec!["error1", "error2", "bulk1", "bulk2"],
+            "high priority first, FIFO within class",
+        );
+    }
+
+    #[test]
+    fn history() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL).with_history(2);
+        assert_eq_and_println(&control.history(), &vec![], "empty history");
+
+        for i in 1..=3 {
+            control.send_data(i);
+            control.drain_and_reset(0);
+        }
+        assert_eq_and_println(&control.history(), &vec![2, 3], "history after drains");
+
+        control.send_data(4);
+        control.drain_tls();
+        control.take_acc(0);
+        assert_eq_and_println(&control.history(), &vec![3, 4], "history after take_acc");
+    }
+
+    #[test]
+    fn with_filter() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control =
+            Control::<i32, i32>::new_additive(&I32_TL).with_filter(|data, _| data % 2 == 0);
+
+        for i in 1..=4 {
+            control.send_data(i);
+        }
+        thread::scope(|s| {
+            s.spawn(|| control.send_data(10));
+            s.spawn(|| control.send_data(11));
+        });
+
+        control.drain_tls();
+        assert_eq_and_println(&control.clone_acc(), &16, "with_filter");
+    }
+
+    #[test]
+    fn with_activity_tracking() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        control.send_data(1);
+        assert!(control.thread_activity().is_empty(), "tracking disabled");
+
+        let control = control.with_activity_tracking();
+        let sender = control.sender();
+        let spawned_tid = thread::spawn(move || {
+            sender.send_data(2);
+            thread::current().id()
+        })
+        .join()
+        .unwrap();
+        control.send_data(3);
+        control.drain_tls();
+
+        let activity = control.thread_activity();
+        assert_eq_and_println(&activity.len(), &2, "threads tracked");
+        assert!(
+            activity[&spawned_tid].last_seen < activity[&thread::current().id()].first_seen,
+            "activity order"
+        );
+        assert_eq_and_println(&control.clone_acc(), &6, "accumulated value");
+    }
+
+    #[test]
+    fn idle_threads() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        control.send_data(1);
+        assert!(
+            control.idle_threads(Duration::ZERO).is_empty(),
+            "tracking disabled"
+        );
+
+        let control = control.with_activity_tracking();
+        let sender = control.sender();
+        let spawned_tid = thread::spawn(move || {
+            sender.send_data(2);
+            thread::current().id()
+        })
+        .join()
+        .unwrap();
+        thread::sleep(Duration::from_millis(100));
+        control.send_data(3);
+
+        assert_eq_and_println(
+            &control.idle_threads(Duration::from_millis(50)),
+            &vec![spawned_tid],
+            "idle threads",
+        );
+        control.drain_tls();
+    }
+
+    #[test]
+    fn is_linked() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control1 = Control::<i32, i32>::new_additive(&I32_TL);
+        let control2 = Control::<i32, i32>::new_additive(&I32_TL);
+        assert!(!I32_TL.with(Holder::is_linked), "not linked");
+
+        control1.send_data(1);
+        assert!(I32_TL.with(Holder::is_linked), "linked");
+        assert!(
+            I32_TL.with(|h| h.is_linked_to(&control1)),
+            "linked to control1"
+        );
+        assert!(
+            !I32_TL.with(|h| h.is_linked_to(&control2)),
+            "not linked to control2"
+        );
+    }
+
+    #[test]
+    fn prune_dropped_controls() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        for i in 0..1000 {
+            let control = Control::<i32, i32>::new_additive(&I32_TL);
+            control.send_data(i);
+            assert_eq_and_println(&control.drain_and_reset(0), &i, "drained value");
+        }
+        assert!(!I32_TL.with(Holder::is_linked), "dropped controls unlinked");
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        control.send_data(1);
+        assert_eq_and_println(&I32_TL.with(|h| h.0.borrow().len()), &1, "slots");
+    }
+
+    #[test]
+    fn finalize() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        control.start_receiving_tls().unwrap();
+        let sender = control.sender();
+        thread::spawn(move || sender.send_data(2)).join().unwrap();
+        control.send_data(1);
+
+        assert_eq_and_println(&control.finalize(0), &3, "finalize");
+        assert!(control.is_sealed(), "sealed");
+        let sender = control.sender();
+        let res = thread::spawn(move || sender.try_send_data(4))
+            .join()
+            .unwrap();
+        assert_eq_and_println(
+            &res,
+            &Err(AccessError::Sealed(SealedError)),
+            "send after finalize",
+        );
+        control.drain_tls();
+        assert_eq_and_println(&control.clone_acc(), &0, "rejected data");
+    }
+
+    #[test]
+    fn set_enabled() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        control.send_data(1);
+        control.set_enabled(false);
+        let sender = control.sender();
+        thread::spawn(move || sender.send_data(2)).join().unwrap();
+        assert_eq_and_println(
+            &control.try_send_data(4),
+            &Err(AccessError::Disabled(DisabledError)),
+            "try_send_data while disabled",
+        );
+
+        control.set_enabled(true);
+        control.send_data(8);
+        assert_eq_and_println(&control.drain_and_reset(0), &9, "disabled data dropped");
+    }
+
+    #[cfg(thread_local_collect_off)]
+    #[test]
+    fn collect_off() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        control.send_data(1);
+        control.send_data_with_priority(2, Priority::High);
+        assert_eq_and_println(
+            &control.try_send_data(3),
+            &Err(AccessError::Disabled(DisabledError)),
+            "try_send_data compiled out",
+        );
+        assert_eq_and_println(&control.drain_and_reset(0), &0, "sends compiled out");
+    }
+
+    #[test]
+    fn with_spill() {
+        thread_local! {static VEC_TL: Holder<u32> = const { Holder::new() };}
+
+        let spilled = Arc::new(Mutex::new(Vec::new()));
+        let control = Control::new(&VEC_TL, Vec::new(), |data, acc: &mut Vec<u32>, _| {
+            acc.push(data)
+        })
+        .with_spill(2, Vec::len, {
+            let spilled = spilled.clone();
+            move |acc| spilled.lock().unwrap().push(acc)
+        });
+
+        for i in 0..7 {
+            control.send_data(i);
+        }
+        control.drain_tls();
+
+        assert_eq_and_println(
+            spilled.lock().unwrap().deref(),
+            &vec![vec![0, 1, 2], vec![3, 4, 5]],
+            "spilled chunks",
+        );
+        assert_eq_and_println(
+            &control.clone_acc(),
+            &vec![6],
+            "accumulated value after spills",
+        );
+    }
+
+    #[test]
+    fn map_input() {
+        thread_local! {static I64_TL: Holder<i64> = const { Holder::new() };}
+
+        let control = Control::<i64, i64>::new_additive(&I64_TL);
+        let from_u8 = control.map_input(|data: u8| data as i64);
+        let from_str = control
+            .sender()
+            .map_input(|data: &str| data.parse::<i64>().expect("valid integer"));
+
+        thread::scope(|s| {
+            s.spawn(move || from_u8.send_data(2));
+            s.spawn(move || from_str.send_data("-5"));
+        });
+        control.send_data(10);
+
+        control.drain_tls();
+        assert_eq_and_println(&control.clone_acc(), &7, "map_input");
+    }
+
+    #[test]
+    fn start_receiving_tls_until() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        let cancel = Arc::new(AtomicBool::new(false));
+        control.start_receiving_tls_until(cancel.clone()).unwrap();
+
+        thread::scope(|s| {
+            s.spawn(|| control.send_data(1));
+            s.spawn(|| control.send_data(2));
+        });
+
+        cancel.store(true, Ordering::Release);
+        while control.lock().bkgd_recv_exists {
+            thread::yield_now();
+        }
+
+        assert_eq_and_println(&control.clone_acc(), &3, "received before cancellation");
+        assert!(
+            control.start_receiving_tls().is_ok(),
+            "background receiver can be restarted after cancellation"
+        );
+        control.drain_tls();
+    }
+
+    #[test]
+    fn stop_receiving_tls_out_of_band() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let barrier = Arc::new(Barrier::new(2));
+        let control = {
+            let barrier = barrier.clone();
+            Control::new(&I32_TL, 0, move |data, acc: &mut i32, _| {
+                if data == 1 {
+                    // Let the main thread stop the background receiver while it is receiving the first value.
+                    barrier.wait();
+                    barrier.wait();
+                }
+                *acc += data;
+            })
+        };
+        let sender = control.sender();
+        thread::spawn(move || (1..=3).for_each(|i| sender.send_data(i)))
+            .join()
+            .unwrap();
+
+        control.start_receiving_tls().unwrap();
+        barrier.wait();
+        control.stop_receiving_tls();
+        barrier.wait();
+        while control.lock().bkgd_recv_exists {
+            thread::yield_now();
+        }
+
+        assert_eq_and_println(&control.clone_acc(), &1, "stopped before pending values");
+        assert_eq_and_println(&control.queue_depth(), &2, "pending values");
+        control.drain_tls();
+        assert_eq_and_println(&control.clone_acc(), &6, "pending values drained");
+    }
+
+    #[test]
+    fn stop_receiving_tls_during_teardown() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        control.start_receiving_tls().unwrap();
+        control.send_data(1);
+
+        // Stops and drops race with each other and with the termination of the background receiver thread.
+        thread::scope(|s| {
+            for _ in 0..4 {
+                let control = control.clone();
+                s.spawn(move || {
+                    control.stop_receiving_tls();
+                    control.stop_receiving_tls();
+                });
+            }
+        });
+        while control.lock().bkgd_recv_exists {
+            thread::yield_now();
+        }
+        control.stop_receiving_tls();
+
+        control.drain_tls();
+        assert_eq_and_println(&control.take_acc(0), &1, "accumulated value");
+    }
+
+    #[test]
+    fn is_receiving() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL);
+        assert!(!control.is_receiving(), "not started");
+
+        control.start_receiving_tls().unwrap();
+        assert!(control.is_receiving(), "started");
+        assert!(control.start_receiving_tls().is_err(), "already receiving");
+
+        control.stop_receiving_tls();
+        while control.is_receiving() {
+            thread::yield_now();
+        }
+        assert!(control.start_receiving_tls().is_ok(), "restarted");
+        control.drain_tls();
+    }
+
+    #[test]
+    fn with_batch_size() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL).with_batch_size(2);
+        (1..=5).for_each(|i| control.send_data(i));
+
+        // All values sent before cancellation are received, over several batches.
+        let cancel = Arc::new(AtomicBool::new(true));
+        control.start_receiving_tls_until(cancel).unwrap();
+        while control.is_receiving() {
+            thread::yield_now();
+        }
+        assert_eq_and_println(&control.queue_depth(), &0, "all values received");
+        assert_eq_and_println(&control.clone_acc(), &15, "accumulated value");
+    }
+
+    #[test]
+    fn receive_tls_instrumented_per_batch() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let control = Control::<i32, i32>::new_additive(&I32_TL)
+            .with_batch_size(2)
+            .with_diagnostics({
+                let counts = counts.clone();
+                move |event| {
+                    if let DiagEvent::Completed {
+                        op: "receive_tls",
+                        count,
+                        ..
+                    } = event
+                    {
+                        counts.lock().unwrap().push(*count);
+                    }
+                }
+            });
+        (1..=5).for_each(|i| control.send_data(i));
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        control.start_receiving_tls_until(cancel).unwrap();
+        while control.is_receiving() {
+            thread::yield_now();
+        }
+        assert_eq_and_println(&*counts.lock().unwrap(), &vec![2, 2, 1], "batch counts");
+    }
+
+    #[test]
+    fn recycle_acc() {
+        thread_local! {static U32_TL: Holder<u32> = const { Holder::new() };}
+
+        let control = Control::new(&U32_TL, Vec::new(), |data, acc: &mut Vec<u32>, _| {
+            acc.push(data)
+        });
+        control.send_data(1);
+        let acc = control.drain_and_reset_recycled();
+        assert_eq_and_println(&acc, &vec![1], "first drain");
+
+        // The recycled value replaces the accumulated value at the next drain.
+        let ptr = acc.as_ptr();
+        control.recycle_acc(acc);
+        control.send_data(2);
+        let acc = control.drain_and_reset_recycled();
+        assert_eq_and_println(&acc, &vec![2], "second drain");
+
+        control.send_data(3);
+        control.drain_tls();
+        let acc = control.take_acc_recycled();
+        assert_eq_and_println(&acc, &vec![3], "value accumulated in recycled value");
+        assert_eq!(acc.as_ptr(), ptr, "allocation reused");
+    }
+
+    #[test]
+    fn subscribe() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL).with_filter(|data, _| *data > 1);
+        let (tx, rx) = mpsc::channel();
+        control.subscribe(move |data, tid| tx.send((*data, tid)).unwrap());
+        control.start_receiving_tls().unwrap();
+
+        let tid = thread::current().id();
+        control.send_data(1);
+        control.send_data(2);
+        let streamed = rx.iter().take(2).collect::<Vec<_>>();
+        assert_eq_and_println(&streamed, &vec![(1, tid), (2, tid)], "streamed live");
+
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                control.send_data(3);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        control.drain_tls();
+        assert_eq_and_println(&rx.recv().unwrap(), &(3, tid_spawned), "streamed on drain");
+        assert_eq_and_println(&control.clone_acc(), &5, "accumulated");
+    }
+
+    #[test]
+    fn with_replay() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL).with_replay(2);
+        (1..=3).for_each(|i| control.send_data(i));
+        assert_eq_and_println(&control.drain_and_reset(0), &6, "values received");
+
+        // A late consumer replays the last two values received.
+        assert_eq_and_println(&control.replay_tls(0), &5, "values replayed");
+        assert_eq_and_println(&control.clone_acc(), &0, "accumulated value unchanged");
+
+        control.send_data(4);
+        control.drain_tls();
+        assert_eq_and_println(&control.replay_tls(10), &17, "buffer holds the last values");
+        assert_eq_and_println(&control.take_acc(0), &4, "new value aggregated");
+    }
+
+    #[test]
+    fn replay_without_take() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let control = Control::<i32, i32>::new_additive(&I32_TL).with_replay(4);
+        (1..=3).for_each(|i| control.send_data(i));
+        control.drain_tls();
+
+        // Replaying without taking the accumulated value first does not count the values twice.
+        assert_eq_and_println(&control.replay_tls(0), &6, "values replayed");
+        assert_eq_and_println(&control.clone_acc(), &6, "values counted once");
+
+        let unbuffered = Control::<i32, i32>::new_additive(&I32_TL);
+        assert_eq_and_println(&unbuffered.replay_tls(7), &7, "no replay buffer");
+    }
+
+    #[test]
+    fn with_receiver_setup() {
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let setup_tid = Arc::new(Mutex::new(None));
+        let control = Control::<i32, i32>::new_additive(&I32_TL).with_receiver_setup({
+            let setup_tid = setup_tid.clone();
+            move || *setup_tid.lock().unwrap() = Some(thread::current().id())
+        });
+
+        control.start_receiving_tls().unwrap();
+        control.send_data(1);
+        control.stop_receiving_tls();
+        // Wait for the background thread to terminate.
+        while control.lock().bkgd_recv_exists {
+            thread::yield_now();
+        }
+        control.drain_tls();
+
+        let setup_tid = *setup_tid.lock().unwrap();
+        assert!(setup_tid.is_some(), "setup hook invoked");
+        assert_ne!(
+            setup_tid,
+            Some(thread::current().id()),
+            "setup hook invoked on background thread"
+        );
+        assert_eq_and_println(&control.clone_acc(), &1, "accumulated value");
+    }
+
+    #[test]
+    fn take_at_barrier() {
+        const NTHREADS: usize = 3;
+        const NPHASES: u64 = 3;
+
+        thread_local! {static U64_TL: Holder<u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        control.start_receiving_tls().unwrap();
+        let barrier = Barrier::new(NTHREADS + 1);
+
+        thread::scope(|s| {
+            for _ in 0..NTHREADS {
+                s.spawn(|| {
+                    for phase in 1..=NPHASES {
+                        control.send_data(phase);
+                        barrier.wait();
+                        barrier.wait();
+                    }
+                });
+            }
+
+            for phase in 1..=NPHASES {
+                let acc = control.take_at_barrier(&barrier, 0);
+                assert_eq_and_println(
+                    &acc,
+                    &(NTHREADS as u64 * phase),
+                    &format!("take_at_barrier phase {phase}"),
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn sender_and_reader() {
+        let control = Control::new(&MY_TL, HashMap::new(), op);
+        let sender = control.sender();
+        let reader = control.reader();
+
+        let (tid_spawned, map_spawned) = thread::scope(|s| {
+            let sender = sender.clone();
+            s.spawn(move || {
+                sender.send_data((1, Foo("a".to_owned())));
+                let map = HashMap::from([(1, Foo("a".to_owned()))]);
+                (thread::current().id(), map)
+            })
+            .join()
+            .unwrap()
+        });
+
+        control.drain_tls();
+        let map = HashMap::from([(tid_spawned, map_spawned)]);
+        assert_eq_and_println(&reader.clone_acc(), &map, "reader after sender send_data");
+
+        let spawned = reader.acc_map(|acc| &acc[&tid_spawned]);
+        assert_eq_and_println(&*spawned, &map[&tid_spawned], "reader acc_map");
+        drop(spawned);
+        let len = control.acc_map(|acc| &acc[&tid_spawned]).len();
+        assert_eq_and_println(&len, &1, "acc_map");
+    }
+
+    #[test]
+    fn multiple_tls() {
+        let control = Control::new(&MY_TL, HashMap::new(), op);
+        let control2 = control.for_tl(&MY_TL2);
+
+        let tid_own = thread::current().id();
+        control.send_data((1, Foo("a".to_owned())));
+        control2.send_data((2, Foo("b".to_owned())));
+        let map_own = HashMap::from([(1, Foo("a".to_owned())), (2, Foo("b".to_owned()))]);
+
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                control.send_data((11, Foo("x".to_owned())));
+                control2.send_data((22, Foo("y".to_owned())));
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        let map_spawned = HashMap::from([(11, Foo("x".to_owned())), (22, Foo("y".to_owned()))]);
+
+        let map = HashMap::from([(tid_own, map_own), (tid_spawned, map_spawned)]);
+        let acc = control2.drain_and_reset(HashMap::new());
+        assert_eq_and_println(
+            &acc,
+            &map,
+            "drain_and_reset with multiple thread-local statics",
+        );
+    }
+
+    #[test]
+    fn multiple_controls() {
+        let control = Control::new(&MY_TL, HashMap::new(), op);
+        let control_count = Control::new(&MY_TL, 0, |_: Data, acc: &mut usize, _| *acc += 1);
+
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                control.send_data((1, Foo("a".to_owned())));
+                control_count.send_data((2, Foo("b".to_owned())));
+                control_count.send_data((3, Foo("c".to_owned())));
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+
+        let map = HashMap::from([(tid_spawned, HashMap::from([(1, Foo("a".to_owned()))]))]);
+        let acc = control.drain_and_reset(HashMap::new());
+        assert_eq_and_println(&acc, &map, "1st control with shared thread-local static");
+
+        let count = control_count.drain_and_reset(0);
+        assert_eq_and_println(&count, &2, "2nd control with shared thread-local static");
+    }
+
+    #[test]
+    fn sink() {
+        /// Writes one line per accepted item to a buffer, which is only complete once the sink is finished.
+        #[derive(Default)]
+        struct LineWriter {
+            pending: Vec<String>,
+            written: Vec<String>,
+        }
+
+        impl crate::sink::Sink<Data> for LineWriter {
+            fn accept(&mut self, (k, Foo(v)): Data, _tid: ThreadId) {
+                self.pending.push(format!("{k}={v}"));
+            }
+
+            fn finish(&mut self) {
+                self.written.append(&mut self.pending);
+            }
+        }
+
+        let control = Control::from_sink(&MY_TL, LineWriter::default());
+
+        thread::scope(|s| {
+            s.spawn(|| control.send_data((1, Foo("a".to_owned()))));
+        });
+        control.send_data((2, Foo("b".to_owned())));
+        control.with_acc(|sink| assert!(sink.written.is_empty(), "not finished"));
+
+        let mut lines = control.drain_sink(LineWriter::default()).written;
+        lines.sort();
+        assert_eq_and_println(
+            &lines,
+            &vec!["1=a".to_owned(), "2=b".to_owned()],
+            "finished sink",
+        );
+    }
+
+    #[cfg(feature = "lock-order")]
+    #[test]
+    #[should_panic(expected = "lock-order violation")]
+    fn lock_order_reentrant_op() {
+        use std::sync::{Arc, OnceLock};
+
+        thread_local! {static I32_TL: Holder<i32> = const { Holder::new() };}
+
+        let cell: Arc<OnceLock<Control<i32, i32>>> = Arc::new(OnceLock::new());
+        let op_cell = cell.clone();
+        let control = Control::new(&I32_TL, 0, move |data, acc: &mut i32, _| {
+            if let Some(control) = op_cell.get() {
+                // Calling back into the control object from `op` would deadlock.
+                control.clone_acc();
+            }
+            *acc += data;
+        });
+        assert!(cell.set(control.clone()).is_ok());
+
+        control.send_data(1);
+        control.drain_tls();
+    }
 }
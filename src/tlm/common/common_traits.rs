@@ -3,7 +3,14 @@
 //=================
 // Param traits
 
-use std::{ops::DerefMut, thread::ThreadId};
+use crate::tlm::{AccessError, ControlMismatchError, ReregistrationError};
+use std::{
+    ops::DerefMut,
+    thread::{LocalKey, ThreadId},
+};
+
+#[cfg(feature = "probed")]
+use std::ops::Deref;
 
 /// Encapsulates the core types used by [`super::ControlG`], [`super::HolderG`], and their
 /// specializations.
@@ -57,13 +64,36 @@ pub trait CtrlParam {
 }
 
 //=================
-// Hidden non-param traits
+// Hidden structs
 
 #[doc(hidden)]
-/// Abstracts a type's ability to construct itself.
+/// Identifies a linked thread-local variable by the ID of its thread and the address of its thread-local static,
+/// as multiple thread-local statics may be linked to the same [`ControlG`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TlKey {
+    pub(crate) tid: ThreadId,
+    pub(crate) tl_addr: usize,
+}
+
+impl TlKey {
+    pub(crate) fn new<H>(tid: ThreadId, tl: &'static LocalKey<H>) -> Self {
+        Self {
+            tid,
+            tl_addr: tl as *const LocalKey<H> as usize,
+        }
+    }
+}
+
+//=================
+// Non-param traits
+
+/// Abstracts a type's ability to construct itself. Implemented by the data wrappers of [`super::HolderG`] (see
+/// [`GuardedData`]), with `S = Self` and `Arg = Option<T>`.
 pub trait New<S> {
+    /// Type of the argument of [`Self::new`].
     type Arg;
 
+    /// Constructs an `S` from `arg`.
     #[allow(clippy::new_ret_no_self)]
     fn new(arg: Self::Arg) -> S;
 }
@@ -101,7 +131,7 @@ where
         &mut self,
         op: &(dyn Fn(P::Dat, &mut P::Acc, ThreadId) + Send + Sync),
         data: P::Dat,
-        tid: ThreadId,
+        key: TlKey,
     );
 }
 
@@ -111,8 +141,9 @@ pub trait CtrlStateWithNode<P>: CtrlStateCore<P>
 where
     P: CoreParam + NodeParam,
 {
-    /// Registers a node with the control state.
-    fn register_node(&mut self, node: P::Node, tid: ThreadId);
+    /// Registers a node with the control state. Returns `false` if the registration is rejected because a node is
+    /// already registered under `key`.
+    fn register_node(&mut self, node: P::Node, key: TlKey) -> bool;
 }
 
 #[doc(hidden)]
@@ -120,8 +151,18 @@ pub trait Ctrl<P>
 where
     P: CoreParam,
 {
-    fn make_data(&self) -> P::Dat;
-    fn tl_data_dropped(&self, data: P::Dat, tid: ThreadId);
+    /// Constructs the initial data of the thread-local variable of thread `tid`.
+    fn make_data(&self, tid: ThreadId) -> P::Dat;
+
+    /// Invoked when a thread-local holder is linked to the control.
+    fn tl_linked(&self);
+
+    /// Returns an identifier shared by the control and its clones, and only by them.
+    fn ctrl_id(&self) -> usize;
+
+    /// Invoked when a thread-local holder is dropped. `take_data` takes the holder's data and must be invoked
+    /// while the control's state lock is held.
+    fn tl_data_dropped(&self, take_data: impl FnOnce() -> Option<P::Dat>, tid: ThreadId);
 }
 
 #[doc(hidden)]
@@ -129,7 +170,7 @@ pub trait CtrlNode<P>
 where
     P: NodeParam,
 {
-    fn register_node(&self, node: P::Node, tid: ThreadId);
+    fn register_node(&self, node: P::Node, tid: ThreadId) -> Result<(), ReregistrationError>;
 }
 
 #[doc(hidden)]
@@ -138,13 +179,34 @@ where
     P: CoreParam + CtrlParam,
     P::Ctrl: Ctrl<P>,
 {
-    fn link(&self, control: &P::Ctrl);
+    /// Links the holder to `control`.
+    ///
+    /// # Errors
+    /// Returns an error if the registration of the holder with `control` is rejected.
+    fn link(&self, control: &P::Ctrl) -> Result<(), ReregistrationError>;
 
     fn is_linked(&self) -> bool;
 
-    fn ensure_linked(&self, control: &P::Ctrl) {
-        if !self.is_linked() {
-            self.link(control);
+    /// Returns the [`Ctrl::ctrl_id`] of the control the holder is linked to, if any.
+    fn linked_id(&self) -> Option<usize>;
+
+    /// Notifies the control the holder is linked to, if any, as if the holder had been dropped, and unlinks it.
+    fn unlink(&self);
+
+    fn is_linked_to(&self, control: &P::Ctrl) -> bool {
+        self.linked_id() == Some(control.ctrl_id())
+    }
+
+    /// Links the holder to `control` unless it is already linked.
+    ///
+    /// # Errors
+    /// Returns an error if the holder is linked to a different control or its registration with `control` is
+    /// rejected.
+    fn ensure_linked(&self, control: &P::Ctrl) -> Result<(), AccessError> {
+        match self.linked_id() {
+            None => Ok(self.link(control)?),
+            Some(id) if id == control.ctrl_id() => Ok(()),
+            Some(_) => Err(ControlMismatchError.into()),
         }
     }
 }
@@ -159,15 +221,46 @@ where
     fn with_data_mut<V>(&self, f: impl FnOnce(&mut P::Dat) -> V) -> V;
 }
 
-#[doc(hidden)]
-/// Abstraction of data wrappers used by [`HolderG`] specializations for different modules.
+/// Abstraction of data wrappers used by [`super::HolderG`] specializations for different modules: a wrapper of an
+/// `Option<T>` that provides exclusive access to it through a guard.
+///
+/// Implementations are provided for [`RefCell`](std::cell::RefCell), used by the `joined` modules, and, for the
+/// `probed` modules, for `Arc<Mutex<_>>` and `Arc<RwLock<_>>` from [`std::sync`] and, with the **"parking_lot"**
+/// feature, from the [`parking_lot`](https://docs.rs/parking_lot) crate. Other synchronization primitives can be used
+/// by implementing this trait and [`GuardedDataRead`] for a newtype wrapper and using it as the `G` type parameter of
+/// the `probed` module's `ControlWith` and `HolderWith` types.
 pub trait GuardedData<T: 'static>: New<Self>
 where
     Self: Sized,
 {
+    /// Guard that provides exclusive access to the wrapped value while it is alive.
     type Guard<'a>: DerefMut<Target = Option<T>> + 'a
     where
         Self: 'a;
 
+    /// Returns a guard that provides exclusive access to the wrapped value, blocking until it is available.
     fn guard(&self) -> Self::Guard<'_>;
 }
+
+#[cfg(feature = "probed")]
+/// Extension of [`GuardedData`] for data wrappers that can be shared across threads and support read-only
+/// access. Used by node types that hold references to thread-local data. Clones must share the wrapped value.
+pub trait GuardedDataRead<T: 'static>: GuardedData<T> + Clone {
+    /// Guard that provides read-only access to the wrapped value while it is alive. It may be the same as
+    /// [`GuardedData::Guard`] if the synchronization primitive does not support shared access.
+    type ReadGuard<'a>: Deref<Target = Option<T>> + 'a
+    where
+        Self: 'a;
+
+    /// Returns a guard that provides read-only access to the wrapped value, blocking until it is available.
+    fn read_guard(&self) -> Self::ReadGuard<'_>;
+
+    /// Non-owning reference to the data wrapper, which does not keep the data alive after its holder is dropped.
+    type Weak;
+
+    /// Returns a non-owning reference to `self`.
+    fn downgrade(&self) -> Self::Weak;
+
+    /// Returns the data wrapper referenced by `weak`, if it is still alive.
+    fn upgrade(weak: &Self::Weak) -> Option<Self>;
+}
@@ -3,14 +3,42 @@
 //! The `Control`type alias in various modules is a specialization of this struct.
 
 use super::common_traits::*;
+#[cfg(feature = "overhead-stats")]
+use crate::overhead::{OverheadRecorder, OverheadStats};
+use crate::{
+    accumulator::{Accumulator, Recycle, Tabular},
+    activity::{self, ActivityTracker, ThreadActivity},
+    alias::ThreadAliases,
+    diag::{DiagEvent, DiagHook, OpRecord},
+    fork::ForkGuard,
+    handles::{ControlReader, ControlSender, FinishedControl, MappedGuard},
+    history::{self, History},
+    instr,
+    lock_order::{LockKind, Tracked},
+    pool::AccPool,
+    sink::Sink,
+    spill::{self, Spill},
+    tlm::{
+        AccessError, AccessGuard, Quiescence, QuiescenceTimeoutError, ReregistrationError, Seal,
+        Switch, TlsDestroyedError,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::tlm::DrainedFuture;
 
 use std::{
+    collections::HashMap,
     fmt::Debug,
     marker::PhantomData,
     mem::replace,
-    ops::Deref,
-    sync::{Arc, Mutex, MutexGuard},
-    thread::{LocalKey, ThreadId},
+    ops::{AddAssign, Deref},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, LocalKey, ThreadId},
+    time::Duration,
 };
 
 //=================
@@ -18,6 +46,11 @@ use std::{
 
 pub(crate) const POISONED_CONTROL_MUTEX: &str = "poisoned control mutex";
 
+/// Returns the key that identifies the control object with `state`, and its clones, in [`AccessGuard`]s.
+fn access_key<S>(state: &Arc<Mutex<S>>) -> usize {
+    Arc::as_ptr(state) as usize
+}
+
 //=================
 // Core structs and impls
 
@@ -33,11 +66,11 @@ pub struct WithNode;
 
 /// Guard that dereferences to the accumulator type. A lock is held during the guard's lifetime.
 struct AccGuardG<'a, S> {
-    guard: MutexGuard<'a, S>,
+    guard: Tracked<MutexGuard<'a, S>>,
 }
 
 impl<'a, S> AccGuardG<'a, S> {
-    pub(crate) fn new(lock: MutexGuard<'a, S>) -> Self {
+    pub(crate) fn new(lock: Tracked<MutexGuard<'a, S>>) -> Self {
         Self { guard: lock }
     }
 }
@@ -125,10 +158,10 @@ where
         &mut self,
         op: &(dyn Fn(P::Dat, &mut P::Acc, ThreadId) + Send + Sync),
         data: P::Dat,
-        tid: ThreadId,
+        key: TlKey,
     ) {
         let acc = self.acc_mut_priv();
-        op(data, acc, tid);
+        op(data, acc, key.tid);
     }
 }
 
@@ -153,6 +186,103 @@ where
     /// Operation that combines data from thread-locals with accumulated value.
     #[allow(clippy::type_complexity)]
     pub(crate) op: Arc<dyn Fn(P::Dat, &mut P::Acc, ThreadId) + Send + Sync>,
+    /// Optional subsystems, `None` unless one of them is enabled with a builder method, so that contributions to a
+    /// control object without any of them only pay for a single branch.
+    pub(crate) extras: Option<Box<Extras<P>>>,
+    /// Accumulated values recycled for reuse as replacements of the accumulated value.
+    pub(crate) pool: Arc<AccPool<P::Acc>>,
+    /// Recorder of the latencies of contributions.
+    #[cfg(feature = "overhead-stats")]
+    pub(crate) overhead: Arc<OverheadRecorder>,
+    /// Sealed state, entered when `self` is finalized.
+    pub(crate) seal: Seal,
+    /// Enabled state, toggled with [`Self::set_enabled`].
+    pub(crate) enabled: Switch,
+    /// Count of the live thread-local variables linked to `self`.
+    pub(crate) quiescence: Quiescence,
+    /// Process in which `self` was instantiated.
+    pub(crate) fork: ForkGuard,
+}
+
+/// Optional subsystems of [`ControlG`], each of which is enabled with a builder method of [`ControlG`].
+pub(crate) struct Extras<P>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+{
+    /// Optional per-thread replacement for `make_data`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) tl_init: Option<Arc<dyn Fn(ThreadId) -> P::Dat + Send + Sync>>,
+    /// Optional function that accesses the thread-local variables `op` depends on, see
+    /// [`ControlG::with_tl_dependencies`].
+    pub(crate) tl_deps: Option<Arc<dyn Fn() + Send + Sync>>,
+    /// Optional diagnostics hook.
+    pub(crate) diag: Option<DiagHook>,
+    /// Optional bounded history of taken accumulated values.
+    pub(crate) history: Option<Arc<History<P::Acc>>>,
+    /// Optional overflow hook for the accumulated value.
+    pub(crate) spill: Option<Arc<Spill<P::Acc>>>,
+    /// Optional aliases assigned to threads when they register.
+    pub(crate) aliases: Option<ThreadAliases>,
+    /// Optional tracker of the contribution activity of each thread.
+    pub(crate) activity: Option<Arc<ActivityTracker>>,
+    /// Set when the aggregation of the thread-local values with the accumulated value may have changed, so that
+    /// `probe_tls` in [`super::super::probed`] recomputes it instead of returning the cached result. `None` unless
+    /// the probe cache is enabled with `with_probe_cache` in that module.
+    pub(crate) probe_stale: Option<Arc<AtomicBool>>,
+}
+
+impl<P> Default for Extras<P>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+{
+    fn default() -> Self {
+        Self {
+            tl_init: None,
+            tl_deps: None,
+            diag: None,
+            history: None,
+            spill: None,
+            aliases: None,
+            activity: None,
+            probe_stale: None,
+        }
+    }
+}
+
+impl<P> Clone for Extras<P>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tl_init: self.tl_init.clone(),
+            tl_deps: self.tl_deps.clone(),
+            diag: self.diag.clone(),
+            history: self.history.clone(),
+            spill: self.spill.clone(),
+            aliases: self.aliases.clone(),
+            activity: self.activity.clone(),
+            probe_stale: self.probe_stale.clone(),
+        }
+    }
+}
+
+impl<P> ControlG<P>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+{
+    /// Returns the optional subsystem of `self` selected by `field`, if it is enabled.
+    #[inline]
+    pub(crate) fn extra<T>(&self, field: impl FnOnce(&Extras<P>) -> &Option<T>) -> Option<&T> {
+        self.extras
+            .as_deref()
+            .and_then(|extras| field(extras).as_ref())
+    }
+
+    /// Returns the optional subsystems of `self` for configuration, enabling them if needed.
+    pub(crate) fn extras_mut(&mut self) -> &mut Extras<P> {
+        self.extras.get_or_insert_with(Box::default)
+    }
 }
 
 impl<P> ControlG<P>
@@ -173,14 +303,243 @@ where
         make_data: fn() -> P::Dat,
         op: impl Fn(P::Dat, &mut P::Acc, ThreadId) + 'static + Send + Sync,
     ) -> Self {
-        let state = P::CtrlState::new(acc_base);
+        let state = Arc::new(Mutex::new(P::CtrlState::new(acc_base)));
+        // Accesses to `self` from inside `op` are reentrant.
+        let key = access_key(&state);
         Self {
             tl,
-            state: Arc::new(Mutex::new(state)),
+            state,
             make_data,
-            op: Arc::new(op),
+            op: Arc::new(move |data, acc, tid| {
+                let _guard = AccessGuard::mark(key);
+                op(data, acc, tid)
+            }),
+            extras: None,
+            pool: Arc::default(),
+            #[cfg(feature = "overhead-stats")]
+            overhead: Arc::default(),
+            seal: Seal::default(),
+            enabled: Switch::default(),
+            quiescence: Quiescence::default(),
+            fork: ForkGuard::new(),
         }
     }
+
+    /// Instantiates a *control* object whose accumulation is defined by the [`Accumulator`] implementation of
+    /// `P::Acc`, with [`Accumulator::zero`] as the initial value for accumulation and [`Accumulator::absorb`] as
+    /// the operation that combines data from thread-locals with the accumulated value.
+    ///
+    /// - `tl` - reference to thread-local static.
+    /// - `make_data` - constructs initial data for [`super::HolderG`].
+    pub fn from_accumulator(tl: &'static LocalKey<P::Hldr>, make_data: fn() -> P::Dat) -> Self
+    where
+        P::Acc: Accumulator<Dat = P::Dat>,
+    {
+        Self::new(tl, P::Acc::zero(), make_data, |data, acc, tid| {
+            acc.absorb(data, tid)
+        })
+    }
+
+    /// Instantiates a *control* object whose accumulated value is `sink`, which accepts data from thread-locals
+    /// with [`Sink::accept`]. The sink is finished and returned with [`Self::take_sink`].
+    ///
+    /// - `tl` - reference to thread-local static.
+    /// - `sink` - destination of the data from thread-locals.
+    /// - `make_data` - constructs initial data for [`super::HolderG`].
+    pub fn from_sink(
+        tl: &'static LocalKey<P::Hldr>,
+        sink: P::Acc,
+        make_data: fn() -> P::Dat,
+    ) -> Self
+    where
+        P::Acc: Sink<P::Dat>,
+    {
+        Self::new(tl, sink, make_data, |data, sink, tid| {
+            sink.accept(data, tid)
+        })
+    }
+
+    /// Instantiates a *control* object for the common case of additive accumulations, e.g., counting or summing.
+    /// The initial value for accumulation is `P::Acc::default()`, initial data for [`super::HolderG`] is
+    /// `P::Dat::default()`, and data from thread-locals is combined with the accumulated value with `+=`.
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn new_additive(tl: &'static LocalKey<P::Hldr>) -> Self
+    where
+        P::Acc: Default + AddAssign<P::Dat>,
+        P::Dat: Default,
+    {
+        Self::new(tl, P::Acc::default(), P::Dat::default, |data, acc, _| {
+            *acc += data
+        })
+    }
+
+    /// Instantiates a *control* object for accumulators that implement [`Extend`], e.g., [`Vec`] or
+    /// [`HashMap`](std::collections::HashMap), without an `op` closure. The initial value for accumulation is
+    /// `P::Acc::default()`, initial data for [`super::HolderG`] is `P::Dat::default()`, and data from thread-locals
+    /// is combined with the accumulated value by extending it with a `(ThreadId, P::Dat)` pair.
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn from_extend(tl: &'static LocalKey<P::Hldr>) -> Self
+    where
+        P::Acc: Default + Extend<(ThreadId, P::Dat)>,
+        P::Dat: Default,
+    {
+        Self::new(tl, P::Acc::default(), P::Dat::default, |data, acc, tid| {
+            acc.extend([(tid, data)])
+        })
+    }
+
+    /// Attaches a diagnostics hook that receives structured events about `self`'s internal operations.
+    /// See [`crate::diag`].
+    pub fn with_diagnostics(mut self, hook: impl Fn(&DiagEvent) + 'static + Send + Sync) -> Self {
+        self.extras_mut().diag = Some(Arc::new(hook));
+        self
+    }
+
+    /// Enables a bounded history that keeps clones of the last `capacity` accumulated values taken from `self`,
+    /// which can be retrieved with [`Self::history`]. The history is shared by all clones of `self`.
+    pub fn with_history(mut self, capacity: usize) -> Self
+    where
+        P::Acc: Clone,
+    {
+        self.extras_mut().history = Some(History::new(capacity));
+        self
+    }
+
+    /// Attaches a predicate that is applied to each thread-local value, together with the [`ThreadId`] of the
+    /// thread it came from, before the value is combined with the accumulated value. Values for which the
+    /// predicate returns `false` are discarded. The predicate applies to all clones of `self` created afterwards.
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&P::Dat, ThreadId) -> bool + 'static + Send + Sync,
+    ) -> Self {
+        let op = self.op;
+        self.op = Arc::new(move |data, acc, tid| {
+            if filter(&data, tid) {
+                op(data, acc, tid)
+            }
+        });
+        self
+    }
+
+    /// Attaches an overflow hook: whenever, after thread-local data has been combined with the accumulated value,
+    /// the size of the accumulated value as computed by `size` exceeds `max_size`, the accumulated value is
+    /// passed to `spill` (e.g., to write it to disk) and replaced with `P::Acc::default()`. This keeps long-running
+    /// collections from growing in memory indefinitely. The hook applies to all clones of `self`
+    /// created afterwards.
+    ///
+    /// `spill` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    pub fn with_spill(
+        mut self,
+        max_size: usize,
+        size: impl Fn(&P::Acc) -> usize + 'static + Send + Sync,
+        spill: impl Fn(P::Acc) + 'static + Send + Sync,
+    ) -> Self
+    where
+        P::Acc: Default,
+    {
+        self.extras_mut().spill = Some(Spill::new(max_size, size, spill, P::Acc::default));
+        self
+    }
+
+    /// Wraps `op`, as configured so far, so that each invocation is logged with `logger`, which receives an
+    /// [`OpRecord`] with the thread the data came from, a summary of the data, and the time `op` took. This shows the
+    /// exact sequence in which data is combined with the accumulated value, e.g., to debug an accumulated value that
+    /// comes out wrong. The wrapper applies to all clones of `self` created afterwards. Invocations of `op` by probes,
+    /// e.g., `probe_tls`, are not logged, since they combine data with a copy of the accumulated value.
+    ///
+    /// `logger` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    pub fn instrumented(mut self, logger: impl Fn(&OpRecord) + 'static + Send + Sync) -> Self
+    where
+        P::Dat: Debug,
+    {
+        self.op = instr::instrument_op(self.op, logger);
+        self
+    }
+
+    /// Enables the tracking of the contribution activity of each thread, which can be retrieved with
+    /// [`Self::thread_activity`]. See [`crate::activity`].
+    pub fn with_activity_tracking(mut self) -> Self {
+        self.extras_mut().activity = Some(Arc::default());
+        self
+    }
+
+    /// Assigns each thread an alias in `aliases` when its thread-local variable is linked to `self`, so that the
+    /// aliases follow the order in which the threads register. `aliases` may be shared with other control objects.
+    /// See [`crate::alias`].
+    pub fn with_thread_aliases(mut self, aliases: ThreadAliases) -> Self {
+        self.extras_mut().aliases = Some(aliases);
+        self
+    }
+
+    /// Replaces the `make_data` function passed to [`Self::new`] with `tl_init`, which receives the [`ThreadId`] of
+    /// the thread whose thread-local data it constructs, e.g., to pre-size or label the data differently per worker.
+    /// `tl_init` is invoked when a thread-local variable is linked to `self` and whenever its data is taken and
+    /// replaced.
+    pub fn with_tl_init(
+        mut self,
+        tl_init: impl Fn(ThreadId) -> P::Dat + 'static + Send + Sync,
+    ) -> Self {
+        self.extras_mut().tl_init = Some(Arc::new(tl_init));
+        self
+    }
+
+    /// Registers `touch`, which must access the thread-local variables that `op` depends on, e.g., with
+    /// `DEP_TL.with(|_| ())`, so that they outlive the thread-local variables linked to `self`.
+    ///
+    /// The data of a thread-local variable linked to `self` is aggregated by its destructor, which is run when its
+    /// thread terminates, in the reverse order in which the thread-local variables were first accessed on that
+    /// thread. If a thread-local variable that `op` depends on is first accessed after the one linked to `self`, it is
+    /// destroyed first and `op` cannot access it. `touch` is invoked on the contributing thread before each access to
+    /// the thread-local variable linked to `self`, so the dependencies are first accessed before it, provided that the
+    /// linked thread-local variable is only accessed through `self` or its clones.
+    pub fn with_tl_dependencies(mut self, touch: impl Fn() + 'static + Send + Sync) -> Self {
+        self.extras_mut().tl_deps = Some(Arc::new(touch));
+        self
+    }
+
+    /// Attaches an overflow hook, like [`Self::with_spill`], that replaces the spilled accumulated value with a
+    /// value produced by `zero`.
+    #[cfg(feature = "restr")]
+    pub(crate) fn with_spill_zero(
+        mut self,
+        max_size: usize,
+        size: impl Fn(&P::Acc) -> usize + 'static + Send + Sync,
+        spill: impl Fn(P::Acc) + 'static + Send + Sync,
+        zero: impl Fn() -> P::Acc + 'static + Send + Sync,
+    ) -> Self {
+        self.extras_mut().spill = Some(Spill::new(max_size, size, spill, zero));
+        self
+    }
+}
+
+impl<P> ControlG<P>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+{
+    /// Returns a clone of `self` that is linked to thread-local static `tl` instead of the thread-local static
+    /// `self` was instantiated with. The returned object shares `self`'s state and accumulated value, so that
+    /// multiple thread-local statics (e.g., defined by separate subsystems) can contribute to a single aggregate.
+    /// Each thread-local static must be accessed through a control object linked to it.
+    pub fn for_tl(&self, tl: &'static LocalKey<P::Hldr>) -> Self {
+        Self { tl, ..self.clone() }
+    }
+
+    /// Returns whether `self` and `other` are the same control object or clones of each other, including clones
+    /// obtained with [`Self::for_tl`], i.e., whether they aggregate into the same accumulated value.
+    pub fn same_control(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+
+    /// Wraps `self` in a [`FinishedControl`] whose [`take_acc`](FinishedControl::take_acc) method returns the
+    /// accumulated value post-processed with `finish`.
+    pub fn with_finisher<V, F>(self, finish: F) -> FinishedControl<Self, F>
+    where
+        F: Fn(P::Acc) -> V,
+    {
+        FinishedControl::new(self, finish)
+    }
 }
 
 impl<P> ControlG<P>
@@ -192,9 +551,49 @@ where
     /// Acquires a lock on [`ControlG`]'s internal Mutex.
     ///
     /// # Panics
-    /// If `self`'s mutex is poisoned.
-    pub(crate) fn lock(&self) -> MutexGuard<'_, P::CtrlState> {
-        self.state.lock().expect(POISONED_CONTROL_MUTEX)
+    /// - If `self`'s mutex is poisoned.
+    /// - With the **"fork-aware"** feature, if `self` is used in a child process created with `fork()`.
+    pub(crate) fn lock(&self) -> Tracked<MutexGuard<'_, P::CtrlState>> {
+        let guard = self.lock_unchanged();
+        // Any operation other than a probe may change the aggregation returned by a probe.
+        if let Some(stale) = self.extra(|e| &e.probe_stale) {
+            stale.store(true, Ordering::SeqCst);
+        }
+        guard
+    }
+
+    /// Same as [`Self::lock`], for operations that do not change the aggregation of the thread-local values with
+    /// the accumulated value, i.e., probes and read-only accessors.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - With the **"fork-aware"** feature, if `self` is used in a child process created with `fork()`.
+    pub(crate) fn lock_unchanged(&self) -> Tracked<MutexGuard<'_, P::CtrlState>> {
+        self.fork.check();
+        Tracked::acquire(LockKind::Control, self.state.as_ref(), || {
+            self.state.lock().expect(POISONED_CONTROL_MUTEX)
+        })
+    }
+
+    /// Marks the aggregation cached by a probe, if the probe cache is enabled, as stale after a contribution. Only
+    /// stores to the flag if it is clear, so that contributions do not contend on it between probes.
+    ///
+    /// The flag is accessed with sequentially consistent ordering: a contribution that is missed by a probe, which
+    /// locks each thread-local value after clearing the flag, observes the cleared flag and sets it again.
+    #[inline]
+    pub(crate) fn invalidate_probe(&self) {
+        if let Some(stale) = self.extra(|e| &e.probe_stale) {
+            if !stale.load(Ordering::SeqCst) {
+                stale.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Accesses the thread-local variables registered with [`Self::with_tl_dependencies`], if any.
+    fn touch_tl_deps(&self) {
+        if let Some(touch) = self.extra(|e| &e.tl_deps) {
+            touch();
+        }
     }
 
     /// Returns a guard object that dereferences to `self`'s accumulated value. A lock is held during the guard's
@@ -203,7 +602,19 @@ where
     /// # Panics
     /// If `self`'s mutex is poisoned.
     pub fn acc(&self) -> impl Deref<Target = P::Acc> + '_ {
-        AccGuardG::new(self.lock())
+        AccGuardG::new(self.lock_unchanged())
+    }
+
+    /// Returns a guard object that dereferences to the part of `self`'s accumulated value selected by `f`, e.g., a
+    /// field, as in `control.acc_map(|acc| &acc.latency_hist)`. A lock is held during the guard's lifetime.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn acc_map<'a, V: ?Sized + 'a>(
+        &'a self,
+        f: impl Fn(&P::Acc) -> &V + 'a,
+    ) -> impl Deref<Target = V> + 'a {
+        MappedGuard::new(self.acc(), f)
     }
 
     /// Provides access to `self`'s accumulated value.
@@ -226,6 +637,18 @@ where
         self.acc().clone()
     }
 
+    /// Renders `self`'s accumulated value as a table with one `key  value` row per entry, e.g., for a debugging dump
+    /// of a per-thread map accumulator at shutdown. See [`Tabular`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn format_acc(&self) -> String
+    where
+        P::Acc: Tabular,
+    {
+        self.with_acc(Tabular::format_table)
+    }
+
     /// Returns `self`'s accumulated value, using a value of the same type to replace
     /// the existing accumulated value.
     ///
@@ -233,8 +656,190 @@ where
     /// If `self`'s mutex is poisoned.
     pub fn take_acc(&self, replacement: P::Acc) -> P::Acc {
         let mut lock = self.lock();
-        let acc = lock.acc_mut();
-        replace(acc, replacement)
+        let acc = replace(lock.acc_mut(), replacement);
+        history::record(self.extra(|e| &e.history), &acc);
+        acc
+    }
+
+    /// Same as [`Self::take_acc`], except that [`Sink::finish`] is called on the accumulated value, e.g., a sink
+    /// passed to [`Self::from_sink`], before it is returned.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_sink(&self, replacement: P::Acc) -> P::Acc
+    where
+        P::Acc: Sink<P::Dat>,
+    {
+        let mut sink = self.take_acc(replacement);
+        sink.finish();
+        sink
+    }
+
+    /// Same as [`Self::take_acc`], except that the replacement is a value previously returned to `self` or one of
+    /// its clones with [`Self::recycle_acc`], if any, or `P::Acc::default()` otherwise. This avoids reallocating,
+    /// e.g., large map accumulators that are taken at high frequency.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_acc_recycled(&self) -> P::Acc
+    where
+        P::Acc: Default,
+    {
+        self.take_acc(self.pool.take())
+    }
+
+    /// Returns `acc`, typically a value taken from `self` that is no longer needed, to `self` for reuse by
+    /// [`Self::take_acc_recycled`]. `acc` is reset with [`Recycle::recycle`], which retains its allocations. At most
+    /// a few values are kept; further ones are dropped.
+    ///
+    /// # Panics
+    /// If the mutex of `self`'s pool is poisoned.
+    pub fn recycle_acc(&self, acc: P::Acc)
+    where
+        P::Acc: Recycle,
+    {
+        self.pool.put(acc)
+    }
+
+    /// Returns whether `self` has been finalized, after which contributions to it are rejected. See the `finalize`
+    /// method of each module's `Control` type.
+    pub fn is_sealed(&self) -> bool {
+        self.seal.is_sealed()
+    }
+
+    /// Enables or disables contributions to `self` and its clones. While disabled, contributions with
+    /// [`Self::try_with_data_mut`] are rejected with [`AccessError::Disabled`] at the cost of a single relaxed atomic
+    /// load, before any locking or allocation, and those with [`Self::with_data_mut`] invoke their closure on a
+    /// scratch value that is then dropped. The methods of the `restr` modules that contribute data, e.g.,
+    /// `aggregate_data`, do nothing while disabled. Data already contributed is not affected. Control objects are
+    /// enabled when instantiated, unless collection is compiled out with the `thread_local_collect_off` or
+    /// `thread_local_collect_release_off` option, in which case they are always disabled.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled)
+    }
+
+    /// Returns whether contributions to `self` are enabled. See [`Self::set_enabled`].
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.is_enabled()
+    }
+
+    /// Finalizes `self` and its clones: after this call, contributions to them are rejected with a
+    /// [`AccessError::Sealed`]. Then returns `self`'s accumulated value, using `replacement` to replace it.
+    /// Used to implement the `finalize` method of each module's `Control` type.
+    ///
+    /// Contributions check the seal while holding the lock on the thread-local value they contribute to, which
+    /// `take` acquires to take that value after `self` is sealed. So a contribution that races this call is either
+    /// completed before its value is taken, and included in the returned value, or rejected.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub(crate) fn seal_and_take_acc(&self, replacement: P::Acc, take: impl FnOnce()) -> P::Acc {
+        self.seal.seal();
+        take();
+        self.take_acc(replacement)
+    }
+
+    /// Returns the last accumulated values taken from `self`, from oldest to newest, if history is enabled
+    /// with [`Self::with_history`], or an empty vector otherwise.
+    ///
+    /// # Panics
+    /// If the history's mutex is poisoned.
+    pub fn history(&self) -> Vec<P::Acc> {
+        history::snapshot(self.extra(|e| &e.history))
+    }
+
+    /// Returns the contribution activity of each thread that has contributed to `self` since activity tracking was
+    /// enabled with [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn thread_activity(&self) -> HashMap<ThreadId, ThreadActivity> {
+        activity::snapshot(self.extra(|e| &e.activity))
+    }
+
+    /// Returns the number of contributions made by each thread to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn contribution_counts(&self) -> HashMap<ThreadId, u64> {
+        activity::counts(self.extra(|e| &e.activity))
+    }
+
+    /// Returns the threads that have contributed to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`] but have not contributed in the last `since`, e.g., so that a supervisor can
+    /// detect hung workers, or an empty vector if tracking is not enabled. Threads that have terminated are included,
+    /// since the tracker does not observe thread termination.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn idle_threads(&self, since: Duration) -> Vec<ThreadId> {
+        activity::idle(self.extra(|e| &e.activity), since)
+    }
+
+    /// Returns the latency statistics of the contributions made to `self` and its clones. See [`crate::overhead`].
+    /// Requires the **"overhead-stats"** feature.
+    #[cfg(feature = "overhead-stats")]
+    pub fn overhead_stats(&self) -> OverheadStats {
+        self.overhead.snapshot()
+    }
+
+    /// Returns a read-only handle to `self`.
+    pub fn reader(&self) -> ControlReader<Self> {
+        ControlReader(self.clone())
+    }
+
+    /// Returns a send-only handle to `self`.
+    pub fn sender(&self) -> ControlSender<Self> {
+        ControlSender(self.clone())
+    }
+}
+
+impl<P> ControlReader<ControlG<P>>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+
+    P::CtrlState: CtrlStateCore<P>,
+{
+    /// Returns a guard object that dereferences to the accumulated value. A lock is held during the guard's
+    /// lifetime.
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn acc(&self) -> impl Deref<Target = P::Acc> + '_ {
+        self.0.acc()
+    }
+
+    /// Returns a guard object that dereferences to the part of the accumulated value selected by `f`. A lock is held
+    /// during the guard's lifetime. See [`ControlG::acc_map`].
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn acc_map<'a, V: ?Sized + 'a>(
+        &'a self,
+        f: impl Fn(&P::Acc) -> &V + 'a,
+    ) -> impl Deref<Target = V> + 'a {
+        self.0.acc_map(f)
+    }
+
+    /// Provides access to the accumulated value.
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn with_acc<V>(&self, f: impl FnOnce(&P::Acc) -> V) -> V {
+        self.0.with_acc(f)
+    }
+
+    /// Returns a clone of the accumulated value.
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn clone_acc(&self) -> P::Acc
+    where
+        P::Acc: Clone,
+    {
+        self.0.clone_acc()
     }
 }
 
@@ -244,17 +849,41 @@ where
 
     P::CtrlState: CtrlStateCore<P>,
 {
-    fn make_data(&self) -> P::Dat {
-        (self.make_data)()
+    fn make_data(&self, tid: ThreadId) -> P::Dat {
+        match self.extra(|e| &e.tl_init) {
+            Some(tl_init) => tl_init(tid),
+            None => (self.make_data)(),
+        }
+    }
+
+    fn tl_linked(&self) {
+        self.quiescence.linked();
+        if let Some(aliases) = self.extra(|e| &e.aliases) {
+            aliases.alias(thread::current().id());
+        }
+    }
+
+    fn ctrl_id(&self) -> usize {
+        access_key(&self.state)
     }
 
     /// Used by [`super::HolderG`] to notify [`ControlG`] that the holder's data has been dropped.
     ///
+    /// The holder's data is taken with `take_data` while `self`'s state lock is held, so that taking the data and
+    /// accumulating it is atomic with respect to `take_tls` and `probe_tls`, which would otherwise transiently
+    /// observe neither the data nor its contribution to the accumulated value.
+    ///
     /// # Panics
     /// If `self`'s mutex is poisoned.
-    fn tl_data_dropped(&self, data: <P as CoreParam>::Dat, tid: ThreadId) {
+    fn tl_data_dropped(&self, take_data: impl FnOnce() -> Option<P::Dat>, tid: ThreadId) {
+        instr::event("tl_data_dropped", tid, self.extra(|e| &e.diag));
         let mut lock = self.lock();
-        lock.tl_data_dropped(self.op.deref(), data, tid);
+        if let Some(data) = take_data() {
+            lock.tl_data_dropped(self.op.deref(), data, TlKey::new(tid, self.tl));
+            spill::check(self.extra(|e| &e.spill), lock.acc_mut());
+        }
+        drop(lock);
+        self.quiescence.dropped(tid);
     }
 }
 
@@ -266,21 +895,343 @@ where
     P: CtrlParam<Ctrl = Self>,
     P::Hldr: HldrLink<P> + HldrData<P>,
 {
-    /// Invokes `f` on the held data.
+    /// Invokes `f` on the held data. If the current thread's thread-local variable is linked to another control
+    /// object, it is first relinked to `self` as with [`Self::relink`], whereas [`Self::try_with_data`] returns
+    /// [`AccessError::Mismatch`].
+    ///
+    /// # Panics
+    /// If the current thread's thread-local variable has been destroyed, if its registration with `self` is rejected
+    /// (see [`AccessError::Reregistration`]), or if called reentrantly. See [`Self::try_with_data`].
     pub fn with_data<V>(&self, f: impl FnOnce(&P::Dat) -> V) -> V {
-        self.tl.with(|h| {
-            h.ensure_linked(self);
-            h.with_data(f)
-        })
+        self.read(f, true).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Invokes `f` on the held data, unless the current thread's thread-local variable has been destroyed, e.g.,
+    /// when called from the destructor of another thread-local variable during thread teardown, the thread-local
+    /// variable is linked to another control object (see [`Self::relink`]), or the call is reentrant, i.e., made
+    /// from inside `f` or `op` while the current thread is already accessing `self`.
+    ///
+    /// # Errors
+    /// Returns an error, without invoking `f`, if the thread-local variable has been destroyed or is linked to
+    /// another control object, or the call is reentrant.
+    pub fn try_with_data<V>(&self, f: impl FnOnce(&P::Dat) -> V) -> Result<V, AccessError> {
+        self.read(f, false)
+    }
+
+    /// Implements [`Self::try_with_data`], and [`Self::with_data`] if `relink` is `true`.
+    fn read<V>(&self, f: impl FnOnce(&P::Dat) -> V, relink: bool) -> Result<V, AccessError> {
+        let _guard = AccessGuard::enter(access_key(&self.state))?;
+        self.touch_tl_deps();
+        let res = self.tl.try_with(|h| -> Result<V, AccessError> {
+            self.link_holder(h, relink)?;
+            Ok(h.with_data(f))
+        });
+        res.map_err(|_| TlsDestroyedError)?
     }
 
-    /// Invokes `f` mutably on the held data.
+    /// Invokes `f` mutably on the held data. If `self` is disabled (see [`Self::set_enabled`]), `f` is instead
+    /// invoked on a scratch value constructed like the initial data (see [`Self::with_tl_init`]), which is then
+    /// dropped, so nothing is contributed. Use [`Self::try_with_data_mut`] to skip `f` altogether while disabled.
+    ///
+    /// If the current thread's thread-local variable is linked to another control object, it is first relinked to
+    /// `self` as with [`Self::relink`], so that its value is aggregated with that control object's accumulated
+    /// value and `f` contributes to `self`, whereas [`Self::try_with_data_mut`] returns [`AccessError::Mismatch`].
+    ///
+    /// # Panics
+    /// - If `self` has been finalized with the `finalize` method of its module's `Control` type. Late contributions
+    ///   are thus caught rather than aggregated with a stale accumulated value; [`Self::try_with_data_mut`] returns
+    ///   [`AccessError::Sealed`] instead.
+    /// - If the current thread's thread-local variable has been destroyed, if its registration with `self` is
+    ///   rejected (see [`AccessError::Reregistration`]), or if called reentrantly. See [`Self::try_with_data_mut`].
     pub fn with_data_mut<V>(&self, f: impl FnOnce(&mut P::Dat) -> V) -> V {
-        self.tl.with(|h| {
-            h.ensure_linked(self);
-            h.with_data_mut(f)
+        if !self.is_enabled() {
+            return f(&mut self.make_data(thread::current().id()));
+        }
+        self.contribute(f, true).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Invokes `f` mutably on the held data, unless `self` has been finalized or disabled (see
+    /// [`Self::set_enabled`]), the current thread's thread-local variable has been destroyed, e.g., when called from
+    /// the destructor of another thread-local variable during thread teardown, the thread-local variable is linked
+    /// to another control object (see [`Self::relink`]), or the call is reentrant, i.e., made from inside `f` or
+    /// `op` while the current thread is already accessing `self`.
+    ///
+    /// # Errors
+    /// Returns an error, without invoking `f`, if `self` has been finalized or disabled, the thread-local variable
+    /// has been destroyed or is linked to another control object, the call is reentrant, or the registration of the
+    /// thread-local variable with `self` is rejected (see [`AccessError::Reregistration`]).
+    pub fn try_with_data_mut<V>(&self, f: impl FnOnce(&mut P::Dat) -> V) -> Result<V, AccessError> {
+        self.enabled.check()?;
+        self.contribute(f, false)
+    }
+
+    /// Implements [`Self::try_with_data_mut`], except that `self` is assumed to be enabled, and the relinking of
+    /// [`Self::with_data_mut`] if `relink` is `true`.
+    fn contribute<V>(
+        &self,
+        f: impl FnOnce(&mut P::Dat) -> V,
+        relink: bool,
+    ) -> Result<V, AccessError> {
+        self.seal.check()?;
+        let _guard = AccessGuard::enter(access_key(&self.state))?;
+        #[cfg(feature = "overhead-stats")]
+        let _timer = self.overhead.timer();
+        // Without optional subsystems, this is the only branch on them before the data is accessed.
+        if let Some(extras) = self.extras.as_deref() {
+            activity::record(extras.activity.as_ref(), thread::current().id());
+            if let Some(touch) = &extras.tl_deps {
+                touch();
+            }
+        }
+        let res = self.tl.try_with(|h| -> Result<V, AccessError> {
+            self.link_holder(h, relink)?;
+            // The seal is checked again while the data lock is held, see `seal_and_take_acc`.
+            Ok(h.with_data_mut(|data| self.seal.check().map(|()| f(data)))?)
+        });
+        self.invalidate_probe();
+        res.map_err(|_| TlsDestroyedError)?
+    }
+
+    /// Links `h` to `self` unless it is already linked to `self`. If it is linked to another control object, it is
+    /// relinked to `self` as with [`Self::relink`] if `relink` is `true`.
+    ///
+    /// # Errors
+    /// Returns an error if `h` is linked to another control object and `relink` is `false`, or if the registration of
+    /// `h` with `self` is rejected.
+    #[inline]
+    fn link_holder(&self, h: &P::Hldr, relink: bool) -> Result<(), AccessError> {
+        match h.ensure_linked(self) {
+            Err(AccessError::Mismatch(_)) if relink => {
+                h.unlink();
+                Ok(h.link(self)?)
+            }
+            res => res,
+        }
+    }
+
+    /// Links the current thread's thread-local variable to `self`, unless it is already linked to `self` or one of its
+    /// clones. If it is linked to another control object, its value is first aggregated with that control object's
+    /// accumulated value, as if the thread-local variable had been dropped, so nothing contributed through the other
+    /// control object is lost or aggregated with `self`'s accumulated value. Does nothing if the thread-local
+    /// variable has been destroyed.
+    ///
+    /// This is needed when a thread-local static is used with different control objects over the lifetime of a
+    /// thread, e.g., a pooled thread serving successive jobs, each with its own control object, since a thread-local
+    /// variable is otherwise linked to the first control object through which it is accessed.
+    ///
+    /// # Panics
+    /// - If the mutex of either control object is poisoned.
+    /// - If called reentrantly, from inside the closure passed to [`Self::with_data_mut`] or from inside `op`.
+    /// - If the registration of the thread-local variable with `self` is rejected, see
+    ///   [`AccessError::Reregistration`].
+    pub fn relink(&self) {
+        let _guard = AccessGuard::enter(access_key(&self.state)).unwrap_or_else(|e| panic!("{e}"));
+        self.touch_tl_deps();
+        _ = self.tl.try_with(|h| {
+            if h.is_linked_to(self) {
+                return;
+            }
+            h.unlink();
+            h.link(self).unwrap_or_else(|e| panic!("{e}"));
+        });
+    }
+
+    /// Takes the value of the current thread's thread-local variable, if it has been linked to `self`, aggregates it with
+    /// `self`'s accumulated value, and replaces it with the initial data (see [`Self::with_tl_init`]).
+    ///
+    /// Thread-local values are otherwise collected when their threads terminate (or, in the `probed` module, by
+    /// `take_tls`), so this is needed for threads that are reused and never terminate, such as the threads of a
+    /// thread pool, whose contributions would otherwise never reach the accumulated value in the `joined` modules.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If called reentrantly, from inside the closure passed to [`Self::with_data_mut`] or from inside `op`.
+    pub fn flush_tl(&self) {
+        let _guard = AccessGuard::enter(access_key(&self.state)).unwrap_or_else(|e| panic!("{e}"));
+        // A destroyed thread-local variable has already been aggregated by its holder's destructor.
+        if !self.tl.try_with(|h| h.is_linked_to(self)).unwrap_or(false) {
+            return;
+        }
+        let tid = thread::current().id();
+        let mut lock = self.lock();
+        let data = self
+            .tl
+            .with(|h| h.with_data_mut(|data| replace(data, self.make_data(tid))));
+        let acc = lock.acc_mut();
+        (self.op)(data, acc, tid);
+        spill::check(self.extra(|e| &e.spill), acc);
+    }
+
+    /// Blocks until all thread-local variables linked to `self` and its clones, other than the current thread's,
+    /// have been dropped, i.e., until their values have been aggregated with `self`'s accumulated value. This is a
+    /// precondition of `take_own_tl` in the `joined` modules and makes it safe to take the accumulated value when
+    /// the participating threads are not joined explicitly.
+    ///
+    /// # Errors
+    /// Returns an error with the number of live thread-local variables, other than the current thread's, if
+    /// `timeout` expires first.
+    ///
+    /// # Panics
+    /// If the mutex used to count the linked thread-local variables is poisoned.
+    pub fn wait_quiescent(&self, timeout: Duration) -> Result<(), QuiescenceTimeoutError> {
+        let own = self.tl.try_with(|h| h.is_linked_to(self)).unwrap_or(false);
+        self.quiescence.wait(usize::from(own), timeout)
+    }
+
+    /// Registers `f` to be invoked when the thread-local variable of thread `tid` that is linked to `self` or one of
+    /// its clones is dropped, right after its value has been aggregated with `self`'s accumulated value, e.g., to
+    /// learn precisely when a worker has finished reporting. `f` is invoked at most once, on thread `tid` as it
+    /// terminates, and without holding any of `self`'s locks; to receive a message instead, pass a closure that
+    /// sends to a channel. It is also invoked if the thread-local variable is relinked to another control object
+    /// with [`Self::relink`].
+    ///
+    /// `f` is never invoked if thread `tid` has already dropped its thread-local variable or never links one to
+    /// `self`.
+    ///
+    /// # Panics
+    /// If the mutex used to count the linked thread-local variables is poisoned.
+    pub fn on_tl_dropped(&self, tid: ThreadId, f: impl FnOnce() + Send + 'static) {
+        self.quiescence.on_dropped(tid, f)
+    }
+
+    /// Returns a future that resolves when all thread-local variables linked to `self` and its clones, other than
+    /// the current thread's, have been dropped, i.e., when their threads have contributed their final values. This
+    /// is the async counterpart of [`Self::wait_quiescent`], for orchestrators that would otherwise poll for
+    /// completeness. Requires the **"async"** feature.
+    #[cfg(feature = "async")]
+    pub fn drained(&self) -> DrainedFuture {
+        let own = self.tl.try_with(|h| h.is_linked_to(self)).unwrap_or(false);
+        self.quiescence.drained(usize::from(own))
+    }
+
+    /// Runs `f` on the blocking thread pool of the current Tokio runtime with [`tokio::task::spawn_blocking`],
+    /// passing it a clone of `self`. The current thread's thread-local variable is linked to `self` before `f` is
+    /// invoked, with [`Self::relink`], and flushed with [`Self::flush_tl`] after `f` returns, since the threads of the blocking pool are
+    /// reused and may never drop their thread-local variables. Requires the **"tokio"** feature.
+    ///
+    /// If `f` panics, the contributions it made are flushed the next time the same thread runs a closure passed to
+    /// this method, or when the thread terminates.
+    ///
+    /// # Panics
+    /// If called outside of a Tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_blocking<R>(
+        &self,
+        f: impl FnOnce(&Self) -> R + Send + 'static,
+    ) -> tokio::task::JoinHandle<R>
+    where
+        Self: Send + 'static,
+        R: Send + 'static,
+    {
+        let control = self.clone();
+        tokio::task::spawn_blocking(move || {
+            control.relink();
+            let res = f(&control);
+            control.flush_tl();
+            res
+        })
+    }
+
+    /// Runs `f` on the thread pool of the [`blocking`](https://docs.rs/blocking) crate, which is used by the
+    /// `smol` and `async-std` runtimes for blocking operations, passing it a clone of `self`, and returns a task
+    /// that can be awaited for `f`'s result. As with [`Self::spawn_blocking`], the current thread's thread-local
+    /// variable is linked to `self` before `f` is invoked and flushed with [`Self::flush_tl`] after `f` returns.
+    /// Requires the **"blocking"** feature.
+    #[cfg(feature = "blocking")]
+    pub fn unblock<R>(&self, f: impl FnOnce(&Self) -> R + Send + 'static) -> blocking::Task<R>
+    where
+        Self: Send + 'static,
+        R: Send + 'static,
+    {
+        let control = self.clone();
+        blocking::unblock(move || {
+            control.relink();
+            let res = f(&control);
+            control.flush_tl();
+            res
         })
     }
+
+    /// Runs `f` as a job on `pool`, a [`threadpool::ThreadPool`], passing it a clone of `self`. As with
+    /// [`Self::spawn_blocking`], the worker thread's thread-local variable is linked to `self` before `f` is invoked
+    /// and flushed with [`Self::flush_tl`] after `f` returns, since the worker threads of a pool are reused and only
+    /// drop their thread-local variables when the pool is dropped. Requires the **"threadpool"** feature.
+    ///
+    /// If `f` panics, its worker thread terminates and the contributions it made are aggregated when its
+    /// thread-local variable is dropped.
+    #[cfg(feature = "threadpool")]
+    pub fn execute(&self, pool: &threadpool::ThreadPool, f: impl FnOnce(&Self) + Send + 'static)
+    where
+        Self: Send + 'static,
+    {
+        let control = self.clone();
+        pool.execute(move || {
+            control.relink();
+            f(&control);
+            control.flush_tl();
+        });
+    }
+
+    /// Runs `f` as a job in `scope`, a [`scoped_threadpool::Scope`], passing it a clone of `self`, so that `f` may
+    /// borrow from the enclosing stack frame. As with [`Self::execute`], the worker thread's thread-local variable is
+    /// linked to `self` before `f` is invoked and flushed after `f` returns, so the contributions of all the jobs
+    /// have been aggregated once the scope ends. Requires the **"scoped_threadpool"** feature.
+    #[cfg(feature = "scoped_threadpool")]
+    pub fn execute_scoped<'pool, 'scope>(
+        &self,
+        scope: &scoped_threadpool::Scope<'pool, 'scope>,
+        f: impl FnOnce(&Self) + Send + 'scope,
+    ) where
+        Self: Send + 'scope,
+    {
+        let control = self.clone();
+        scope.execute(move || {
+            control.relink();
+            f(&control);
+            control.flush_tl();
+        });
+    }
+}
+
+impl<P> ControlSender<ControlG<P>>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+
+    P::CtrlState: CtrlStateCore<P>,
+    P: CtrlParam<Ctrl = ControlG<P>>,
+    P::Hldr: HldrLink<P> + HldrData<P>,
+{
+    /// Invokes `f` mutably on the held data. See [`ControlG::with_data_mut`].
+    ///
+    /// # Panics
+    /// If the control object has been finalized, or under the other conditions of [`ControlG::with_data_mut`]. See
+    /// [`Self::try_with_data_mut`].
+    pub fn with_data_mut<V>(&self, f: impl FnOnce(&mut P::Dat) -> V) -> V {
+        self.0.with_data_mut(f)
+    }
+
+    /// Invokes `f` mutably on the held data, unless the control object has been finalized or the current thread's
+    /// thread-local variable has been destroyed. See [`ControlG::try_with_data_mut`].
+    pub fn try_with_data_mut<V>(&self, f: impl FnOnce(&mut P::Dat) -> V) -> Result<V, AccessError> {
+        self.0.try_with_data_mut(f)
+    }
+}
+
+impl<P, F, V> FinishedControl<ControlG<P>, F>
+where
+    P: CoreParam + CtrlStateParam + HldrParam,
+
+    P::CtrlState: CtrlStateCore<P>,
+    F: Fn(P::Acc) -> V,
+{
+    /// Returns the control object's accumulated value post-processed with the finisher, using `replacement` to
+    /// replace the existing accumulated value. See [`ControlG::take_acc`].
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn take_acc(&self, replacement: P::Acc) -> V {
+        (self.finish)(self.control.take_acc(replacement))
+    }
 }
 
 impl<P> CtrlNode<P> for ControlG<P>
@@ -292,11 +1243,15 @@ where
 {
     /// Called by [`super::HolderG`] when a thread-local variable starts being used.
     ///
+    /// # Errors
+    /// Returns an error if the registration is rejected by `self`'s state.
+    ///
     /// # Panics
     /// If `self`'s mutex is poisoned.
-    fn register_node(&self, node: P::Node, tid: ThreadId) {
-        let mut lock = self.lock();
-        lock.register_node(node, tid)
+    fn register_node(&self, node: P::Node, tid: ThreadId) -> Result<(), ReregistrationError> {
+        instr::event("register_node", tid, self.extra(|e| &e.diag));
+        let registered = self.lock().register_node(node, TlKey::new(tid, self.tl));
+        registered.then_some(()).ok_or(ReregistrationError)
     }
 }
 
@@ -310,6 +1265,14 @@ where
             state: self.state.clone(),
             make_data: self.make_data,
             op: self.op.clone(),
+            extras: self.extras.clone(),
+            pool: self.pool.clone(),
+            #[cfg(feature = "overhead-stats")]
+            overhead: self.overhead.clone(),
+            seal: self.seal.clone(),
+            enabled: self.enabled.clone(),
+            quiescence: self.quiescence.clone(),
+            fork: self.fork,
         }
     }
 }
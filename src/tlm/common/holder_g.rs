@@ -6,6 +6,7 @@ use super::{
     common_traits::*,
     control_g::{DefaultDiscr, WithNode},
 };
+use crate::tlm::ReregistrationError;
 
 use std::{
     cell::{Ref, RefCell, RefMut},
@@ -13,11 +14,18 @@ use std::{
     marker::PhantomData,
     mem::take,
     ops::{Deref, DerefMut},
-    sync::{Arc, Mutex, MutexGuard},
     thread,
 };
 
+#[cfg(feature = "probed")]
+use crate::lock_order::{LockKind, Tracked};
+#[cfg(feature = "probed")]
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+
+#[cfg(feature = "probed")]
 pub(crate) const POISONED_GUARDED_DATA_MUTEX: &str = "poisoned guarded data mutex";
+#[cfg(feature = "probed")]
+pub(crate) const POISONED_GUARDED_DATA_RWLOCK: &str = "poisoned guarded data rwlock";
 
 impl<S> New<Self> for RefCell<S> {
     type Arg = S;
@@ -35,6 +43,7 @@ impl<T: 'static> GuardedData<T> for RefCell<Option<T>> {
     }
 }
 
+#[cfg(feature = "probed")]
 impl<T> New<Self> for Arc<Mutex<T>> {
     type Arg = T;
 
@@ -43,12 +52,148 @@ impl<T> New<Self> for Arc<Mutex<T>> {
     }
 }
 
+#[cfg(feature = "probed")]
 impl<T: 'static> GuardedData<T> for Arc<Mutex<Option<T>>> {
-    type Guard<'a> = MutexGuard<'a, Option<T>>;
+    type Guard<'a> = Tracked<MutexGuard<'a, Option<T>>>;
+
+    fn guard(&self) -> Self::Guard<'_> {
+        Tracked::acquire(LockKind::Data, self.as_ref(), || {
+            self.lock().expect(POISONED_GUARDED_DATA_MUTEX)
+        })
+    }
+}
+
+#[cfg(feature = "probed")]
+impl<T: 'static> GuardedDataRead<T> for Arc<Mutex<Option<T>>> {
+    type ReadGuard<'a> = Tracked<MutexGuard<'a, Option<T>>>;
+
+    fn read_guard(&self) -> Self::ReadGuard<'_> {
+        self.guard()
+    }
+
+    type Weak = Weak<Mutex<Option<T>>>;
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn upgrade(weak: &Self::Weak) -> Option<Self> {
+        weak.upgrade()
+    }
+}
+
+#[cfg(feature = "probed")]
+impl<T> New<Self> for Arc<RwLock<T>> {
+    type Arg = T;
+
+    fn new(t: Self::Arg) -> Self {
+        Arc::new(RwLock::new(t))
+    }
+}
+
+#[cfg(feature = "probed")]
+impl<T: 'static> GuardedData<T> for Arc<RwLock<Option<T>>> {
+    type Guard<'a> = Tracked<RwLockWriteGuard<'a, Option<T>>>;
+
+    fn guard(&self) -> Self::Guard<'_> {
+        Tracked::acquire(LockKind::Data, self.as_ref(), || {
+            self.write().expect(POISONED_GUARDED_DATA_RWLOCK)
+        })
+    }
+}
+
+#[cfg(feature = "probed")]
+impl<T: 'static> GuardedDataRead<T> for Arc<RwLock<Option<T>>> {
+    type ReadGuard<'a> = Tracked<RwLockReadGuard<'a, Option<T>>>;
+
+    fn read_guard(&self) -> Self::ReadGuard<'_> {
+        Tracked::acquire(LockKind::Data, self.as_ref(), || {
+            self.read().expect(POISONED_GUARDED_DATA_RWLOCK)
+        })
+    }
+
+    type Weak = Weak<RwLock<Option<T>>>;
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn upgrade(weak: &Self::Weak) -> Option<Self> {
+        weak.upgrade()
+    }
+}
+
+#[cfg(all(feature = "probed", feature = "parking_lot"))]
+impl<T> New<Self> for Arc<parking_lot::Mutex<T>> {
+    type Arg = T;
+
+    fn new(t: Self::Arg) -> Self {
+        Arc::new(parking_lot::Mutex::new(t))
+    }
+}
+
+#[cfg(all(feature = "probed", feature = "parking_lot"))]
+impl<T: 'static> GuardedData<T> for Arc<parking_lot::Mutex<Option<T>>> {
+    type Guard<'a> = Tracked<parking_lot::MutexGuard<'a, Option<T>>>;
 
     fn guard(&self) -> Self::Guard<'_> {
-        let res = self.lock().expect(POISONED_GUARDED_DATA_MUTEX);
-        res
+        Tracked::acquire(LockKind::Data, self.as_ref(), || self.lock())
+    }
+}
+
+#[cfg(all(feature = "probed", feature = "parking_lot"))]
+impl<T: 'static> GuardedDataRead<T> for Arc<parking_lot::Mutex<Option<T>>> {
+    type ReadGuard<'a> = Tracked<parking_lot::MutexGuard<'a, Option<T>>>;
+
+    fn read_guard(&self) -> Self::ReadGuard<'_> {
+        self.guard()
+    }
+
+    type Weak = Weak<parking_lot::Mutex<Option<T>>>;
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn upgrade(weak: &Self::Weak) -> Option<Self> {
+        weak.upgrade()
+    }
+}
+
+#[cfg(all(feature = "probed", feature = "parking_lot"))]
+impl<T> New<Self> for Arc<parking_lot::RwLock<T>> {
+    type Arg = T;
+
+    fn new(t: Self::Arg) -> Self {
+        Arc::new(parking_lot::RwLock::new(t))
+    }
+}
+
+#[cfg(all(feature = "probed", feature = "parking_lot"))]
+impl<T: 'static> GuardedData<T> for Arc<parking_lot::RwLock<Option<T>>> {
+    type Guard<'a> = Tracked<parking_lot::RwLockWriteGuard<'a, Option<T>>>;
+
+    fn guard(&self) -> Self::Guard<'_> {
+        Tracked::acquire(LockKind::Data, self.as_ref(), || self.write())
+    }
+}
+
+#[cfg(all(feature = "probed", feature = "parking_lot"))]
+impl<T: 'static> GuardedDataRead<T> for Arc<parking_lot::RwLock<Option<T>>> {
+    type ReadGuard<'a> = Tracked<parking_lot::RwLockReadGuard<'a, Option<T>>>;
+
+    fn read_guard(&self) -> Self::ReadGuard<'_> {
+        Tracked::acquire(LockKind::Data, self.as_ref(), || self.read())
+    }
+
+    type Weak = Weak<parking_lot::RwLock<Option<T>>>;
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn upgrade(weak: &Self::Weak) -> Option<Self> {
+        weak.upgrade()
     }
 }
 
@@ -98,10 +243,12 @@ where
     P::GData: GuardedData<P::Dat, Arg = Option<P::Dat>>,
     P::Ctrl: Ctrl<P>,
 {
-    /// Instantiates a holder object.
-    pub fn new() -> Self {
+    /// Instantiates a holder object with the given (empty) guarded data. Used to implement the `new` functions of
+    /// the module-specific holders, which are `const fn`s when the guarded data can be constructed in a `const`
+    /// context.
+    pub(crate) const fn with_data(data: P::GData) -> Self {
         Self {
-            data: P::GData::new(None),
+            data,
             control: RefCell::new(None),
             _d: PhantomData,
         }
@@ -112,35 +259,61 @@ where
         self.control.borrow()
     }
 
-    fn is_linked(&self) -> bool {
+    /// Returns whether `self` has been linked to a control object, which happens on the first contribution of the
+    /// current thread to it. Call it through the thread-local static, e.g., `MY_TL.with(Holder::is_linked)`, to
+    /// check whether the current thread has already registered with the control object.
+    pub fn is_linked(&self) -> bool {
         self.control().as_ref().is_some()
     }
 
-    /// Returns data guard for the held data, ensuring the data is initialized.
+    /// Returns whether `self` has been linked to `control` or one of its clones. A holder is linked to the first
+    /// control object through which it is accessed on its thread, until it is relinked with the control object's
+    /// `relink` method.
+    pub fn is_linked_to(&self, control: &P::Ctrl) -> bool {
+        self.linked_id() == Some(control.ctrl_id())
+    }
+
+    /// Returns the [`Ctrl::ctrl_id`] of the control object `self` is linked to, if any.
+    fn linked_id(&self) -> Option<usize> {
+        self.control().as_ref().map(Ctrl::ctrl_id)
+    }
+
+    /// Notifies the control object `self` is linked to, if any, as if `self` had been dropped, which aggregates the
+    /// held data with that control object's accumulated value, and unlinks `self`. Used to relink `self`.
+    fn unlink(&self) {
+        self.drop_data();
+        *self.control.borrow_mut() = None;
+    }
+
+    /// Returns data guard for the held data.
+    ///
+    /// The data is initialized when `self` is linked (see [`Self::init_data`]) and remains initialized until `self`
+    /// is dropped, so the steady-state access path does not need to check for or perform initialization.
     pub(crate) fn data_guard(&self) -> <P::GData as GuardedData<P::Dat>>::Guard<'_> {
-        let mut guard = self.data.guard();
-        if guard.is_none() {
-            let control_guard = self.control();
-            let control = control_guard
-                .as_ref()
-                .expect("holder must be linked to control");
-            let data = guard.deref_mut();
-            *data = Some(control.make_data());
-        }
-        guard
+        self.data.guard()
+    }
+
+    /// Initializes the held data with `control`'s `make_data` function. Called by [`HldrLink::link`].
+    fn init_data(&self, control: &P::Ctrl) {
+        control.tl_linked();
+        *self.data.guard() = Some(control.make_data(thread::current().id()));
     }
 
     /// Used by [`Drop`] trait impl.
+    ///
+    /// The data is taken by `control` while it holds its state lock. This follows the library's lock-ordering
+    /// protocol: the control's state lock is always acquired before a holder's data lock (e.g., by
+    /// `take_tls`), so a holder must never hold its data lock while acquiring the control's state lock.
+    /// It also ensures that a concurrent `take_tls` or `probe_tls` either finds the data in this holder or
+    /// finds it already accumulated, so the data is accumulated exactly once and probes never transiently
+    /// miss it.
     fn drop_data(&self) {
         match self.control().deref() {
             None => (),
-            Some(control) => {
-                let mut data_guard = self.data_guard();
-                let data = take(data_guard.deref_mut());
-                if let Some(data) = data {
-                    control.tl_data_dropped(data, thread::current().id())
-                };
-            }
+            Some(control) => control.tl_data_dropped(
+                || take(self.data_guard().deref_mut()),
+                thread::current().id(),
+            ),
         }
     }
 }
@@ -153,8 +326,8 @@ where
 {
     /// Invokes `f` on the held data.
     ///
-    /// # Errors
-    /// Returns an error if [`HolderG`] not linked with [`super::ControlG`].
+    /// # Panics
+    /// If [`HolderG`] not linked with [`super::ControlG`].
     fn with_data<V>(&self, f: impl FnOnce(&P::Dat) -> V) -> V {
         let guard = self.data_guard();
         f(guard.unwrap())
@@ -162,8 +335,8 @@ where
 
     /// Invokes `f` mutably on the held data.
     ///
-    /// # Errors
-    /// Returns an error if [`HolderG`] not linked with [`super::ControlG`].
+    /// # Panics
+    /// If [`HolderG`] not linked with [`super::ControlG`].
     fn with_data_mut<V>(&self, f: impl FnOnce(&mut P::Dat) -> V) -> V {
         let mut guard = self.data_guard();
         f(guard.unwrap_mut())
@@ -177,15 +350,25 @@ where
 
     P::Ctrl: Ctrl<P> + Clone,
 {
-    /// Initializes the `control` field in [`HolderG`].
-    fn link(&self, control: &P::Ctrl) {
+    /// Initializes the `control` field and the held data in [`HolderG`].
+    fn link(&self, control: &P::Ctrl) -> Result<(), ReregistrationError> {
         let mut ctrl_ref = self.control.borrow_mut();
         *ctrl_ref = Some(control.clone());
+        self.init_data(control);
+        Ok(())
     }
 
     fn is_linked(&self) -> bool {
         Self::is_linked(self)
     }
+
+    fn linked_id(&self) -> Option<usize> {
+        Self::linked_id(self)
+    }
+
+    fn unlink(&self) {
+        Self::unlink(self)
+    }
 }
 
 impl<P> HldrLink<P> for HolderG<P, WithNode>
@@ -196,16 +379,32 @@ where
     P: NodeParam<NodeFnArg = P::Ctrl>,
     P::Ctrl: Ctrl<P> + CtrlNode<P> + Clone,
 {
-    /// Initializes the `control` field in [`HolderG`] when a node type is used.
-    fn link(&self, control: &P::Ctrl) {
+    /// Initializes the `control` field and the held data in [`HolderG`] when a node type is used.
+    ///
+    /// The node is registered with `control` before this method returns and therefore before the held data can be
+    /// updated, so a concurrent `take_tls` either sees the node or there is no data to be taken. The held data is
+    /// initialized after the node is registered, without holding `control`'s state lock. The node is registered
+    /// before `self` is linked, so a rejected registration leaves `self` unlinked and does not unregister the node
+    /// it collided with when `self` is dropped.
+    fn link(&self, control: &P::Ctrl) -> Result<(), ReregistrationError> {
+        control.register_node(P::node_fn(control), thread::current().id())?;
         let mut ctrl_ref = self.control.borrow_mut();
         *ctrl_ref = Some(control.clone());
-        control.register_node(P::node_fn(control), thread::current().id());
+        self.init_data(control);
+        Ok(())
     }
 
     fn is_linked(&self) -> bool {
         Self::is_linked(self)
     }
+
+    fn linked_id(&self) -> Option<usize> {
+        Self::linked_id(self)
+    }
+
+    fn unlink(&self) {
+        Self::unlink(self)
+    }
 }
 
 impl<P, D> Debug for HolderG<P, D>
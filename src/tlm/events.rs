@@ -0,0 +1,289 @@
+//! This module supports the collection of heterogeneous values (*events*) from multiple threads through a single
+//! [`tlm::channeled`](super::channeled) control object, which dispatches each event by type to the accumulator
+//! registered for that type. It allows an application to collect different kinds of telemetry through one channel
+//! instead of using one control object per kind.
+//!
+//! - Accumulators are registered, one per event type, in an [`EventAccs`] value with [`EventAccs::with_acc`].
+//! - The [`Control`] object is instantiated with [`Control::from_event_accs`] and events are sent with
+//!   [`Control::send_event`].
+//! - Events are received and dispatched exactly as values are in [`tlm::channeled`](super::channeled), e.g., with
+//!   [`Control::drain_tls`] or a background receiver thread.
+//! - The accumulated value for an event type is accessed with [`Control::with_event_acc`] or taken with
+//!   [`Control::take_event_acc`].
+//! - Events of a type for which no accumulator has been registered are discarded and counted, see
+//!   [`EventAccs::unmatched`].
+//!
+//! ## Usage pattern
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use thread_local_collect::tlm::events::{Control, EventAccs, Holder};
+//!
+//! struct Latency(Duration);
+//! struct Error(&'static str);
+//!
+//! thread_local! {
+//!     static EVENTS_TL: Holder = Holder::new();
+//! }
+//!
+//! let accs = EventAccs::new()
+//!     .with_acc(Duration::ZERO, |Latency(l), acc: &mut Duration, _| *acc += l)
+//!     .with_acc(Vec::new(), |Error(e), acc: &mut Vec<&'static str>, _| acc.push(e));
+//! let control = Control::from_event_accs(&EVENTS_TL, accs);
+//!
+//! std::thread::scope(|s| {
+//!     s.spawn(|| control.send_event(Latency(Duration::from_millis(5))));
+//!     s.spawn(|| control.send_event(Error("timeout")));
+//! });
+//! control.send_event(Latency(Duration::from_millis(3)));
+//! control.drain_tls();
+//!
+//! assert_eq!(
+//!     control.with_event_acc::<Latency, Duration, _>(|acc| acc.copied()),
+//!     Some(Duration::from_millis(8))
+//! );
+//! assert_eq!(
+//!     control.take_event_acc::<Error, Vec<&'static str>>(Vec::new()),
+//!     Some(vec!["timeout"])
+//! );
+//! ```
+
+use super::channeled;
+use crate::handles::ControlSender;
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+    mem::replace,
+    thread::{LocalKey, ThreadId},
+};
+
+/// Type-erased event sent to a [`Control`].
+pub type Event = Box<dyn Any + Send>;
+
+/// Specialization of [`channeled::Holder`] for [`Event`]s.
+pub type Holder = channeled::Holder<Event>;
+
+/// Specialization of [`channeled::Control`] that dispatches [`Event`]s to the accumulators in an [`EventAccs`].
+pub type Control = channeled::Control<Event, EventAccs>;
+
+/// Type-erased accumulator for events of a single type.
+trait ErasedAcc: Send {
+    fn absorb(&mut self, event: Event, tid: ThreadId);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn acc_type_name(&self) -> &'static str;
+}
+
+/// Accumulator of type `U` for events of type `E`.
+struct TypedAcc<E, U> {
+    acc: U,
+    op: Box<dyn Fn(E, &mut U, ThreadId) + Send + Sync>,
+}
+
+impl<E, U> ErasedAcc for TypedAcc<E, U>
+where
+    E: 'static,
+    U: Send + 'static,
+{
+    fn absorb(&mut self, event: Event, tid: ThreadId) {
+        // `EventAccs` only dispatches events of type `E` to this accumulator.
+        if let Ok(event) = event.downcast::<E>() {
+            (self.op)(*event, &mut self.acc, tid);
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        &self.acc
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.acc
+    }
+
+    fn acc_type_name(&self) -> &'static str {
+        type_name::<U>()
+    }
+}
+
+/// Accumulated value of a [`Control`]: a collection of accumulators, each registered for a distinct event type.
+pub struct EventAccs {
+    accs: HashMap<TypeId, Box<dyn ErasedAcc>>,
+    unmatched: usize,
+}
+
+impl EventAccs {
+    /// Instantiates an [`EventAccs`] with no registered accumulators.
+    pub fn new() -> Self {
+        Self {
+            accs: HashMap::new(),
+            unmatched: 0,
+        }
+    }
+
+    /// Registers an accumulator for events of type `E`, with initial value `acc_base` and operation `op`
+    /// that combines events with the accumulated value. Replaces any accumulator previously registered for `E`.
+    pub fn with_acc<E, U>(
+        mut self,
+        acc_base: U,
+        op: impl Fn(E, &mut U, ThreadId) + 'static + Send + Sync,
+    ) -> Self
+    where
+        E: 'static,
+        U: Send + 'static,
+    {
+        let acc = TypedAcc {
+            acc: acc_base,
+            op: Box::new(op),
+        };
+        self.accs.insert(TypeId::of::<E>(), Box::new(acc));
+        self
+    }
+
+    /// Returns a reference to the accumulated value for events of type `E`, or `None` if no accumulator of type
+    /// `U` is registered for `E`.
+    pub fn acc<E: 'static, U: 'static>(&self) -> Option<&U> {
+        self.accs.get(&TypeId::of::<E>())?.as_any().downcast_ref()
+    }
+
+    /// Returns a mutable reference to the accumulated value for events of type `E`, or `None` if no accumulator
+    /// of type `U` is registered for `E`.
+    pub fn acc_mut<E: 'static, U: 'static>(&mut self) -> Option<&mut U> {
+        self.accs
+            .get_mut(&TypeId::of::<E>())?
+            .as_any_mut()
+            .downcast_mut()
+    }
+
+    /// Returns the number of events that were discarded because no accumulator was registered for their type.
+    pub fn unmatched(&self) -> usize {
+        self.unmatched
+    }
+
+    /// Dispatches `event` to the accumulator registered for its type.
+    fn absorb(&mut self, event: Event, tid: ThreadId) {
+        match self.accs.get_mut(&(*event).type_id()) {
+            Some(acc) => acc.absorb(event, tid),
+            None => self.unmatched += 1,
+        }
+    }
+}
+
+impl Debug for EventAccs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let accs: Vec<_> = self.accs.values().map(|acc| acc.acc_type_name()).collect();
+        f.debug_struct("EventAccs")
+            .field("accs", &accs)
+            .field("unmatched", &self.unmatched)
+            .finish()
+    }
+}
+
+impl Control {
+    /// Instantiates a *control* object that dispatches events to the accumulators registered in `accs`.
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn from_event_accs(tl: &'static LocalKey<Holder>, accs: EventAccs) -> Self {
+        Self::new(tl, accs, |event, accs, tid| accs.absorb(event, tid))
+    }
+
+    /// Sends `event` from the thread where it is called to be dispatched by `self` to the accumulator registered
+    /// for type `E`.
+    pub fn send_event<E: Send + 'static>(&self, event: E) {
+        self.send_data(Box::new(event))
+    }
+
+    /// Invokes `f` with the accumulated value for events of type `E`, or `None` if no accumulator of type `U`
+    /// is registered for `E`.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn with_event_acc<E: 'static, U: 'static, V>(&self, f: impl FnOnce(Option<&U>) -> V) -> V {
+        self.with_acc(|accs| f(accs.acc::<E, U>()))
+    }
+
+    /// Returns the accumulated value for events of type `E`, using `replacement` to replace it, or `None` (in which
+    /// case `replacement` is dropped) if no accumulator of type `U` is registered for `E`.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_event_acc<E: 'static, U: 'static>(&self, replacement: U) -> Option<U> {
+        self.with_acc_mut(|accs| accs.acc_mut::<E, U>().map(|acc| replace(acc, replacement)))
+    }
+}
+
+impl ControlSender<Control> {
+    /// Sends `event` from the thread where it is called to be dispatched by the [`Control`] instance.
+    /// See [`Control::send_event`].
+    pub fn send_event<E: Send + 'static>(&self, event: E) {
+        self.0.send_event(event)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{Control, EventAccs, Holder};
+    use crate::dev_support::assert_eq_and_println;
+    use std::thread::{self, ThreadId};
+
+    thread_local! {
+        static EVENTS_TL: Holder = Holder::new();
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Hit(&'static str);
+
+    struct Bytes(u64);
+
+    #[test]
+    fn dispatch_by_type() {
+        let accs = EventAccs::new()
+            .with_acc(
+                Vec::new(),
+                |Hit(h), acc: &mut Vec<(&str, ThreadId)>, tid| acc.push((h, tid)),
+            )
+            .with_acc(0u64, |Bytes(b), acc: &mut u64, _| *acc += b);
+        let control = Control::from_event_accs(&EVENTS_TL, accs);
+
+        let tid_spawned = thread::scope(|s| {
+            let sender = control.sender();
+            s.spawn(move || {
+                sender.send_event(Hit("a"));
+                sender.send_event(Bytes(10));
+                sender.send_event("unregistered");
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        control.send_event(Bytes(5));
+        control.drain_tls();
+
+        assert_eq_and_println(
+            &control.with_event_acc::<Hit, Vec<(&str, ThreadId)>, _>(|acc| acc.cloned()),
+            &Some(vec![("a", tid_spawned)]),
+            "Hit accumulator",
+        );
+        assert_eq_and_println(
+            &control.take_event_acc::<Bytes, u64>(0),
+            &Some(15),
+            "Bytes accumulator",
+        );
+        assert_eq_and_println(
+            &control.with_event_acc::<Bytes, u64, _>(|acc| acc.copied()),
+            &Some(0),
+            "Bytes accumulator after take",
+        );
+        assert_eq_and_println(
+            &control.with_event_acc::<Bytes, i32, _>(|acc| acc.copied()),
+            &None,
+            "wrong accumulator type",
+        );
+        assert_eq_and_println(
+            &control.with_acc(|accs| accs.unmatched()),
+            &1,
+            "unmatched events",
+        );
+    }
+}
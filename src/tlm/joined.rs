@@ -23,16 +23,19 @@
 pub use crate::tlm::common::{ControlG, HolderG};
 
 use super::common::{Ctrl, CtrlParam, DefaultDiscr, HldrParam};
-use crate::tlm::common::{
-    CoreParam, CtrlStateG, CtrlStateParam, CtrlStateWithNode, GDataParam, New, NodeParam,
-    SubStateParam, WithNode,
+use crate::{
+    instr::OpInstr,
+    spill,
+    tlm::common::{
+        CoreParam, CtrlStateG, CtrlStateParam, CtrlStateWithNode, GDataParam, New, NodeParam,
+        SubStateParam, TlKey, WithNode,
+    },
 };
 use std::{
     cell::RefCell,
     marker::PhantomData,
-    mem::replace,
     ops::DerefMut,
-    thread::{self, ThreadId},
+    thread::{self, JoinHandle, LocalKey, ThreadId},
 };
 
 //=================
@@ -41,12 +44,30 @@ use std::{
 /// Parameter bundle that enables specialization of the common generic structs for this module.
 #[derive(Debug)]
 pub struct Joined<T, U> {
-    own_tl_used: bool,
-    tid: ThreadId,
     _t: PhantomData<T>,
     _u: PhantomData<U>,
 }
 
+#[doc(hidden)]
+/// Sub-state of [`Control`] that keeps track of the thread-local statics used in the thread
+/// responsible for collection/aggregation.
+#[derive(Debug)]
+pub struct OwnTls<H: 'static> {
+    tid: ThreadId,
+    own_tls: Vec<&'static LocalKey<H>>,
+}
+
+impl<H> New<OwnTls<H>> for OwnTls<H> {
+    type Arg = ();
+
+    fn new(_: ()) -> OwnTls<H> {
+        Self {
+            tid: thread::current().id(),
+            own_tls: Vec::new(),
+        }
+    }
+}
+
 type P<T, U> = Joined<T, U>;
 
 impl<T, U> CoreParam for P<T, U> {
@@ -59,33 +80,26 @@ where
     T: 'static,
     U: 'static,
 {
-    type Node = ();
+    type Node = &'static LocalKey<Holder<T, U>>;
     type NodeFnArg = Control<T, U>;
 
-    fn node_fn(_arg: &Self::NodeFnArg) -> Self::Node {}
+    fn node_fn(arg: &Self::NodeFnArg) -> Self::Node {
+        arg.tl
+    }
 }
 
-impl<T, U> SubStateParam for P<T, U> {
-    type SubState = Self;
+impl<T, U> SubStateParam for P<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
+    type SubState = OwnTls<Holder<T, U>>;
 }
 
 impl<T, U> GDataParam for P<T, U> {
     type GData = RefCell<Option<T>>;
 }
 
-impl<T, U> New<P<T, U>> for P<T, U> {
-    type Arg = ();
-
-    fn new(_: ()) -> P<T, U> {
-        Self {
-            own_tl_used: false,
-            tid: thread::current().id(),
-            _t: PhantomData,
-            _u: PhantomData,
-        }
-    }
-}
-
 impl<T, U> CtrlParam for P<T, U>
 where
     T: 'static,
@@ -104,7 +118,11 @@ where
 
 type CtrlState<T, U> = CtrlStateG<P<T, U>, DefaultDiscr>;
 
-impl<T, U> CtrlStateParam for P<T, U> {
+impl<T, U> CtrlStateParam for P<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
     type CtrlState = CtrlState<T, U>;
 }
 
@@ -113,10 +131,12 @@ where
     T: 'static,
     U: 'static,
 {
-    fn register_node(&mut self, _node: (), tid: ThreadId) {
-        if tid == self.s.tid {
-            self.s.own_tl_used = true;
+    fn register_node(&mut self, node: &'static LocalKey<Holder<T, U>>, key: TlKey) -> bool {
+        // A thread-local variable that is relinked to the control object is already registered.
+        if key.tid == self.s.tid && !self.s.own_tls.iter().any(|tl| std::ptr::eq(*tl, node)) {
+            self.s.own_tls.push(node);
         }
+        true
     }
 }
 
@@ -135,29 +155,163 @@ where
     /// This method takes the value of the designated thread-local variable in the thread responsible for
     /// collection/aggregation (i.e., the thread where `self` is instantiated), if that variable is used, and
     /// aggregates that value with this object's accumulator, replacing that value with the evaluation of the
-    /// `make_data` function passed to [`Control::new`].
+    /// `make_data` function passed to [`Control::new`] (or of the function set with [`Control::with_tl_init`]).
+    /// The same applies to any other thread-local statics linked to this object's state with [`Control::for_tl`].
     ///
     /// This object's accumulated value reflects the aggregation of all participating thread-local values when this
     /// method is called from the thread responsible for collection/aggregation after the other threads have terminated
-    /// and explicitly joined, directly or indirectly, into the thread responsible for collection/aggregation.
+    /// and explicitly joined, directly or indirectly, into the thread responsible for collection/aggregation, or
+    /// after [`ControlG::wait_quiescent`] has returned successfully.
     ///
     /// # Panics
     /// If `self`'s mutex is poisoned.
     pub fn take_own_tl(&self) {
+        let mut instr = OpInstr::start("take_own_tl", self.extra(|e| &e.diag));
         let mut guard = self.lock();
         // Need explicit deref_mut to avoid compilation error in for loop.
         let state = guard.deref_mut();
-        if state.s.own_tl_used {
-            self.tl.with(|h| {
+        for tl in state.s.own_tls.iter() {
+            tl.with(|h| {
+                // The thread-local variable may have been relinked to another control object.
+                if !h.is_linked_to(self) {
+                    return;
+                }
                 let mut data_guard = h.data_guard();
-                let data = replace(data_guard.deref_mut(), Some(self.make_data()));
+                let tid = thread::current().id();
+                let data = data_guard.replace(self.make_data(tid));
                 if let Some(data) = data {
-                    log::trace!("`take_own_tl`: executing `op`");
-                    (self.op)(data, &mut state.acc, thread::current().id());
+                    instr.item(tid);
+                    (self.op)(data, &mut state.acc, tid);
+                    spill::check(self.extra(|e| &e.spill), &mut state.acc);
                 }
             });
         }
     }
+
+    /// Finalizes `self`, taking the value of the designated thread-local variable in the calling thread as with
+    /// [`Self::take_own_tl`] and returning the accumulated value, which is replaced with `replacement`. Further
+    /// contributions to `self` or its clones are rejected: [`ControlG::with_data_mut`] panics and
+    /// [`ControlG::try_with_data_mut`] returns [`AccessError::Sealed`](crate::tlm::AccessError::Sealed). As with
+    /// [`Self::take_own_tl`], the returned value is final when this method is called from the thread responsible for
+    /// collection/aggregation after the other threads have terminated and explicitly joined into it.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn finalize(&self, replacement: U) -> U {
+        self.seal_and_take_acc(replacement, || self.take_own_tl())
+    }
+
+    /// Joins the participating threads through `handles`, takes the value of the designated thread-local variable
+    /// in the calling thread as with [`Self::take_own_tl`], and returns the accumulated value, which is replaced with
+    /// `replacement`, together with the result of each thread, in the order of `handles`.
+    ///
+    /// This satisfies the module's precondition that the participating threads be explicitly joined before the
+    /// final accumulation, so the returned value is final provided that `handles` includes all threads that
+    /// contributed to `self` and this method is called from the thread responsible for collection/aggregation.
+    /// A thread that panicked still has its thread-local value aggregated, and its panic payload is returned in its
+    /// result rather than propagated.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn join_and_take<R>(
+        &self,
+        handles: impl IntoIterator<Item = JoinHandle<R>>,
+        replacement: U,
+    ) -> (U, Vec<thread::Result<R>>) {
+        let results = handles.into_iter().map(JoinHandle::join).collect();
+        self.take_own_tl();
+        (self.take_acc(replacement), results)
+    }
+}
+
+/// Owns the threads spawned through it for a [`Control`], so that the module's precondition that the participating
+/// threads be explicitly joined before the final accumulation is met without the caller having to reason about the
+/// order of joins and thread-local drops. Created with [`ThreadRegistry::new`] and consumed by
+/// [`ThreadRegistry::finish`].
+///
+/// ```rust
+/// use thread_local_collect::tlm::joined::{Control, Holder, ThreadRegistry};
+///
+/// thread_local! {
+///     static COUNT_TL: Holder<u64, u64> = Holder::new();
+/// }
+///
+/// let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+/// let mut registry = ThreadRegistry::new(&control);
+/// for i in 1..=3 {
+///     registry.spawn(move |control| control.with_data_mut(|data| *data += i));
+/// }
+/// assert_eq!(registry.finish(0).unwrap(), 6);
+/// ```
+pub struct ThreadRegistry<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
+    control: Control<T, U>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl<T, U> ThreadRegistry<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
+    /// Instantiates a registry whose threads contribute to `control` and its clones. It should be created and
+    /// finished in the thread responsible for collection/aggregation.
+    pub fn new(control: &Control<T, U>) -> Self {
+        Self {
+            control: control.clone(),
+            handles: Vec::new(),
+        }
+    }
+
+    /// Spawns a thread that links its thread-local variable to the registry's control object and then runs `f`,
+    /// passing it a clone of the control object.
+    pub fn spawn(&mut self, f: impl FnOnce(&Control<T, U>) + Send + 'static)
+    where
+        Control<T, U>: Send,
+    {
+        let control = self.control.clone();
+        self.handles.push(thread::spawn(move || {
+            control.relink();
+            f(&control);
+        }));
+    }
+
+    /// Returns the number of threads spawned through `self`.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns `true` if no threads have been spawned through `self`.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Joins all threads spawned through `self` and returns the final accumulated value, including the value of the
+    /// designated thread-local variable in the calling thread, replacing it with `replacement`
+    /// (see [`Control::join_and_take`]).
+    ///
+    /// # Errors
+    /// If any thread panicked, returns the panic payload of the first such thread, in spawning order, without taking
+    /// the accumulated value, which still includes the thread-local values of all threads, including those that
+    /// panicked, and remains available through the control object.
+    ///
+    /// # Panics
+    /// If the control object's mutex is poisoned.
+    pub fn finish(self, replacement: U) -> thread::Result<U> {
+        let mut res = Ok(());
+        for h in self.handles {
+            let joined = h.join();
+            if res.is_ok() {
+                res = joined;
+            }
+        }
+        res?;
+        self.control.take_own_tl();
+        Ok(self.control.take_acc(replacement))
+    }
 }
 
 /// Specialization of [`HolderG`] for this module.
@@ -165,16 +319,38 @@ where
 /// the held data with the control object.
 pub type Holder<T, U> = HolderG<P<T, U>, WithNode>;
 
+impl<T, U> Holder<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
+    /// Instantiates a holder object. As this is a `const fn`, the thread-local can be declared with a `const`
+    /// initializer, which avoids the lazy-initialization check on each access:
+    ///
+    /// ```rust
+    /// use thread_local_collect::tlm::joined::Holder;
+    ///
+    /// thread_local! {
+    ///     static MY_TL: Holder<i32, i32> = const { Holder::new() };
+    /// }
+    /// ```
+    pub const fn new() -> Self {
+        Self::with_data(RefCell::new(None))
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
-    use crate::dev_support::assert_eq_and_println;
+    use crate::{accumulator::PerThread, dev_support::assert_eq_and_println};
     use std::{
         collections::HashMap,
         fmt::Debug,
         iter::once,
+        sync::mpsc,
         thread::{self, ThreadId},
+        time::Duration,
     };
 
     // Avoid using active lock in an assertion. Otherwise, if the assertion fails,
@@ -188,7 +364,8 @@ mod tests {
     type AccValue = HashMap<ThreadId, HashMap<i32, Foo>>;
 
     thread_local! {
-        static MY_TL: Holder<Data, AccValue> = Holder::new();
+        static MY_TL: Holder<Data, AccValue> = const { Holder::new() };
+        static MY_TL2: Holder<Data, AccValue> = const { Holder::new() };
     }
 
     fn insert_tl_entry(k: i32, v: Foo, control: &Control<Data, AccValue>) {
@@ -410,4 +587,381 @@ mod tests {
             assert_eq_and_println(&acc, &map, "take_acc - control reused");
         }
     }
+
+    #[test]
+    fn multiple_tls() {
+        let control = Control::new(&MY_TL, HashMap::new(), HashMap::new, op);
+        let control2 = control.for_tl(&MY_TL2);
+
+        let tid_own = thread::current().id();
+        insert_tl_entry(1, Foo("a".to_owned()), &control);
+        insert_tl_entry(2, Foo("b".to_owned()), &control2);
+        let map_own = HashMap::from([(1, Foo("a".to_owned())), (2, Foo("b".to_owned()))]);
+
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                insert_tl_entry(11, Foo("x".to_owned()), &control);
+                insert_tl_entry(22, Foo("y".to_owned()), &control2);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        let map_spawned = HashMap::from([(11, Foo("x".to_owned())), (22, Foo("y".to_owned()))]);
+
+        control.take_own_tl();
+        let map = HashMap::from([(tid_own, map_own), (tid_spawned, map_spawned)]);
+        let acc = control2.take_acc(HashMap::new());
+        assert_eq_and_println(&acc, &map, "take_acc with multiple thread-local statics");
+    }
+
+    #[test]
+    fn with_tl_dependencies() {
+        use std::cell::RefCell;
+
+        thread_local! {
+            static U64_TL: Holder<u64, Vec<bool>> = const { Holder::new() };
+            // Has a destructor, so it is unavailable once destroyed.
+            static DEP_TL: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+        }
+
+        // Records whether `DEP_TL` is still available when the data is aggregated at thread exit.
+        let new_control = || {
+            Control::new(
+                &U64_TL,
+                Vec::new(),
+                || 0,
+                |_, acc: &mut Vec<bool>, _| acc.push(DEP_TL.try_with(|_| ()).is_ok()),
+            )
+        };
+        let contribute = |control: &Control<u64, Vec<bool>>| {
+            control.with_data_mut(|data| *data += 1);
+            DEP_TL.with(|dep| dep.borrow_mut().push(1));
+        };
+
+        let control = new_control();
+        thread::scope(|s| s.spawn(|| contribute(&control)).join().unwrap());
+        assert_eq_and_println(
+            &control.take_acc(Vec::new()),
+            &vec![false],
+            "dependency destroyed first",
+        );
+
+        let control = new_control().with_tl_dependencies(|| DEP_TL.with(|_| ()));
+        thread::scope(|s| s.spawn(|| contribute(&control)).join().unwrap());
+        assert_eq_and_println(
+            &control.take_acc(Vec::new()),
+            &vec![true],
+            "dependency outlives holder",
+        );
+    }
+
+    #[test]
+    fn flush_tl() {
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        let (data_sender, data_receiver) = mpsc::channel::<u64>();
+        let (done_sender, done_receiver) = mpsc::channel();
+
+        // Pooled worker thread that keeps running while its contributions are collected.
+        let worker = thread::spawn({
+            let control = control.clone();
+            move || {
+                for data in data_receiver {
+                    control.with_data_mut(|acc| *acc += data);
+                    control.flush_tl();
+                    done_sender.send(()).unwrap();
+                }
+            }
+        });
+
+        data_sender.send(1).unwrap();
+        done_receiver.recv().unwrap();
+        assert_eq_and_println(&control.take_acc(0), &1, "flushed while worker is alive");
+        data_sender.send(2).unwrap();
+        done_receiver.recv().unwrap();
+        assert_eq_and_println(&control.take_acc(0), &2, "flushed again");
+
+        drop(data_sender);
+        worker.join().unwrap();
+        assert_eq_and_println(
+            &control.take_acc(0),
+            &0,
+            "nothing left after worker terminates",
+        );
+    }
+
+    #[test]
+    fn wait_quiescent() {
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        control.with_data_mut(|acc| *acc += 1);
+        for i in 2..=4 {
+            let control = control.clone();
+            thread::spawn(move || control.with_data_mut(|acc| *acc += i));
+        }
+        control.wait_quiescent(Duration::from_secs(5)).unwrap();
+
+        // Detached thread that stays alive until `sender` is dropped.
+        let (sender, receiver) = mpsc::channel::<()>();
+        let (linked_sender, linked_receiver) = mpsc::channel();
+        thread::spawn({
+            let control = control.clone();
+            move || {
+                control.with_data_mut(|acc| *acc += 5);
+                linked_sender.send(()).unwrap();
+                _ = receiver.recv();
+            }
+        });
+        linked_receiver.recv().unwrap();
+        let res = control.wait_quiescent(Duration::from_millis(10));
+        assert_eq_and_println(
+            &res.map_err(|e| e.live),
+            &Err(1),
+            "timed out with live thread",
+        );
+
+        drop(sender);
+        control.wait_quiescent(Duration::from_secs(5)).unwrap();
+        control.take_own_tl();
+        assert_eq_and_println(&control.take_acc(0), &15, "after quiescence");
+    }
+
+    #[test]
+    fn relink() {
+        use crate::tlm::{AccessError, ControlMismatchError};
+
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control1 = Control::<u64, u64>::new_additive(&U64_TL);
+        let control2 = Control::<u64, u64>::new_additive(&U64_TL);
+        control1.with_data_mut(|acc| *acc += 1);
+        assert_eq_and_println(
+            &control2.try_with_data_mut(|acc| *acc += 10),
+            &Err(AccessError::Mismatch(ControlMismatchError)),
+            "access through other control",
+        );
+        assert!(
+            U64_TL.with(|h| h.is_linked_to(&control1)),
+            "linked to control1"
+        );
+
+        control2.relink();
+        assert!(
+            U64_TL.with(|h| h.is_linked_to(&control2)),
+            "linked to control2"
+        );
+        control2.with_data_mut(|acc| *acc += 2);
+        assert_eq_and_println(
+            &control1.try_with_data_mut(|acc| *acc += 10),
+            &Err(AccessError::Mismatch(ControlMismatchError)),
+            "access through control1 after relink",
+        );
+
+        control1.take_own_tl();
+        control2.take_own_tl();
+        assert_eq_and_println(&control1.take_acc(0), &1, "control1 flushed on relink");
+        assert_eq_and_println(&control2.take_acc(0), &2, "control2");
+
+        control1.with_data_mut(|acc| *acc += 3);
+        assert!(
+            U64_TL.with(|h| h.is_linked_to(&control1)),
+            "relinked to control1 by with_data_mut"
+        );
+        control2.with_data(|_| ());
+        assert!(
+            U64_TL.with(|h| h.is_linked_to(&control2)),
+            "relinked to control2 by with_data"
+        );
+        assert_eq_and_println(
+            &control1.take_acc(0),
+            &3,
+            "control1 flushed on implicit relink",
+        );
+    }
+
+    #[test]
+    fn tls_destroyed() {
+        use crate::tlm::{AccessError, TlsDestroyedError};
+        use std::cell::RefCell;
+
+        /// Contributes from its destructor, which runs during thread teardown.
+        struct Late(Control<u64, u64>, mpsc::Sender<Result<(), AccessError>>);
+
+        impl Drop for Late {
+            fn drop(&mut self) {
+                let res = self.0.try_with_data_mut(|acc| *acc += 10);
+                self.1.send(res).unwrap();
+            }
+        }
+
+        thread_local! {
+            static U64_TL: Holder<u64, u64> = const { Holder::new() };
+            static LATE_TL: RefCell<Option<Late>> = const { RefCell::new(None) };
+        }
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn({
+            let control = control.clone();
+            move || {
+                // Thread-local variables are destroyed in the reverse order of their initialization, so `U64_TL`
+                // is destroyed before `LATE_TL`.
+                LATE_TL.with(|late| *late.borrow_mut() = Some(Late(control.clone(), sender)));
+                control.with_data_mut(|acc| *acc += 1);
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq_and_println(
+            &receiver.recv().unwrap(),
+            &Err(AccessError::TlsDestroyed(TlsDestroyedError)),
+            "contribution from destructor",
+        );
+        assert_eq_and_println(&control.take_acc(0), &1, "accumulated value");
+    }
+
+    #[test]
+    fn join_and_take() {
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        control.with_data_mut(|acc| *acc += 1);
+        let handles = (2..=4)
+            .map(|i| {
+                let control = control.clone();
+                thread::spawn(move || {
+                    control.with_data_mut(|acc| *acc += i);
+                    i
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let (acc, results) = control.join_and_take(handles, 0);
+        assert_eq_and_println(&acc, &10, "accumulated value");
+        let results = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq_and_println(&results, &vec![2, 3, 4], "thread results");
+        assert_eq_and_println(&control.clone_acc(), &0, "replacement");
+    }
+
+    #[test]
+    fn thread_registry() {
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        control.with_data_mut(|acc| *acc += 1);
+        let mut registry = ThreadRegistry::new(&control);
+        for i in 2..=4 {
+            registry.spawn(move |control| {
+                assert!(U64_TL.with(|h| h.is_linked_to(control)), "linked");
+                control.with_data_mut(|acc| *acc += i);
+            });
+        }
+        assert_eq_and_println(&registry.len(), &3, "spawned threads");
+        assert_eq_and_println(&registry.finish(0).unwrap(), &10, "finish");
+
+        let mut registry = ThreadRegistry::new(&control);
+        registry.spawn(|control| control.with_data_mut(|acc| *acc += 5));
+        registry.spawn(|_| panic!("expected panic in thread_registry test"));
+        assert!(registry.finish(0).is_err(), "panicked thread");
+        assert_eq_and_println(&control.take_acc(0), &5, "value kept after panic");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn spawn_blocking() {
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let hs = (1..=4)
+                .map(|i| {
+                    control.spawn_blocking(move |control| control.with_data_mut(|acc| *acc += i))
+                })
+                .collect::<Vec<_>>();
+            for h in hs {
+                h.await.unwrap();
+            }
+        });
+
+        // The threads of the blocking pool are still alive.
+        assert_eq_and_println(&control.take_acc(0), &10, "spawn_blocking");
+    }
+
+    #[cfg(feature = "threadpool")]
+    #[test]
+    fn execute() {
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        let pool = threadpool::ThreadPool::new(2);
+        for i in 1..=4 {
+            control.execute(&pool, move |control| control.with_data_mut(|acc| *acc += i));
+        }
+        pool.join();
+
+        // The worker threads are still alive.
+        assert_eq_and_println(&control.take_acc(0), &10, "execute");
+    }
+
+    #[test]
+    fn per_thread_accumulator() {
+        thread_local! {static PT_TL: Holder<Data, PerThread<Data>> = const { Holder::new() };}
+
+        let control = Control::from_accumulator(&PT_TL, HashMap::new);
+
+        let tid_own = thread::current().id();
+        control.with_data_mut(|data| data.insert(1, Foo("a".to_owned())));
+        let map_own = HashMap::from([(1, Foo("a".to_owned()))]);
+
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                control.with_data_mut(|data| data.insert(2, Foo("b".to_owned())));
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        let map_spawned = HashMap::from([(2, Foo("b".to_owned()))]);
+
+        control.take_own_tl();
+        let map = HashMap::from([(tid_own, map_own), (tid_spawned, map_spawned)]);
+        let acc = control.take_acc(PerThread::default());
+        assert_eq_and_println(&acc.into_inner(), &map, "per-thread accumulator");
+    }
+
+    #[cfg(thread_local_collect_off)]
+    #[test]
+    fn collect_off() {
+        use crate::tlm::{AccessError, DisabledError};
+
+        thread_local! {static U64_TL: Holder<u64, u64> = const { Holder::new() };}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        assert!(!control.is_enabled(), "disabled when compiled out");
+        let res = control.with_data_mut(|acc| {
+            *acc += 1;
+            *acc
+        });
+        assert_eq_and_println(&res, &1, "closure invoked on scratch data");
+        assert_eq_and_println(
+            &control.try_with_data_mut(|acc| *acc += 2),
+            &Err(AccessError::Disabled(DisabledError)),
+            "try_with_data_mut compiled out",
+        );
+        thread::spawn({
+            let control = control.clone();
+            move || control.with_data_mut(|acc| *acc += 3)
+        })
+        .join()
+        .unwrap();
+        control.take_own_tl();
+        assert_eq_and_println(&control.take_acc(0), &0, "contributions compiled out");
+    }
 }
@@ -1,12 +1,67 @@
 //! Modules that use the [`std::thread_local`] macro.
 
+#[cfg(any(feature = "joined", feature = "probed"))]
 pub(crate) mod common;
+#[cfg(all(feature = "restr", any(feature = "joined", feature = "probed")))]
 pub mod restr;
 
+#[cfg(feature = "probed")]
+pub use common::GuardedDataRead;
+#[cfg(any(feature = "joined", feature = "probed"))]
+pub use common::{GuardedData, New};
+
 #[doc(hidden)]
+#[cfg(feature = "probed")]
 pub(crate) mod tmap_d;
 
+#[cfg(all(feature = "probed", any(test, kani)))]
+#[allow(clippy::unwrap_used)]
+mod proofs;
+
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+mod seal;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+pub(crate) use seal::Seal;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+pub use seal::SealedError;
+
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+mod switch;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+pub use switch::DisabledError;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+pub(crate) use switch::Switch;
+
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+mod access;
+#[cfg(any(feature = "joined", feature = "probed", feature = "channeled"))]
+pub use access::{
+    AccessError, ControlMismatchError, ReentrantAccessError, ReregistrationError, TlsDestroyedError,
+};
+
+#[cfg(any(feature = "joined", feature = "probed"))]
+mod reentrancy;
+#[cfg(any(feature = "joined", feature = "probed"))]
+pub(crate) use reentrancy::AccessGuard;
+
+#[cfg(any(feature = "joined", feature = "probed"))]
+mod quiesce;
+#[cfg(all(feature = "async", any(feature = "joined", feature = "probed")))]
+pub use quiesce::DrainedFuture;
+#[cfg(any(feature = "joined", feature = "probed"))]
+pub(crate) use quiesce::Quiescence;
+#[cfg(any(feature = "joined", feature = "probed"))]
+pub use quiesce::QuiescenceTimeoutError;
+
+#[cfg(feature = "channeled")]
 pub mod channeled;
+#[cfg(feature = "channeled")]
+pub mod events;
+#[cfg(feature = "joined")]
 pub mod joined;
+#[cfg(feature = "probed")]
 pub mod probed;
+#[cfg(feature = "signal")]
+pub mod signal;
+#[cfg(feature = "joined")]
 pub mod simple_joined;
@@ -9,6 +9,9 @@
 //! - After all participating threads other than the thread responsible for collection/aggregation have
 //! terminated (joins are not necessary), a call to [`Control::take_tls`] followed by a call to one of the accumulator retrieval functions
 //! will return the final aggregated value.
+//! - The thread-local values are guarded by a [`Mutex`] so [`Control::probe_tls`] and a participating thread's
+//! access to its thread-local value block each other. When probing is frequent, [`ControlRw`] and [`HolderRw`]
+//! may be used instead, in which case the thread-local values are guarded by an [`RwLock`] and probes only take read locks.
 //!
 //! ## Usage pattern
 
@@ -23,95 +26,213 @@
 
 pub use crate::tlm::common::{ControlG, HolderG};
 
-use super::common::{CtrlParam, CtrlStateG, CtrlStateParam, HldrParam};
-use crate::tlm::{
-    common::{
-        CoreParam, Ctrl, GDataParam, NodeParam, SubStateParam, WithNode,
-        POISONED_GUARDED_DATA_MUTEX,
+use super::common::{CtrlParam, CtrlStateG, CtrlStateParam, GuardedDataRead, HldrParam};
+use crate::{
+    breakdown::Breakdown,
+    handles::ControlReader,
+    history,
+    instr::{self, OpInstr},
+    spill,
+    tlm::{
+        common::{CoreParam, Ctrl, GDataParam, NodeParam, SubStateParam, TlKey, WithNode},
+        tmap_d::TmapD,
     },
-    tmap_d::TmapD,
 };
 use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
     marker::PhantomData,
     mem::replace,
-    ops::DerefMut,
-    sync::{Arc, Mutex},
+    ops::{DerefMut, Sub},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Barrier, Mutex, RwLock,
+    },
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
 };
 
+/// Maximum interval between checks of the number of registered thread-local variables in
+/// [`ControlG::blocking_probe`].
+const BLOCKING_PROBE_MAX_POLL: Duration = Duration::from_millis(1);
+
+/// Policy applied by [`ControlG`] when a thread-local variable is registered under the key of a registered one,
+/// i.e., for the same thread and thread-local static, see [`ControlG::with_reregistration`].
+///
+/// [`ThreadId`]s are never reused within a process and a registration is removed when its thread-local variable is
+/// dropped, so such a collision means that a registration outlived its thread-local variable without being
+/// removed, e.g., in the child of a `fork` whose parent registered the forking thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reregistration {
+    /// The new registration replaces the existing one, whose thread-local value is no longer collected.
+    #[default]
+    Replace,
+    /// The new registration is rejected, and the existing one is kept. The contribution that attempted it returns
+    /// [`AccessError::Reregistration`](crate::tlm::AccessError::Reregistration) (or panics, if made with a
+    /// panicking method such as [`ControlG::with_data_mut`]).
+    Reject,
+}
+
+/// Accumulated value of an epoch, returned by [`ControlG::advance_epoch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epoch<U> {
+    /// Epoch number.
+    pub epoch: u64,
+    /// Value accumulated during the epoch.
+    pub acc: U,
+}
+
+/// Iterator over the values of the linked thread-local variables, returned by [`ControlG::drain_iter`].
+///
+/// Each value is taken, and replaced with the evaluation of the `make_data` function, when the iterator reaches it.
+/// Values not reached before the iterator is dropped are left in place.
+pub struct DrainIter<'a, T, U, G>
+where
+    T: 'static,
+    U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
+{
+    control: &'a ControlG<P<T, U, G>>,
+    nodes: std::vec::IntoIter<(ThreadId, G)>,
+}
+
+impl<T, U, G> Iterator for DrainIter<'_, T, U, G>
+where
+    T: 'static,
+    U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
+{
+    type Item = (ThreadId, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (tid, gdata) in self.nodes.by_ref() {
+            let mut guard = gdata.guard();
+            // The value is `None` if the thread-local variable was dropped after `nodes` was collected.
+            if guard.is_some() {
+                let data = guard.replace(self.control.make_data(tid));
+                self.control.invalidate_probe();
+                return data.map(|data| (tid, data));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.nodes.len()))
+    }
+}
+
+/// Indicates that [`ControlG::blocking_probe`] timed out before the required number of thread-local variables
+/// were registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeTimeoutError {
+    /// Number of thread-local variables registered when the timeout expired.
+    pub registered: usize,
+}
+
+impl Display for ProbeTimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "blocking_probe timed out with only {} registered thread-local variables.",
+            self.registered
+        )
+    }
+}
+
+impl Error for ProbeTimeoutError {}
+
 //=================
 // Core implementation based on common module
 
 /// Parameter bundle that enables specialization of the common generic structs for this module.
+///
+/// Type parameter `G` is the type of the guarded data shared between a [`HolderG`] and the corresponding node
+/// in the state of [`ControlG`]. It defaults to a [`Mutex`]-based wrapper; see [`ControlRw`] for an
+/// [`RwLock`]-based alternative.
 #[derive(Debug)]
-pub struct Probed<T, U> {
+pub struct Probed<T, U, G = Arc<Mutex<Option<T>>>> {
     _t: PhantomData<T>,
     _u: PhantomData<U>,
+    _g: PhantomData<G>,
 }
 
-type P<T, U> = Probed<T, U>;
+type P<T, U, G> = Probed<T, U, G>;
 
-impl<T, U> CoreParam for P<T, U> {
+impl<T, U, G> CoreParam for P<T, U, G> {
     type Dat = T;
     type Acc = U;
 }
 
 #[doc(hidden)]
+/// Node registered with [`ControlG`]'s state for each linked thread-local variable. It holds a non-owning reference
+/// to the guarded data, so entries whose holders have been dropped can be pruned by [`ControlG::take_tls`] and
+/// [`ControlG::probe_tls`] even if the drop notification is missed.
 #[derive(Debug)]
-pub struct Node<T> {
-    data: Arc<Mutex<Option<T>>>,
+pub struct Node<W> {
+    pub(crate) data: W,
+    /// Group tag set with [`ControlG::link_tagged`].
+    pub(crate) tag: Option<&'static str>,
 }
 
-impl<T, U> NodeParam for P<T, U>
+impl<T, U, G> NodeParam for P<T, U, G>
 where
     T: 'static,
     U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
 {
-    type Node = Node<T>;
-    type NodeFnArg = Control<T, U>;
+    type Node = Node<G::Weak>;
+    type NodeFnArg = ControlG<Self>;
 
     fn node_fn(arg: &Self::NodeFnArg) -> Self::Node {
         arg.tl.with(|h| Node {
-            data: h.data.clone(),
+            data: h.data.downgrade(),
+            tag: None,
         })
     }
 }
 
-impl<T, U> SubStateParam for P<T, U>
+impl<T, U, G> SubStateParam for P<T, U, G>
 where
     T: 'static,
     U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
 {
     type SubState = TmapD<Self>;
 }
 
-impl<T, U> GDataParam for P<T, U> {
-    type GData = Arc<Mutex<Option<T>>>;
+impl<T, U, G> GDataParam for P<T, U, G> {
+    type GData = G;
 }
 
-impl<T, U> CtrlParam for P<T, U>
+impl<T, U, G> CtrlParam for P<T, U, G>
 where
     T: 'static,
     U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
 {
-    type Ctrl = Control<T, U>;
+    type Ctrl = ControlG<Self>;
 }
 
-impl<T, U> HldrParam for P<T, U>
+impl<T, U, G> HldrParam for P<T, U, G>
 where
     T: 'static,
     U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
 {
-    type Hldr = Holder<T, U>;
+    type Hldr = HolderG<Self, WithNode>;
 }
 
-type CtrlState<T, U> = CtrlStateG<P<T, U>, TmapD<P<T, U>>>;
+type CtrlState<T, U, G> = CtrlStateG<P<T, U, G>, TmapD<P<T, U, G>>>;
 
-impl<T, U> CtrlStateParam for P<T, U>
+impl<T, U, G> CtrlStateParam for P<T, U, G>
 where
     T: 'static,
     U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
 {
-    type CtrlState = CtrlState<T, U>;
+    type CtrlState = CtrlState<T, U, G>;
 }
 
 /// Specialization of [`ControlG`] for this module.
@@ -119,81 +240,612 @@ where
 ///
 /// `T` is the type of the thread-local values and `U` is the type of the accumulated value.
 /// The data values are held in thread-locals of type [`Holder<T, U>`].
-pub type Control<T, U> = ControlG<P<T, U>>;
+pub type Control<T, U> = ControlG<P<T, U, Arc<Mutex<Option<T>>>>>;
 
-impl<T, U> Control<T, U>
+/// Variant of [`Control`] whose thread-local values are guarded by an [`RwLock`] instead of a [`Mutex`].
+/// [`ControlG::probe_tls`] only takes read locks on the thread-local values, so frequent probing interferes less
+/// with the participating threads' own access to their thread-local values.
+///
+/// `T` is the type of the thread-local values and `U` is the type of the accumulated value.
+/// The data values are held in thread-locals of type [`HolderRw<T, U>`].
+pub type ControlRw<T, U> = ControlG<P<T, U, Arc<RwLock<Option<T>>>>>;
+
+/// Variant of [`Control`] whose thread-local values are guarded by the data wrapper `G`, e.g.,
+/// `Arc<parking_lot::Mutex<Option<T>>>` with the **"parking_lot"** feature, or a user-defined wrapper that implements
+/// [`GuardedDataRead`]. [`Control`] and [`ControlRw`] are specializations of this type.
+///
+/// `T` is the type of the thread-local values and `U` is the type of the accumulated value.
+/// The data values are held in thread-locals of type [`HolderWith<T, U, G>`].
+///
+/// ```rust
+/// use std::sync::{Arc, RwLock};
+/// use thread_local_collect::tlm::probed::{ControlWith, HolderWith};
+///
+/// type Guarded = Arc<RwLock<Option<u64>>>;
+///
+/// thread_local! {
+///     static MY_TL: HolderWith<u64, u64, Guarded> = HolderWith::new();
+/// }
+///
+/// let control = ControlWith::<u64, u64, Guarded>::new_additive(&MY_TL);
+/// control.with_data_mut(|data| *data += 1);
+/// assert_eq!(control.probe_tls(), 1);
+/// ```
+pub type ControlWith<T, U, G> = ControlG<P<T, U, G>>;
+
+impl<T, U, G> ControlG<P<T, U, G>>
 where
     T: 'static,
     U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
 {
+    /// Sets the policy applied when a thread-local variable is registered under the key of a registered one (see
+    /// [`Reregistration`]). The default is [`Reregistration::Replace`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn with_reregistration(self, policy: Reregistration) -> Self {
+        self.lock().s.reregistration = policy;
+        self
+    }
+
+    /// Enables the caching of the result of [`Self::probe_tls`], so that probing again when nothing has changed
+    /// returns a clone of the cached result without visiting the thread-local variables, e.g., for dashboards that
+    /// poll at high frequency. The cache keeps a clone of the last probed value, and contributions to `self` and its
+    /// clones mark it as stale with an atomic flag, so it should only be enabled when probes are frequent.
+    pub fn with_probe_cache(mut self) -> Self {
+        self.extras_mut().probe_stale = Some(Arc::new(AtomicBool::new(true)));
+        self
+    }
+
     /// Takes the values of any remaining linked thread-local-variables and aggregates those values
     /// with this object's accumulator, replacing those values with the evaluation of the `make_data` function
-    /// passed to [`Control::new`].
+    /// passed to [`Control::new`] (or of the function set with [`Control::with_tl_init`]).
     ///
     /// This object's accumulated value reflects the aggregation of all participating thread-local values when this
     /// method is called from the thread responsible for collection/aggregation after the other threads have terminated.
     ///
+    /// Registry entries whose thread-local variables no longer exist are removed.
+    ///
     /// # Panics
     /// - If `self`'s mutex is poisoned.
-    /// - If [`Holder`] guarded data mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
     pub fn take_tls(&self) {
+        let mut instr = OpInstr::start("take_tls", self.extra(|e| &e.diag));
         let mut guard = self.lock();
-        // Need explicit deref_mut to avoid compilation error in for loop.
-        let state = guard.deref_mut();
-        for (tid, node) in state.s.tmap.iter() {
-            log::trace!("executing `take_tls` for key={:?}", tid);
-            let mut data_guard = node.data.lock().expect(POISONED_GUARDED_DATA_MUTEX);
-            let data = replace(data_guard.deref_mut(), Some(self.make_data()));
+        self.take_tls_locked(guard.deref_mut(), &mut instr, |_, _| true);
+    }
+
+    /// Links the current thread's thread-local variable to `self`, as its first contribution would, and tags it
+    /// with the group `tag`, e.g., `"io"` or `"compute"`, so that the thread pools of an application can be
+    /// compared with [`Self::take_group`], [`Self::probe_group`], and [`Self::drain_per_group`] rather than
+    /// individual threads. Calling it again replaces the tag. A thread-local variable that is relinked (see
+    /// [`ControlG::relink`]) must be tagged again.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - Under the same conditions as [`ControlG::with_data`].
+    pub fn link_tagged(&self, tag: &'static str) {
+        self.with_data(|_| ());
+        let key = TlKey::new(thread::current().id(), self.tl);
+        if let Some(node) = self.lock_unchanged().s.tmap.get_mut(&key) {
+            node.tag = Some(tag);
+        }
+    }
+
+    /// Returns the group tag of thread `tid`, if it is registered with `self` and was tagged with
+    /// [`Self::link_tagged`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn thread_tag(&self, tid: ThreadId) -> Option<&'static str> {
+        self.lock_unchanged()
+            .s
+            .tmap
+            .get(&TlKey::new(tid, self.tl))?
+            .tag
+    }
+
+    /// Same as [`Self::take_tls`], except that only the values of the thread-local variables tagged with `tag`
+    /// (see [`Self::link_tagged`]) are taken and aggregated, leaving the values of the other threads untouched.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn take_group(&self, tag: &str) {
+        let mut instr = OpInstr::start("take_group", self.extra(|e| &e.diag));
+        let mut guard = self.lock();
+        self.take_tls_locked(guard.deref_mut(), &mut instr, |_, node_tag| {
+            node_tag == Some(tag)
+        });
+    }
+
+    /// Same as [`Self::take_tls`], except that only the values of the thread-local variables of the threads in
+    /// `tids` are taken and aggregated, leaving the values of the other threads untouched, e.g., when a subset of
+    /// the worker threads is being recycled while the others keep running. Threads in `tids` that have no linked
+    /// thread-local variable are ignored.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn take_threads(&self, tids: &[ThreadId]) {
+        let mut instr = OpInstr::start("take_threads", self.extra(|e| &e.diag));
+        let mut guard = self.lock();
+        self.take_tls_locked(guard.deref_mut(), &mut instr, |tid, _| tids.contains(&tid));
+    }
+
+    /// Replaces the value of the linked thread-local variable of thread `tid` with the evaluation of the `make_data`
+    /// function passed to [`Control::new`] (or of the function set with [`Control::with_tl_init`]), discarding the
+    /// current value without aggregating it, e.g., when a worker's partial data is known to be invalid after a
+    /// recoverable error on that worker. Returns `false` if `tid` has no linked thread-local variable.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn clear_thread(&self, tid: ThreadId) -> bool {
+        let state = self.lock();
+        let mut cleared = false;
+        for (key, node) in state.s.tmap.iter() {
+            if key.tid != tid {
+                continue;
+            }
+            if let Some(gdata) = G::upgrade(&node.data) {
+                gdata.guard().replace(self.make_data(tid));
+                cleared = true;
+            }
+        }
+        cleared
+    }
+
+    /// Takes the values of the linked thread-local variables, replacing them with the evaluation of the `make_data`
+    /// function passed to [`Control::new`] (or of the function set with [`Control::with_tl_init`]), and returns them
+    /// per thread instead of aggregating them with the accumulated value, e.g., to report a per-worker breakdown of
+    /// the data collected since the last drain. The accumulated value is left untouched.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn drain_per_thread(&self) -> Breakdown<T> {
+        let state = self.lock();
+        let values = state
+            .s
+            .tmap
+            .iter()
+            .filter_map(|(key, node)| {
+                let gdata = G::upgrade(&node.data)?;
+                let data = gdata.guard().replace(self.make_data(key.tid))?;
+                Some((key.tid, data))
+            })
+            .collect();
+        Breakdown::new(values)
+    }
+
+    /// Same as [`Self::drain_per_thread`], except that the per-thread values are taken lazily, as the returned
+    /// iterator is advanced, so that a large number of large values can be streamed, e.g., to a writer, without
+    /// materializing all of them at once. `self`'s lock is only held while the iterator is created.
+    ///
+    /// Threads that link to `self` after the iterator is created are not visited, and values contributed by the
+    /// visited threads before they are reached are included.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned, when the iterator is advanced.
+    pub fn drain_iter(&self) -> DrainIter<'_, T, U, G> {
+        let state = self.lock();
+        let nodes = state
+            .s
+            .tmap
+            .iter()
+            .filter_map(|(key, node)| Some((key.tid, G::upgrade(&node.data)?)))
+            .collect::<Vec<_>>();
+        DrainIter {
+            control: self,
+            nodes: nodes.into_iter(),
+        }
+    }
+
+    /// Same as [`Self::drain_per_thread`], except that the per-thread values are grouped by the tags of their
+    /// thread-local variables (see [`Self::link_tagged`]), with untagged ones under `None`.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn drain_per_group(&self) -> HashMap<Option<&'static str>, Breakdown<T>> {
+        let state = self.lock();
+        let mut groups = HashMap::<_, Vec<_>>::new();
+        for (key, node) in state.s.tmap.iter() {
+            let Some(gdata) = G::upgrade(&node.data) else {
+                continue;
+            };
+            let data = gdata.guard().replace(self.make_data(key.tid));
             if let Some(data) = data {
-                log::trace!("executed `take` -- `take_tls` for key={:?}", tid);
-                log::trace!("executing `op` -- `take_tls` for key={:?}", tid);
-                (self.op)(data, &mut state.acc, *tid);
+                groups.entry(node.tag).or_default().push((key.tid, data));
             }
         }
+        groups
+            .into_iter()
+            .map(|(tag, values)| (tag, Breakdown::new(values)))
+            .collect()
+    }
+
+    /// Takes the values of the linked thread-local variables selected by `select`, given their thread and group
+    /// tag, and aggregates them with the accumulated value, while the caller holds `self`'s state lock.
+    fn take_tls_locked(
+        &self,
+        state: &mut CtrlState<T, U, G>,
+        instr: &mut OpInstr,
+        select: impl Fn(ThreadId, Option<&str>) -> bool,
+    ) {
+        let acc = &mut state.acc;
+        state.s.tmap.retain(|key, node| {
+            let Some(gdata) = G::upgrade(&node.data) else {
+                return false;
+            };
+            if !select(key.tid, node.tag) {
+                return true;
+            }
+            let mut data_guard = gdata.guard();
+            let data = data_guard.replace(self.make_data(key.tid));
+            if let Some(data) = data {
+                instr.item(key.tid);
+                (self.op)(data, acc, key.tid);
+                spill::check(self.extra(|e| &e.spill), acc);
+            }
+            true
+        });
     }
 
     /// Collects the values of any remaining linked thread-local-variables, without changing those values,
     /// aggregates those values with a clone of this object's accumulator, and returns the aggregate
     /// value. This object's accumulator remains unchanged.
     ///
+    /// If the probe cache is enabled with [`Self::with_probe_cache`] and no thread has contributed and no operation
+    /// other than a read-only accessor, e.g., [`ControlG::acc`] or [`Self::registered_count`], has been performed on
+    /// `self` or its clones since the last probe, a clone of the last result is returned without visiting the
+    /// thread-local variables, so frequent
+    /// polling is cheap while the participating threads are idle.
+    ///
+    /// Registry entries whose thread-local variables no longer exist are removed.
+    ///
     /// # Panics
     /// - If `self`'s mutex is poisoned.
-    /// - If [`Holder`] guarded data mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
     pub fn probe_tls(&self) -> U
     where
         T: Clone,
         U: Clone,
     {
-        let state = self.lock();
-        let mut acc_clone = state.acc.clone();
-        for (tid, node) in state.s.tmap.iter() {
-            log::trace!("executing `probe_tls` for key={:?}", tid);
-            let data = node.data.lock().expect(POISONED_GUARDED_DATA_MUTEX).clone();
-            if let Some(data) = data {
-                log::trace!("executing `op` -- `probe_tls` for key={:?}", tid);
-                (self.op)(data, &mut acc_clone, *tid);
+        let mut instr = OpInstr::start("probe_tls", self.extra(|e| &e.diag));
+        let mut state = self.lock_unchanged();
+        self.probe_locked(&mut state, &mut instr)
+    }
+
+    /// Returns the change of the probed value (see [`Self::probe_tls`]) since the previous call of this method or
+    /// [`Self::probe_delta_with`] on `self` or its clones, or the probed value itself on the first call, e.g., for
+    /// exporters that push increments to cumulative-counter systems.
+    ///
+    /// The probed value includes the accumulated value, so the accumulated value should not be taken between calls.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn probe_delta(&self) -> U
+    where
+        T: Clone,
+        U: Clone + Sub<Output = U>,
+    {
+        self.probe_delta_with(|current, previous| current.clone() - previous.clone())
+    }
+
+    /// Same as [`Self::probe_delta`], with the change computed by `diff` from the current and previous probed
+    /// values, for accumulated values that do not implement [`Sub`], e.g., maps.
+    ///
+    /// `diff` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn probe_delta_with(&self, diff: impl FnOnce(&U, &U) -> U) -> U
+    where
+        T: Clone,
+        U: Clone,
+    {
+        let mut instr = OpInstr::start("probe_delta", self.extra(|e| &e.diag));
+        let mut state = self.lock_unchanged();
+        let current = self.probe_locked(&mut state, &mut instr);
+        let delta = match &state.s.delta_base {
+            Some(previous) => diff(&current, previous),
+            None => current.clone(),
+        };
+        state.s.delta_base = Some(current);
+        delta
+    }
+
+    /// Implements [`Self::probe_tls`] given `self`'s locked state.
+    fn probe_locked(&self, state: &mut CtrlState<T, U, G>, instr: &mut OpInstr) -> U
+    where
+        T: Clone,
+        U: Clone,
+    {
+        if let Some(stale) = self.extra(|e| &e.probe_stale) {
+            if !stale.swap(false, Ordering::SeqCst) {
+                if let Some(acc) = &state.s.probe_cache {
+                    return acc.clone();
+                }
             }
         }
+        let mut acc_clone = state.acc.clone();
+        instr::probing(|| {
+            state.s.tmap.retain(|key, node| {
+                let Some(gdata) = G::upgrade(&node.data) else {
+                    return false;
+                };
+                let data = gdata.read_guard().clone();
+                if let Some(data) = data {
+                    instr.item(key.tid);
+                    (self.op)(data, &mut acc_clone, key.tid);
+                }
+                true
+            })
+        });
+        if self.extra(|e| &e.probe_stale).is_some() {
+            state.s.probe_cache = Some(acc_clone.clone());
+        }
         acc_clone
     }
+
+    /// Collects the values of the linked thread-local variables tagged with `tag` (see [`Self::link_tagged`]),
+    /// without changing those values, and returns their aggregation with `acc_base`. Unlike [`Self::probe_tls`],
+    /// the accumulated value is not included, since it does not retain the groups of the values aggregated with it.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn probe_group(&self, tag: &str, acc_base: U) -> U
+    where
+        T: Clone,
+    {
+        let mut instr = OpInstr::start("probe_group", self.extra(|e| &e.diag));
+        let state = self.lock_unchanged();
+        let mut acc = acc_base;
+        instr::probing(|| {
+            for (key, node) in state.s.tmap.iter() {
+                if node.tag != Some(tag) {
+                    continue;
+                }
+                let Some(gdata) = G::upgrade(&node.data) else {
+                    continue;
+                };
+                let data = gdata.read_guard().clone();
+                if let Some(data) = data {
+                    instr.item(key.tid);
+                    (self.op)(data, &mut acc, key.tid);
+                }
+            }
+        });
+        acc
+    }
+
+    /// Ends the current epoch and starts the next one. The values of the linked thread-local variables are taken
+    /// and aggregated with the accumulated value, which is returned tagged with the number of the epoch that
+    /// ended (and recorded in [`ControlG::history`], if enabled) and replaced with `replacement`. Epochs are
+    /// numbered consecutively starting at 0.
+    ///
+    /// All of the above is done atomically with respect to other operations on `self`, so each thread-local
+    /// contribution is included in exactly one epoch. This is suited, for example, to the collection of per-frame
+    /// statistics from worker threads in game engines and simulations.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn advance_epoch(&self, replacement: U) -> Epoch<U> {
+        let mut instr = OpInstr::start("advance_epoch", self.extra(|e| &e.diag));
+        let mut guard = self.lock();
+        let state = guard.deref_mut();
+        self.take_tls_locked(state, &mut instr, |_, _| true);
+        let epoch = state.s.epoch;
+        state.s.epoch += 1;
+        let acc = replace(&mut state.acc, replacement);
+        history::record(self.extra(|e| &e.history), &acc);
+        Epoch { epoch, acc }
+    }
+
+    /// Finalizes `self`, taking the values of the linked thread-local variables as with [`Self::take_tls`] and
+    /// returning the accumulated value, which is replaced with `replacement`. `self` and its clones are then sealed:
+    /// contributions with [`ControlG::with_data_mut`] panic and those with [`ControlG::try_with_data_mut`] return
+    /// [`AccessError::Sealed`](crate::tlm::AccessError::Sealed), so that late contributions are caught instead of
+    /// being silently aggregated with a stale accumulated value. A contribution concurrent with this call is either
+    /// included in the returned value or rejected.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn finalize(&self, replacement: U) -> U {
+        self.seal_and_take_acc(replacement, || self.take_tls())
+    }
+
+    /// Returns the number of the current epoch. See [`Self::advance_epoch`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn current_epoch(&self) -> u64 {
+        self.lock_unchanged().s.epoch
+    }
+
+    /// Coordinates with the participating threads through `barrier` to take a consistent cut of their
+    /// contributions, for phase-based programs. `barrier` must be shared by the participating threads and the thread
+    /// calling this method, each of which must call [`Barrier::wait`] twice: once when it has finished contributing
+    /// to the current phase, and again before it starts contributing to the next phase.
+    ///
+    /// This method waits at `barrier` until all participating threads have finished the current phase,
+    /// takes the accumulated value as with [`Self::advance_epoch`] (which includes starting a new
+    /// epoch), and then waits at `barrier` again to release the participating threads. The taken value is returned.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    ///
+    /// If this method panics, the participating threads will block at their second wait on `barrier`.
+    pub fn take_at_barrier(&self, barrier: &Barrier, replacement: U) -> U {
+        barrier.wait();
+        let acc = self.advance_epoch(replacement).acc;
+        barrier.wait();
+        acc
+    }
+
+    /// Returns the number of thread-local variables that have registered with `self` since its instantiation,
+    /// including those whose threads have since terminated. When a single thread-local static is linked to `self`,
+    /// this is the number of threads that have contributed data.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn registered_count(&self) -> usize {
+        self.lock_unchanged().s.registered
+    }
+
+    /// Returns whether thread `tid` is currently registered with `self`, i.e., it has contributed data and its
+    /// thread-local variable has not yet been dropped.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn contains_thread(&self, tid: ThreadId) -> bool {
+        self.lock_unchanged()
+            .s
+            .tmap
+            .contains_key(&TlKey::new(tid, self.tl))
+    }
+
+    /// Blocks until at least `min_threads` thread-local variables have registered with `self` (see
+    /// [`Self::registered_count`]) and then returns the result of [`Self::probe_tls`]. This is useful, for example,
+    /// in tests and benchmarks that need to wait for worker threads to start.
+    ///
+    /// # Errors
+    /// Returns an error if fewer than `min_threads` thread-local variables have registered when `timeout` expires.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn blocking_probe(
+        &self,
+        min_threads: usize,
+        timeout: Duration,
+    ) -> Result<U, ProbeTimeoutError>
+    where
+        T: Clone,
+        U: Clone,
+    {
+        let deadline = Instant::now() + timeout;
+        let mut poll = Duration::from_micros(10);
+        loop {
+            let registered = self.registered_count();
+            if registered >= min_threads {
+                return Ok(self.probe_tls());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(ProbeTimeoutError { registered });
+            }
+            thread::sleep(poll.min(deadline - now));
+            poll = (poll * 2).min(BLOCKING_PROBE_MAX_POLL);
+        }
+    }
+}
+
+impl<T, U, G> ControlReader<ControlG<P<T, U, G>>>
+where
+    T: 'static,
+    U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
+{
+    /// Returns a clone of the current aggregation of the linked thread-local values with the accumulated value.
+    /// See [`ControlG::probe_tls`].
+    ///
+    /// # Panics
+    /// - If the control object's mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn probe_tls(&self) -> U
+    where
+        T: Clone,
+        U: Clone,
+    {
+        self.0.probe_tls()
+    }
+
+    /// Blocks until at least `min_threads` thread-local variables have registered with the control object and then
+    /// returns a clone of the current aggregation. See [`ControlG::blocking_probe`].
+    ///
+    /// # Errors
+    /// Returns an error if fewer than `min_threads` thread-local variables have registered when `timeout` expires.
+    ///
+    /// # Panics
+    /// - If the control object's mutex is poisoned.
+    /// - If [`Holder`] guarded data lock is poisoned.
+    pub fn blocking_probe(
+        &self,
+        min_threads: usize,
+        timeout: Duration,
+    ) -> Result<U, ProbeTimeoutError>
+    where
+        T: Clone,
+        U: Clone,
+    {
+        self.0.blocking_probe(min_threads, timeout)
+    }
 }
 
 /// Specialization of [`HolderG`] for this module.
 /// Holds thread-local data of type `T` and a smart pointer to a [`Control<T, U>`], enabling the linkage of
 /// the held data with the control object.
-pub type Holder<T, U> = HolderG<P<T, U>, WithNode>;
+pub type Holder<T, U> = HolderG<P<T, U, Arc<Mutex<Option<T>>>>, WithNode>;
+
+/// Variant of [`Holder`] for use with [`ControlRw`].
+/// Holds thread-local data of type `T` and a smart pointer to a [`ControlRw<T, U>`], enabling the linkage of
+/// the held data with the control object.
+pub type HolderRw<T, U> = HolderG<P<T, U, Arc<RwLock<Option<T>>>>, WithNode>;
+
+/// Variant of [`Holder`] for use with [`ControlWith`], whose thread-local values are guarded by the data wrapper `G`.
+/// Holds thread-local data of type `T` and a smart pointer to a [`ControlWith<T, U, G>`], enabling the linkage of
+/// the held data with the control object.
+pub type HolderWith<T, U, G> = HolderG<P<T, U, G>, WithNode>;
+
+impl<T, U, G> HolderWith<T, U, G>
+where
+    T: 'static,
+    U: 'static,
+    G: GuardedDataRead<T, Arg = Option<T>> + 'static,
+{
+    /// Instantiates a holder object.
+    ///
+    /// Unlike the holders of the [`joined`](crate::tlm::joined) and [`simple_joined`](crate::tlm::simple_joined)
+    /// modules, this is not a `const fn`, as the held data is shared with the control object through a
+    /// heap-allocated guard of type `G`.
+    pub fn new() -> Self {
+        Self::with_data(G::new(None))
+    }
+}
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use super::{Control, Holder};
-    use crate::dev_support::{assert_eq_and_println, ThreadGater};
+    use super::{
+        Control, ControlRw, Epoch, Holder, HolderRw, Node, ProbeTimeoutError, Reregistration,
+    };
+    use crate::{
+        dev_support::{assert_eq_and_println, ThreadGater},
+        diag::DiagEvent,
+        tlm::{common::TlKey, AccessError, DisabledError, ReregistrationError, SealedError},
+    };
     use std::{
         collections::HashMap,
         fmt::Debug,
         iter::once,
-        sync::Mutex,
+        panic::{self, AssertUnwindSafe},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            mpsc, Arc, Barrier, Mutex,
+        },
         thread::{self, ThreadId},
+        time::Duration,
     };
 
     #[derive(Debug, Clone, PartialEq)]
@@ -205,6 +857,9 @@ mod tests {
 
     thread_local! {
         static MY_TL: Holder<Data, AccValue> = Holder::new();
+        static MY_TL_RW: HolderRw<Data, AccValue> = HolderRw::new();
+        static COUNT_TL: Holder<u64, u64> = Holder::new();
+        static MY_TL2: Holder<Data, AccValue> = Holder::new();
     }
 
     fn insert_tl_entry(k: i32, v: Foo, control: &Control<Data, AccValue>) {
@@ -613,4 +1268,1157 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn diagnostics_hook() {
+        let events = Arc::new(Mutex::new(Vec::<DiagEvent>::new()));
+        let control = Control::new(&MY_TL, HashMap::new(), HashMap::new, op).with_diagnostics({
+            let events = events.clone();
+            move |e| events.lock().unwrap().push(e.clone())
+        });
+
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                insert_tl_entry(1, Foo("a".to_owned()), &control);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        insert_tl_entry(1, Foo("b".to_owned()), &control);
+        control.take_tls();
+
+        let tid_own = thread::current().id();
+        let events = events.lock().unwrap().clone();
+        println!("events={events:?}");
+
+        let expected_head = [
+            DiagEvent::Point {
+                op: "register_node",
+                tid: tid_spawned,
+            },
+            DiagEvent::Point {
+                op: "tl_data_dropped",
+                tid: tid_spawned,
+            },
+            DiagEvent::Point {
+                op: "register_node",
+                tid: tid_own,
+            },
+            DiagEvent::Item {
+                op: "take_tls",
+                tid: Some(tid_own),
+            },
+        ];
+        assert_eq!(events[..4], expected_head);
+        match &events[4] {
+            DiagEvent::Completed { op, count, .. } => {
+                assert_eq!((*op, *count), ("take_tls", 1));
+            }
+            e => panic!("unexpected event {e:?}"),
+        }
+    }
+
+    #[test]
+    fn probed_rw_own_thread_and_explicit_join() {
+        let control = ControlRw::new(&MY_TL_RW, HashMap::new(), HashMap::new, op);
+
+        let tid_own = thread::current().id();
+        let map_own = HashMap::from([(1, Foo("a".to_owned()))]);
+        control.with_data_mut(|data| data.insert(1, Foo("a".to_owned())));
+
+        let spawned_thread_gater = ThreadGater::new("spawned");
+        let main_thread_gater = ThreadGater::new("main");
+
+        let map_spawned = HashMap::from([(2, Foo("b".to_owned()))]);
+
+        let tid_spawned = thread::scope(|s| {
+            let h = s.spawn(|| {
+                control.with_data_mut(|data| data.insert(2, Foo("b".to_owned())));
+                spawned_thread_gater.open(0);
+                main_thread_gater.wait_for(0);
+                thread::current().id()
+            });
+
+            spawned_thread_gater.wait_for(0);
+            let acc = control.probe_tls();
+            let tid_spawned = h.thread().id();
+            let map = HashMap::from([
+                (tid_own, map_own.clone()),
+                (tid_spawned, map_spawned.clone()),
+            ]);
+            assert_eq_and_println(&acc, &map, "probe_tls with spawned thread active");
+            main_thread_gater.open(0);
+
+            h.join().unwrap()
+        });
+
+        control.take_tls();
+        let map = HashMap::from([(tid_own, map_own), (tid_spawned, map_spawned)]);
+        let acc = control.take_acc(HashMap::new());
+        assert_eq_and_println(&acc, &map, "take_acc after take_tls");
+    }
+
+    /// Threads link, contribute, and terminate concurrently with `take_tls` and `probe_tls` calls. Every
+    /// contribution must be accumulated exactly once.
+    #[test]
+    fn registration_vs_take_stress() {
+        const NWAVES: u64 = 20;
+        const NTHREADS: u64 = 8;
+        const NINCS: u64 = 200;
+
+        let control = Control::new(&COUNT_TL, 0, || 0, |data, acc: &mut u64, _| *acc += data);
+        let done = AtomicBool::new(false);
+
+        thread::scope(|s| {
+            let taker = s.spawn(|| {
+                let mut last_probe = 0;
+                while !done.load(Ordering::Relaxed) {
+                    control.take_tls();
+                    let probe = control.probe_tls();
+                    assert!(probe >= last_probe, "probes must be monotonic");
+                    assert!(
+                        probe <= NWAVES * NTHREADS * NINCS,
+                        "probe must not overcount"
+                    );
+                    last_probe = probe;
+                }
+            });
+
+            for _ in 0..NWAVES {
+                let hs = (0..NTHREADS)
+                    .map(|_| {
+                        s.spawn(|| {
+                            for _ in 0..NINCS {
+                                control.with_data_mut(|data| *data += 1);
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                hs.into_iter().for_each(|h| h.join().unwrap());
+            }
+
+            done.store(true, Ordering::Relaxed);
+            taker.join().unwrap();
+        });
+
+        control.take_tls();
+        let acc = control.take_acc(0);
+        assert_eq!(
+            acc,
+            NWAVES * NTHREADS * NINCS,
+            "every contribution counted once"
+        );
+    }
+
+    #[test]
+    fn multiple_tls() {
+        let control = Control::new(&MY_TL, HashMap::new(), HashMap::new, op);
+        let control2 = control.for_tl(&MY_TL2);
+
+        let tid_own = thread::current().id();
+        insert_tl_entry(1, Foo("a".to_owned()), &control);
+        insert_tl_entry(2, Foo("b".to_owned()), &control2);
+        let map_own = HashMap::from([(1, Foo("a".to_owned())), (2, Foo("b".to_owned()))]);
+
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                insert_tl_entry(11, Foo("x".to_owned()), &control);
+                insert_tl_entry(22, Foo("y".to_owned()), &control2);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        let map_spawned = HashMap::from([(11, Foo("x".to_owned())), (22, Foo("y".to_owned()))]);
+
+        let map = HashMap::from([(tid_own, map_own), (tid_spawned, map_spawned)]);
+
+        let acc = control2.probe_tls();
+        assert_eq_and_println(&acc, &map, "probe_tls with multiple thread-local statics");
+
+        control.take_tls();
+        let acc = control2.take_acc(HashMap::new());
+        assert_eq_and_println(&acc, &map, "take_acc with multiple thread-local statics");
+    }
+
+    #[test]
+    fn reader() {
+        let control = Control::new(&MY_TL, HashMap::new(), HashMap::new, op);
+        let reader = control.reader();
+
+        let tid_own = thread::current().id();
+        insert_tl_entry(1, Foo("a".to_owned()), &control);
+        let map = HashMap::from([(tid_own, HashMap::from([(1, Foo("a".to_owned()))]))]);
+
+        let probed = thread::scope(|s| s.spawn(|| reader.clone().probe_tls()).join().unwrap());
+        assert_eq_and_println(&probed, &map, "reader probe_tls");
+        assert!(reader.acc().is_empty(), "reader acc before take_tls");
+
+        control.take_tls();
+        let acc = reader.clone_acc();
+        assert_eq_and_println(&acc, &map, "reader clone_acc after take_tls");
+        reader.with_acc(|acc| assert_eq!(acc.len(), 1));
+    }
+
+    #[test]
+    fn new_additive() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+
+        control.with_data_mut(|data| *data += 1);
+        thread::scope(|s| {
+            s.spawn(|| control.with_data_mut(|data| *data += 2));
+        });
+
+        assert_eq_and_println(&control.probe_tls(), &3, "probe_tls new_additive");
+        control.take_tls();
+        assert_eq_and_println(&control.clone_acc(), &3, "take_tls new_additive");
+    }
+
+    #[test]
+    fn from_extend() {
+        thread_local! {
+            static EXTEND_TL: Holder<u64, Vec<(ThreadId, u64)>> = Holder::new();
+        }
+
+        let control = Control::<u64, Vec<(ThreadId, u64)>>::from_extend(&EXTEND_TL);
+
+        control.with_data_mut(|data| *data += 1);
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                control.with_data_mut(|data| *data += 2);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+
+        control.take_tls();
+        let mut acc = control.take_acc(Vec::new());
+        acc.sort_by_key(|&(_, data)| data);
+        let expected = vec![(thread::current().id(), 1), (tid_spawned, 2)];
+        assert_eq_and_println(&acc, &expected, "take_tls from_extend");
+    }
+
+    #[test]
+    fn with_filter() {
+        let main_tid = thread::current().id();
+        let control =
+            Control::<u64, u64>::new_additive(&COUNT_TL).with_filter(move |_, tid| tid != main_tid);
+
+        control.with_data_mut(|data| *data += 1);
+        thread::scope(|s| {
+            s.spawn(|| control.with_data_mut(|data| *data += 2));
+            s.spawn(|| control.with_data_mut(|data| *data += 4));
+        });
+
+        assert_eq_and_println(&control.probe_tls(), &6, "probe_tls with_filter");
+        control.take_tls();
+        assert_eq_and_println(&control.clone_acc(), &6, "take_tls with_filter");
+    }
+
+    #[test]
+    fn probe_delta() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+
+        control.with_data_mut(|data| *data += 1);
+        assert_eq_and_println(&control.probe_delta(), &1, "first probe_delta");
+        assert_eq_and_println(&control.probe_delta(), &0, "unchanged probe_delta");
+
+        thread::scope(|s| {
+            s.spawn(|| control.with_data_mut(|data| *data += 10));
+        });
+        control.with_data_mut(|data| *data += 100);
+        assert_eq_and_println(&control.probe_tls(), &111, "probe_tls");
+        assert_eq_and_println(&control.probe_delta(), &110, "probe_delta after probe_tls");
+
+        let reader = control.clone();
+        control.with_data_mut(|data| *data += 1000);
+        let delta = reader.probe_delta_with(|current, previous| 2 * (current - previous));
+        assert_eq_and_println(&delta, &2000, "probe_delta_with on clone");
+    }
+
+    #[test]
+    fn instrumented() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL).instrumented({
+            let records = records.clone();
+            move |record| records.lock().unwrap().push(record.clone())
+        });
+
+        control.with_data_mut(|data| *data += 1);
+        control.link_tagged("io");
+        let tid_spawned = thread::scope(|s| {
+            s.spawn(|| {
+                control.with_data_mut(|data| *data += 20);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        assert_eq_and_println(&control.probe_tls(), &21, "probe_tls not logged");
+        assert_eq_and_println(&control.probe_group("io", 0), &1, "probe_group not logged");
+        control.take_tls();
+        assert_eq_and_println(&control.clone_acc(), &21, "take_tls instrumented");
+
+        let logged = records
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| (r.seq, r.tid, r.summary.clone()))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (0, tid_spawned, "20".to_owned()),
+            (1, thread::current().id(), "1".to_owned()),
+        ];
+        assert_eq_and_println(&logged, &expected, "logged fold sequence");
+    }
+
+    #[test]
+    fn with_spill() {
+        let spilled = Arc::new(Mutex::new(Vec::new()));
+        let control =
+            Control::<u64, u64>::new_additive(&COUNT_TL).with_spill(5, |acc| *acc as usize, {
+                let spilled = spilled.clone();
+                move |acc| spilled.lock().unwrap().push(acc)
+            });
+
+        thread::scope(|s| {
+            for i in 1..=4 {
+                let control = &control;
+                s.spawn(move || control.with_data_mut(|data| *data += i));
+            }
+        });
+        control.take_tls();
+
+        let spilled = spilled.lock().unwrap().clone();
+        let acc = control.clone_acc();
+        assert!(
+            spilled.iter().all(|&chunk| chunk > 5),
+            "spilled chunks exceed max_size"
+        );
+        assert!(acc <= 5, "accumulated value does not exceed max_size");
+        assert_eq_and_println(
+            &(spilled.iter().sum::<u64>() + acc),
+            &10,
+            "nothing lost by spilling",
+        );
+    }
+
+    #[test]
+    fn with_activity_tracking() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL).with_activity_tracking();
+        let main_tid = thread::current().id();
+
+        control.with_data_mut(|data| *data += 1);
+        let spawned_tid = thread::scope(|s| {
+            s.spawn(|| {
+                control.with_data_mut(|data| *data += 2);
+                control.with_data_mut(|data| *data += 4);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+        control.with_data_mut(|data| *data += 8);
+
+        let activity = control.thread_activity();
+        assert_eq_and_println(&activity.len(), &2, "threads tracked");
+        let main = activity[&main_tid];
+        let spawned = activity[&spawned_tid];
+        assert!(main.first_seen < spawned.first_seen, "first_seen order");
+        assert!(spawned.last_seen < main.last_seen, "last_seen order");
+        assert!(spawned.first_seen <= spawned.last_seen, "spawned interval");
+    }
+
+    #[test]
+    fn with_tl_init() {
+        thread_local! {static VEC_TL: Holder<Vec<u64>, Vec<u64>> = Holder::new();}
+
+        let control = Control::new(
+            &VEC_TL,
+            Vec::new(),
+            Vec::new,
+            |data, acc: &mut Vec<u64>, _| acc.extend(data),
+        )
+        .with_tl_init(|_| Vec::with_capacity(16));
+
+        control.with_data_mut(|data| {
+            assert!(data.capacity() >= 16, "initial data pre-sized");
+            data.push(1);
+        });
+        control.take_tls();
+        assert_eq_and_println(&control.clone_acc(), &vec![1], "take_tls with_tl_init");
+        control.with_data(|data| assert!(data.capacity() >= 16, "replacement data pre-sized"));
+    }
+
+    #[test]
+    fn on_tl_dropped() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("contribute");
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|s| {
+            let hs = (1..=3)
+                .map(|i| {
+                    let control = &control;
+                    let gater = &gater;
+                    s.spawn(move || {
+                        gater.wait_for(0);
+                        control.with_data_mut(|data| *data += i);
+                    })
+                })
+                .collect::<Vec<_>>();
+            let tid = hs[1].thread().id();
+            control.on_tl_dropped(tid, move || sender.send(tid).unwrap());
+            gater.open(0);
+
+            assert_eq_and_println(&receiver.recv().unwrap(), &tid, "notified thread");
+            assert!(
+                control.clone_acc() >= 2,
+                "thread's value accumulated when notified"
+            );
+        });
+
+        assert!(receiver.recv().is_err(), "notified only once");
+        control.take_tls();
+        assert_eq_and_println(&control.take_acc(0), &6, "all values");
+    }
+
+    #[test]
+    fn same_control() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let other = Control::<u64, u64>::new_additive(&COUNT_TL);
+
+        assert!(control.same_control(&control.clone()), "clone");
+        assert!(
+            control.same_control(&control.sender().clone().0),
+            "clone through sender"
+        );
+        assert!(!control.same_control(&other), "other control");
+    }
+
+    #[cfg(feature = "overhead-stats")]
+    #[test]
+    fn overhead_stats() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        assert_eq_and_println(&control.overhead_stats().count, &0, "no contributions");
+
+        thread::scope(|s| {
+            for _ in 0..3 {
+                let control = control.clone();
+                s.spawn(move || {
+                    for i in 0..10 {
+                        control.with_data_mut(|data| *data += i);
+                    }
+                });
+            }
+        });
+
+        let stats = control.overhead_stats();
+        assert_eq_and_println(&stats.count, &30, "contributions");
+        assert_eq_and_println(&stats.buckets.iter().sum::<u64>(), &30, "histogram total");
+        assert!(stats.max <= stats.total, "max within total");
+        assert!(
+            stats.quantile(0.99) <= Some(stats.max),
+            "quantile within max"
+        );
+    }
+
+    #[test]
+    fn take_threads() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("take_threads");
+
+        thread::scope(|s| {
+            let hs = [1, 10].map(|i| {
+                let control = &control;
+                let gater = &gater;
+                s.spawn(move || {
+                    control.with_data_mut(|data| *data += i);
+                    gater.wait_for(0);
+                    control.with_data_mut(|data| *data += i);
+                })
+            });
+            while control.registered_count() < 2 {
+                thread::yield_now();
+            }
+
+            control.take_threads(&[hs[0].thread().id()]);
+            assert_eq_and_println(&control.clone_acc(), &1, "selected thread taken");
+            assert_eq_and_println(&control.probe_tls(), &11, "other thread untouched");
+            gater.open(0);
+        });
+
+        control.take_tls();
+        assert_eq_and_println(&control.take_acc(0), &22, "all values");
+    }
+
+    #[test]
+    fn clear_thread() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("clear_thread");
+
+        thread::scope(|s| {
+            let hs = [1, 10].map(|i| {
+                let control = &control;
+                let gater = &gater;
+                s.spawn(move || {
+                    control.with_data_mut(|data| *data += i);
+                    gater.wait_for(0);
+                    control.with_data_mut(|data| *data += i);
+                })
+            });
+            while control.registered_count() < 2 {
+                thread::yield_now();
+            }
+
+            assert!(control.clear_thread(hs[0].thread().id()), "cleared");
+            assert!(
+                !control.clear_thread(thread::current().id()),
+                "unregistered thread"
+            );
+            assert_eq_and_println(&control.probe_tls(), &10, "cleared value discarded");
+            gater.open(0);
+        });
+
+        control.take_tls();
+        assert_eq_and_println(&control.take_acc(0), &21, "all values");
+    }
+
+    #[test]
+    fn drain_per_thread() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("drain_per_thread");
+
+        thread::scope(|s| {
+            let hs = [1, 10].map(|i| {
+                let control = &control;
+                let gater = &gater;
+                s.spawn(move || {
+                    control.with_data_mut(|data| *data += i);
+                    gater.wait_for(0);
+                    control.with_data_mut(|data| *data += i);
+                })
+            });
+            while control.registered_count() < 2 {
+                thread::yield_now();
+            }
+
+            let breakdown = control.drain_per_thread().sorted();
+            let expected = hs
+                .each_ref()
+                .map(|h| h.thread().id())
+                .into_iter()
+                .zip([1, 10]);
+            assert_eq_and_println(
+                &breakdown.into_iter().collect::<Vec<_>>(),
+                &expected.collect::<Vec<_>>(),
+                "per-thread values in thread order",
+            );
+            assert_eq_and_println(&control.probe_tls(), &0, "values drained");
+            gater.open(0);
+        });
+
+        control.take_tls();
+        assert_eq_and_println(&control.take_acc(0), &11, "values after drain");
+    }
+
+    #[test]
+    fn drain_iter() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("drain_iter");
+
+        thread::scope(|s| {
+            let hs = [1, 10].map(|i| {
+                let control = &control;
+                let gater = &gater;
+                s.spawn(move || {
+                    control.with_data_mut(|data| *data += i);
+                    gater.wait_for(0);
+                    control.with_data_mut(|data| *data += i);
+                })
+            });
+            while control.registered_count() < 2 {
+                thread::yield_now();
+            }
+
+            let mut iter = control.drain_iter();
+            let (tid, data) = iter.next().unwrap();
+            assert_eq_and_println(&control.probe_tls(), &(11 - data), "only first value taken");
+            let mut values = vec![(tid, data)];
+            values.extend(iter);
+            values.sort_by_key(|&(_, data)| data);
+            let expected = hs
+                .each_ref()
+                .map(|h| h.thread().id())
+                .into_iter()
+                .zip([1, 10]);
+            assert_eq_and_println(&values, &expected.collect::<Vec<_>>(), "per-thread values");
+            assert_eq_and_println(&control.probe_tls(), &0, "values drained");
+            gater.open(0);
+        });
+
+        control.take_tls();
+        assert_eq_and_println(&control.take_acc(0), &11, "values after drain");
+    }
+
+    #[test]
+    fn groups() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("groups");
+
+        thread::scope(|s| {
+            let hs = [("io", 1), ("io", 10), ("compute", 100)].map(|(tag, i)| {
+                let control = &control;
+                let gater = &gater;
+                s.spawn(move || {
+                    control.link_tagged(tag);
+                    control.with_data_mut(|data| *data += i);
+                    gater.wait_for(0);
+                })
+            });
+            control.with_data_mut(|data| *data += 1000);
+            while control.registered_count() < 4 {
+                thread::yield_now();
+            }
+            while hs
+                .iter()
+                .any(|h| control.thread_tag(h.thread().id()).is_none())
+            {
+                thread::yield_now();
+            }
+
+            assert_eq_and_println(
+                &control.thread_tag(hs[2].thread().id()),
+                &Some("compute"),
+                "thread tag",
+            );
+            assert_eq_and_println(
+                &control.thread_tag(thread::current().id()),
+                &None,
+                "untagged",
+            );
+            assert_eq_and_println(&control.probe_group("io", 0), &11, "probe io");
+            assert_eq_and_println(&control.probe_group("other", 0), &0, "probe unknown tag");
+
+            control.take_group("io");
+            assert_eq_and_println(&control.clone_acc(), &11, "acc after take io");
+            assert_eq_and_println(&control.probe_tls(), &1111, "probe after take io");
+
+            let mut groups = control.drain_per_group();
+            let compute = groups.remove(&Some("compute")).unwrap();
+            let untagged = groups.remove(&None).unwrap();
+            assert_eq_and_println(
+                &compute.into_iter().collect::<Vec<_>>(),
+                &vec![(hs[2].thread().id(), 100)],
+                "compute breakdown",
+            );
+            assert_eq_and_println(
+                &untagged.into_iter().collect::<Vec<_>>(),
+                &vec![(thread::current().id(), 1000)],
+                "untagged breakdown",
+            );
+            let io = groups.remove(&Some("io")).unwrap();
+            assert_eq_and_println(&io.len(), &2, "io threads drained after take");
+            gater.open(0);
+        });
+    }
+
+    #[test]
+    fn contains_thread() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("linked");
+
+        thread::scope(|s| {
+            let h = s.spawn(|| {
+                assert!(
+                    !COUNT_TL.with(Holder::is_linked),
+                    "not linked before contribution"
+                );
+                control.with_data_mut(|data| *data += 1);
+                assert!(
+                    COUNT_TL.with(Holder::is_linked),
+                    "linked after contribution"
+                );
+                gater.wait_for(0);
+            });
+            let tid = h.thread().id();
+            while control.registered_count() < 1 {
+                thread::yield_now();
+            }
+            assert!(control.contains_thread(tid), "registered thread");
+            assert!(
+                !control.contains_thread(thread::current().id()),
+                "main thread"
+            );
+            gater.open(0);
+            h.join().unwrap();
+            assert!(!control.contains_thread(tid), "terminated thread");
+        });
+    }
+
+    #[test]
+    fn finalize() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let sender = control.sender();
+
+        thread::scope(|s| {
+            s.spawn(|| control.with_data_mut(|data| *data += 2));
+        });
+        control.with_data_mut(|data| *data += 1);
+
+        assert_eq_and_println(&control.finalize(0), &3, "finalize");
+        assert!(control.is_sealed(), "sealed");
+        assert_eq_and_println(
+            &sender.try_with_data_mut(|data| *data += 1),
+            &Err(AccessError::Sealed(SealedError)),
+            "contribution after finalize",
+        );
+        assert_eq_and_println(&control.probe_tls(), &0, "rejected contribution");
+    }
+
+    #[test]
+    fn finalize_racing_contributions() {
+        for _ in 0..20 {
+            let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+            let barrier = Barrier::new(5);
+
+            let (accepted, finalized) = thread::scope(|s| {
+                let contributors: Vec<_> = (0..4)
+                    .map(|_| {
+                        s.spawn(|| {
+                            barrier.wait();
+                            let mut accepted = 0;
+                            while control.try_with_data_mut(|data| *data += 1).is_ok() {
+                                accepted += 1;
+                            }
+                            accepted
+                        })
+                    })
+                    .collect();
+                barrier.wait();
+                thread::sleep(Duration::from_millis(1));
+                let finalized = control.finalize(0);
+                let accepted: u64 = contributors.into_iter().map(|h| h.join().unwrap()).sum();
+                (accepted, finalized)
+            });
+
+            assert_eq_and_println(&finalized, &accepted, "accepted contributions finalized");
+            assert_eq_and_println(&control.take_acc(0), &0, "no contribution after finalize");
+        }
+    }
+
+    #[test]
+    fn set_enabled() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        control.with_data_mut(|data| *data += 1);
+
+        control.set_enabled(false);
+        assert!(!control.clone().is_enabled(), "disabled clone");
+        assert_eq_and_println(
+            &control.try_with_data_mut(|data| *data += 10),
+            &Err(AccessError::Disabled(DisabledError)),
+            "contribution while disabled",
+        );
+        let res = control.with_data_mut(|data| {
+            *data += 10;
+            *data
+        });
+        assert_eq_and_println(&res, &10, "with_data_mut while disabled");
+        assert_eq_and_println(&control.probe_tls(), &1, "data before disable retained");
+
+        control.set_enabled(true);
+        control.with_data_mut(|data| *data += 100);
+        control.take_tls();
+        assert_eq_and_println(&control.take_acc(0), &101, "after re-enable");
+    }
+
+    #[cfg(thread_local_collect_off)]
+    #[test]
+    fn collect_off() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        assert!(!control.is_enabled(), "disabled when compiled out");
+        control.set_enabled(true);
+        assert!(!control.is_enabled(), "cannot be enabled when compiled out");
+        let res = control.with_data_mut(|data| {
+            *data += 1;
+            *data
+        });
+        assert_eq_and_println(&res, &1, "closure invoked on scratch data");
+        assert_eq_and_println(
+            &control.try_with_data_mut(|data| *data += 2),
+            &Err(AccessError::Disabled(DisabledError)),
+            "try_with_data_mut compiled out",
+        );
+        thread::scope(|s| {
+            s.spawn(|| control.with_data_mut(|data| *data += 3));
+        });
+        assert_eq_and_println(&control.probe_tls(), &0, "probe");
+        control.take_tls();
+        assert_eq_and_println(&control.take_acc(0), &0, "contributions compiled out");
+    }
+
+    #[test]
+    #[should_panic(expected = "finalized")]
+    fn finalize_then_with_data_mut() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        control.finalize(0);
+        control.with_data_mut(|data| *data += 1);
+    }
+
+    #[test]
+    fn advance_epoch() {
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("frame");
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                control.with_data_mut(|data| *data += 1);
+                gater.wait_for(0);
+                control.with_data_mut(|data| *data += 10);
+            });
+
+            control.with_data_mut(|data| *data += 2);
+            // Wait for the spawned thread to contribute to epoch 0.
+            while control.probe_tls() < 3 {
+                thread::yield_now();
+            }
+
+            assert_eq_and_println(
+                &control.advance_epoch(0),
+                &Epoch { epoch: 0, acc: 3 },
+                "advance_epoch 0",
+            );
+            assert_eq_and_println(&control.current_epoch(), &1, "current_epoch");
+            gater.open(0);
+        });
+
+        assert_eq_and_println(
+            &control.advance_epoch(0),
+            &Epoch { epoch: 1, acc: 10 },
+            "advance_epoch 1",
+        );
+        assert_eq_and_println(
+            &control.advance_epoch(0),
+            &Epoch { epoch: 2, acc: 0 },
+            "advance_epoch 2",
+        );
+    }
+
+    #[test]
+    fn take_at_barrier() {
+        const NTHREADS: usize = 3;
+        const NPHASES: u64 = 3;
+
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let barrier = Barrier::new(NTHREADS + 1);
+
+        thread::scope(|s| {
+            for _ in 0..NTHREADS {
+                s.spawn(|| {
+                    for phase in 1..=NPHASES {
+                        control.with_data_mut(|data| *data += phase);
+                        barrier.wait();
+                        barrier.wait();
+                    }
+                });
+            }
+
+            for phase in 1..=NPHASES {
+                let acc = control.take_at_barrier(&barrier, 0);
+                assert_eq_and_println(
+                    &acc,
+                    &(NTHREADS as u64 * phase),
+                    &format!("take_at_barrier phase {phase}"),
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn blocking_probe() {
+        const NTHREADS: usize = 4;
+
+        let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+        let gater = ThreadGater::new("workers");
+
+        let res = control.blocking_probe(1, Duration::from_millis(10));
+        assert_eq_and_println(
+            &res,
+            &Err(ProbeTimeoutError { registered: 0 }),
+            "blocking_probe timeout",
+        );
+
+        thread::scope(|s| {
+            for _ in 0..NTHREADS {
+                s.spawn(|| {
+                    control.with_data_mut(|data| *data += 1);
+                    gater.wait_for(0);
+                });
+            }
+
+            let res = control.blocking_probe(NTHREADS, Duration::from_secs(10));
+            assert_eq_and_println(
+                &res,
+                &Ok(NTHREADS as u64),
+                "blocking_probe after workers started",
+            );
+            gater.open(0);
+        });
+
+        control.take_tls();
+        assert_eq_and_println(&control.registered_count(), &NTHREADS, "registered_count");
+    }
+
+    #[test]
+    fn dead_nodes_pruned() {
+        let control = Control::new(&MY_TL, HashMap::new(), HashMap::new, op);
+        insert_tl_entry(1, Foo("a".to_owned()), &control);
+
+        // Simulate a registry entry whose holder was dropped without notifying the control object.
+        let dead_key = TlKey {
+            tid: thread::current().id(),
+            tl_addr: 0,
+        };
+        let dead_data = Arc::new(Mutex::new(Some(HashMap::from([(2, Foo("b".to_owned()))]))));
+        let dead_node = Node {
+            data: Arc::downgrade(&dead_data),
+            tag: None,
+        };
+        control.lock().s.tmap.insert(dead_key, dead_node);
+        drop(dead_data);
+        assert_eq!(control.lock().s.tmap.len(), 2, "registry before probe");
+
+        let map = HashMap::from([(
+            thread::current().id(),
+            HashMap::from([(1, Foo("a".to_owned()))]),
+        )]);
+        assert_eq_and_println(&control.probe_tls(), &map, "probe_tls with dead node");
+        assert_eq!(control.lock().s.tmap.len(), 1, "registry after probe");
+
+        control.take_tls();
+        assert_eq_and_println(&control.clone_acc(), &map, "take_tls after pruning");
+    }
+
+    #[test]
+    fn probe_cache() {
+        let ops = Arc::new(AtomicUsize::new(0));
+        let control = Control::<u64, u64>::new(&COUNT_TL, 0, u64::default, {
+            let ops = ops.clone();
+            move |data, acc, _| {
+                ops.fetch_add(1, Ordering::Relaxed);
+                *acc += data;
+            }
+        })
+        .with_probe_cache();
+        let ops = || ops.load(Ordering::Relaxed);
+
+        control.with_data_mut(|data| *data += 1);
+        assert_eq_and_println(&control.probe_tls(), &1, "computed");
+        let computed = ops();
+        assert_eq_and_println(&control.probe_tls(), &1, "cached");
+        assert_eq_and_println(&ops(), &computed, "cached probe does not aggregate");
+
+        let tid = thread::current().id();
+        assert_eq_and_println(&*control.acc(), &0, "acc");
+        assert_eq_and_println(&control.clone_acc(), &0, "clone_acc");
+        assert_eq_and_println(&control.registered_count(), &1, "registered_count");
+        assert!(control.contains_thread(tid), "contains_thread");
+        assert_eq_and_println(&control.thread_tag(tid), &None, "thread_tag");
+        assert_eq_and_println(&control.current_epoch(), &0, "current_epoch");
+        assert_eq_and_println(&control.probe_tls(), &1, "cached after accessors");
+        assert_eq_and_println(&ops(), &computed, "accessors do not invalidate the cache");
+
+        thread::scope(|s| {
+            s.spawn(|| control.with_data_mut(|data| *data += 10));
+        });
+        assert_eq_and_println(&control.probe_tls(), &11, "invalidated by contribution");
+
+        control.with_data_mut(|data| *data += 100);
+        assert_eq_and_println(
+            &control.probe_tls(),
+            &111,
+            "invalidated by own contribution",
+        );
+
+        control.take_tls();
+        control.take_acc(0);
+        assert_eq_and_println(&control.probe_tls(), &0, "invalidated by take_acc");
+    }
+
+    #[test]
+    fn probe_cache_disabled() {
+        let ops = Arc::new(AtomicUsize::new(0));
+        let control = Control::<u64, u64>::new(&COUNT_TL, 0, u64::default, {
+            let ops = ops.clone();
+            move |data, acc, _| {
+                ops.fetch_add(1, Ordering::Relaxed);
+                *acc += data;
+            }
+        });
+
+        control.with_data_mut(|data| *data += 1);
+        assert_eq_and_println(&control.probe_tls(), &1, "first probe");
+        assert_eq_and_println(&control.probe_tls(), &1, "second probe");
+        assert_eq_and_println(&ops.load(Ordering::Relaxed), &2, "each probe aggregates");
+        assert!(control.lock().s.probe_cache.is_none(), "nothing cached");
+    }
+
+    #[test]
+    fn reregistration() {
+        // Simulates a registration that outlived its thread-local variable, e.g., in the child of a fork.
+        let stale = |control: &Control<u64, u64>| {
+            let data = Arc::new(Mutex::new(Some(5)));
+            let node = Node {
+                data: Arc::downgrade(&data),
+                tag: None,
+            };
+            let key = TlKey::new(thread::current().id(), &COUNT_TL);
+            control.lock().s.tmap.insert(key, node);
+            data
+        };
+
+        thread::spawn(move || {
+            let control = Control::<u64, u64>::new_additive(&COUNT_TL);
+            let _stale = stale(&control);
+            control.with_data_mut(|data| *data += 1);
+            assert_eq_and_println(&control.probe_tls(), &1, "stale registration replaced");
+        })
+        .join()
+        .unwrap();
+
+        thread::spawn(move || {
+            let control = Control::<u64, u64>::new_additive(&COUNT_TL)
+                .with_reregistration(Reregistration::Reject);
+            let _stale = stale(&control);
+            assert_eq_and_println(
+                &control.try_with_data_mut(|data| *data += 1),
+                &Err(AccessError::Reregistration(ReregistrationError)),
+                "registration rejected",
+            );
+            assert_eq_and_println(&control.probe_tls(), &5, "stale registration kept");
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                control.with_data_mut(|data| *data += 1);
+            }));
+            assert!(res.is_err(), "registration rejected by with_data_mut");
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn drained() {
+        thread_local! {static U64_TL: Holder<u64, u64> = Holder::new();}
+
+        let control = Control::<u64, u64>::new_additive(&U64_TL);
+        control.with_data_mut(|acc| *acc += 1);
+
+        let drained = control.drained();
+        let barrier = Arc::new(Barrier::new(4));
+        let hs = (2..=4)
+            .map(|i| {
+                let control = control.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    control.with_data_mut(|acc| *acc += i);
+                    barrier.wait();
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // All threads are linked after this point.
+        barrier.wait();
+        futures_lite::future::block_on(drained);
+        assert_eq_and_println(&control.probe_tls(), &10, "drained");
+
+        for h in hs {
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn reentrant_access() {
+        use crate::tlm::ReentrantAccessError;
+        use std::sync::OnceLock;
+
+        thread_local! {static U64_TL: Holder<u64, u64> = Holder::new();}
+
+        // The control object, set after it is instantiated, and the result of the access to it from `op`.
+        let this = Arc::new(Mutex::new(None::<Control<u64, u64>>));
+        let nested = Arc::new(OnceLock::new());
+        let control = Control::<u64, u64>::new(&U64_TL, 0, u64::default, {
+            let this = this.clone();
+            let nested = nested.clone();
+            move |data, acc, _| {
+                *acc += data;
+                if let Some(control) = this.lock().unwrap().as_ref() {
+                    _ = nested.set(control.try_with_data_mut(|data| *data += 100));
+                }
+            }
+        });
+        *this.lock().unwrap() = Some(control.clone());
+
+        let res = control.with_data_mut(|data| {
+            *data += 1;
+            control.try_with_data_mut(|data| *data += 100)
+        });
+        assert_eq_and_println(
+            &res,
+            &Err(AccessError::Reentrant(ReentrantAccessError)),
+            "nested with_data_mut",
+        );
+        let res = control.with_data(|_| control.try_with_data(|data| *data));
+        assert_eq_and_println(
+            &res,
+            &Err(AccessError::Reentrant(ReentrantAccessError)),
+            "nested with_data",
+        );
+        control.with_data_mut(|data| *data += 1);
+        assert_eq_and_println(&control.probe_tls(), &2, "after nested accesses");
+
+        control.take_tls();
+        assert_eq_and_println(
+            &nested.get(),
+            &Some(&Err(AccessError::Reentrant(ReentrantAccessError))),
+            "with_data_mut from op",
+        );
+        assert_eq_and_println(&control.take_acc(0), &2, "take_tls");
+        this.lock().unwrap().take();
+    }
+
+    #[cfg(feature = "parking_lot")]
+    #[test]
+    fn parking_lot_guards() {
+        use super::{ControlWith, HolderWith};
+
+        type PlMutex = Arc<parking_lot::Mutex<Option<u64>>>;
+        type PlRwLock = Arc<parking_lot::RwLock<Option<u64>>>;
+
+        thread_local! {
+            static MUTEX_TL: HolderWith<u64, u64, PlMutex> = HolderWith::new();
+            static RWLOCK_TL: HolderWith<u64, u64, PlRwLock> = HolderWith::new();
+        }
+
+        let mutex_control = ControlWith::<u64, u64, PlMutex>::new_additive(&MUTEX_TL);
+        let rwlock_control = ControlWith::<u64, u64, PlRwLock>::new_additive(&RWLOCK_TL);
+        thread::scope(|s| {
+            for i in 1..=4 {
+                let mutex_control = &mutex_control;
+                let rwlock_control = &rwlock_control;
+                s.spawn(move || {
+                    mutex_control.with_data_mut(|data| *data += i);
+                    rwlock_control.with_data_mut(|data| *data += i);
+                });
+            }
+        });
+        mutex_control.with_data_mut(|data| *data += 5);
+        rwlock_control.with_data_mut(|data| *data += 5);
+
+        assert_eq_and_println(&mutex_control.probe_tls(), &15, "parking_lot Mutex");
+        assert_eq_and_println(&rwlock_control.probe_tls(), &15, "parking_lot RwLock");
+        mutex_control.take_tls();
+        assert_eq_and_println(&mutex_control.take_acc(0), &15, "parking_lot Mutex take");
+    }
 }
@@ -0,0 +1,191 @@
+//! Bounded model-checking harnesses for the registration protocol of [`super::probed`]: the state machine of
+//! [`super::common::CtrlStateG`] with [`super::tmap_d::TmapD`] under arbitrary sequences of register, contribute,
+//! take, probe, and drop steps, each of which the library performs while holding the control object's state lock.
+//!
+//! After each step, the model asserts that the accumulated value plus the values held by the live thread-local
+//! variables equals the sum of all contributions, i.e., that no contribution is lost or accumulated twice, and that
+//! the registry contains exactly the live thread-local variables.
+//!
+//! The harness runs with [Kani](https://model-checking.github.io/kani/), e.g., `cargo kani --features probed`, which
+//! proves these properties for all step sequences up to the unwinding bound. The same model is exercised by a unit
+//! test that enumerates all short step sequences.
+
+use super::{
+    common::{Ctrl, CtrlNode, TlKey},
+    probed::{Control, Holder, Node},
+};
+use std::{
+    sync::{Arc, Mutex},
+    thread::{self, LocalKey},
+};
+
+/// Number of thread-local variables in the model.
+const HOLDERS: usize = 2;
+
+thread_local! {
+    static TL0: Holder<u32, u32> = Holder::new();
+    static TL1: Holder<u32, u32> = Holder::new();
+}
+
+const TLS: [&LocalKey<Holder<u32, u32>>; HOLDERS] = [&TL0, &TL1];
+
+/// Step of the registration protocol.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// Links thread-local variable `i`, as on its first access.
+    Link(usize),
+    /// Adds a value to thread-local variable `i`, if it is linked.
+    Contribute(usize, u32),
+    /// Takes the values of the linked thread-local variables.
+    Take,
+    /// Probes the linked thread-local variables.
+    Probe,
+    /// Drops thread-local variable `i`, as on thread termination.
+    Drop(usize),
+}
+
+/// Control object with one thread-local variable per static in [`TLS`]. The guarded data of each thread-local
+/// variable is held by the model instead of a holder, so that it can be dropped at any step.
+struct Model {
+    /// Control objects, sharing the same state, linked to the statics in [`TLS`].
+    controls: Vec<Control<u32, u32>>,
+    /// Guarded data of the linked thread-local variables that have not been dropped.
+    data: [Option<Arc<Mutex<Option<u32>>>>; HOLDERS],
+    /// Whether each thread-local variable has been dropped. A dropped thread-local variable is not linked again.
+    dropped: [bool; HOLDERS],
+    /// Sum of all contributions.
+    contributed: u32,
+}
+
+impl Model {
+    fn new() -> Self {
+        let control = Control::new(TLS[0], 0, u32::default, |data, acc, _| *acc += data);
+        Self {
+            controls: TLS.iter().map(|tl| control.for_tl(tl)).collect(),
+            data: [None, None],
+            dropped: [false; HOLDERS],
+            contributed: 0,
+        }
+    }
+
+    /// Performs `step` as the library does.
+    fn step(&mut self, step: Step) {
+        let tid = thread::current().id();
+        match step {
+            Step::Link(i) if self.data[i].is_none() && !self.dropped[i] => {
+                // Same sequence as `HolderG::link` for holders with nodes.
+                let data = Arc::new(Mutex::new(None));
+                self.controls[i]
+                    .register_node(
+                        Node {
+                            data: Arc::downgrade(&data),
+                            tag: None,
+                        },
+                        tid,
+                    )
+                    .unwrap();
+                self.controls[i].tl_linked();
+                *data.lock().unwrap() = Some(self.controls[i].make_data(tid));
+                self.data[i] = Some(data);
+            }
+            Step::Contribute(i, value) => {
+                if let Some(data) = &self.data[i] {
+                    *data.lock().unwrap().as_mut().unwrap() += value;
+                    self.contributed += value;
+                }
+            }
+            Step::Take => self.controls[0].take_tls(),
+            Step::Probe => {
+                let probed = self.controls[0].probe_tls();
+                assert_eq!(probed, self.contributed, "probe sees all contributions");
+            }
+            Step::Drop(i) => {
+                // Same sequence as `HolderG::drop_data`.
+                if let Some(data) = self.data[i].take() {
+                    self.controls[i].tl_data_dropped(|| data.lock().unwrap().take(), tid);
+                    self.dropped[i] = true;
+                }
+            }
+            Step::Link(_) => (),
+        }
+    }
+
+    /// Asserts the protocol invariants.
+    fn check(&self) {
+        let held = self
+            .data
+            .iter()
+            .flatten()
+            .map(|data| data.lock().unwrap().unwrap_or_default())
+            .sum::<u32>();
+        assert_eq!(
+            self.controls[0].clone_acc() + held,
+            self.contributed,
+            "no lost or double-accumulated contributions"
+        );
+
+        let tid = thread::current().id();
+        let state = self.controls[0].lock();
+        for (i, tl) in TLS.iter().enumerate() {
+            assert_eq!(
+                state.s.tmap.contains_key(&TlKey::new(tid, tl)),
+                self.data[i].is_some(),
+                "registry contains exactly the live thread-local variables"
+            );
+        }
+    }
+}
+
+#[cfg(kani)]
+fn any_step() -> Step {
+    let i = kani::any::<usize>() % HOLDERS;
+    match kani::any::<u8>() % 5 {
+        0 => Step::Link(i),
+        1 => Step::Contribute(i, kani::any::<u8>().into()),
+        2 => Step::Take,
+        3 => Step::Probe,
+        _ => Step::Drop(i),
+    }
+}
+
+#[cfg(kani)]
+#[kani::proof]
+#[kani::unwind(8)]
+fn registration_protocol() {
+    let mut model = Model::new();
+    for _ in 0..6 {
+        model.step(any_step());
+        model.check();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{Model, Step};
+
+    const STEPS: [Step; 8] = [
+        Step::Link(0),
+        Step::Link(1),
+        Step::Contribute(0, 1),
+        Step::Contribute(1, 2),
+        Step::Take,
+        Step::Probe,
+        Step::Drop(0),
+        Step::Drop(1),
+    ];
+
+    #[test]
+    fn all_short_sequences() {
+        let len = 5;
+        for n in 0..STEPS.len().pow(len) {
+            let mut model = Model::new();
+            let mut n = n;
+            for _ in 0..len {
+                model.step(STEPS[n % STEPS.len()]);
+                n /= STEPS.len();
+                model.check();
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Count of the live thread-local holders linked to a control object, which allows waiting for the holders to be
+//! dropped, and callbacks to be invoked when the holders of specific threads are dropped.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Debug, Display},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::ThreadId,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    mem::take,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+const POISONED_QUIESCENCE_MUTEX: &str = "poisoned quiescence mutex";
+
+/// Indicates that `wait_quiescent` timed out before the thread-local holders linked to the control object were
+/// dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuiescenceTimeoutError {
+    /// Number of live thread-local holders, other than the caller's, when the timeout expired.
+    pub live: usize,
+}
+
+impl Display for QuiescenceTimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "wait_quiescent timed out with {} live thread-local variables.",
+            self.live
+        )
+    }
+}
+
+impl Error for QuiescenceTimeoutError {}
+
+/// Callbacks to be invoked when the holders of the threads they are keyed by are dropped.
+#[derive(Default)]
+struct Watchers(HashMap<ThreadId, Vec<Box<dyn FnOnce() + Send>>>);
+
+impl Debug for Watchers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+/// Count of live holders, wakers of pending [`DrainedFuture`]s, and per-thread drop callbacks.
+#[derive(Debug, Default)]
+struct Live {
+    count: usize,
+    #[cfg(feature = "async")]
+    wakers: Vec<Waker>,
+    watchers: Watchers,
+}
+
+/// Count of live holders shared by a control object and its clones.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Quiescence(Arc<(Mutex<Live>, Condvar)>);
+
+impl Quiescence {
+    /// Locks the count of live holders.
+    ///
+    /// # Panics
+    /// If the mutex is poisoned.
+    fn lock(&self) -> MutexGuard<'_, Live> {
+        self.0 .0.lock().expect(POISONED_QUIESCENCE_MUTEX)
+    }
+
+    /// Records the linkage of a holder.
+    ///
+    /// # Panics
+    /// If the mutex is poisoned.
+    pub(crate) fn linked(&self) {
+        self.lock().count += 1;
+    }
+
+    /// Records the drop of the linked holder of thread `tid`, wakes up the waiting threads and tasks, and invokes
+    /// the callbacks registered for `tid`, without holding the lock.
+    ///
+    /// # Panics
+    /// If the mutex is poisoned.
+    pub(crate) fn dropped(&self, tid: ThreadId) {
+        let mut live = self.lock();
+        live.count = live.count.saturating_sub(1);
+        #[cfg(feature = "async")]
+        let wakers = take(&mut live.wakers);
+        let watchers = live.watchers.0.remove(&tid);
+        drop(live);
+        self.0 .1.notify_all();
+        #[cfg(feature = "async")]
+        wakers.into_iter().for_each(Waker::wake);
+        watchers.into_iter().flatten().for_each(|f| f());
+    }
+
+    /// Registers `f` to be invoked when the linked holder of thread `tid` is dropped.
+    ///
+    /// # Panics
+    /// If the mutex is poisoned.
+    pub(crate) fn on_dropped(&self, tid: ThreadId, f: impl FnOnce() + Send + 'static) {
+        self.lock()
+            .watchers
+            .0
+            .entry(tid)
+            .or_default()
+            .push(Box::new(f));
+    }
+
+    /// Blocks until the number of live holders is at most `own`, the number of holders of the calling thread.
+    ///
+    /// # Errors
+    /// Returns an error if `timeout` expires first.
+    ///
+    /// # Panics
+    /// If the mutex is poisoned.
+    pub(crate) fn wait(&self, own: usize, timeout: Duration) -> Result<(), QuiescenceTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut live = self.lock();
+        while live.count > own {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(QuiescenceTimeoutError {
+                    live: live.count - own,
+                });
+            }
+            live = self
+                .0
+                 .1
+                .wait_timeout(live, deadline - now)
+                .expect(POISONED_QUIESCENCE_MUTEX)
+                .0;
+        }
+        Ok(())
+    }
+
+    /// Returns a future that resolves when the number of live holders is at most `own`.
+    #[cfg(feature = "async")]
+    pub(crate) fn drained(&self, own: usize) -> DrainedFuture {
+        DrainedFuture {
+            quiescence: self.clone(),
+            own,
+        }
+    }
+}
+
+/// Future returned by the `drained` methods of the [`crate::tlm`] `Control` types, which resolves when the
+/// thread-local variables linked to the control object, other than those of the thread that created the future,
+/// have been dropped. It does not depend on a specific async runtime. Requires the **"async"** feature.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct DrainedFuture {
+    quiescence: Quiescence,
+    own: usize,
+}
+
+#[cfg(feature = "async")]
+impl Future for DrainedFuture {
+    type Output = ();
+
+    /// # Panics
+    /// If the mutex used to count the linked thread-local variables is poisoned.
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut live = self.quiescence.lock();
+        if live.count <= self.own {
+            return Poll::Ready(());
+        }
+        if !live.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            live.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
@@ -0,0 +1,127 @@
+//! Detection of reentrant access to a control object from the thread that is already accessing it, e.g., a call to
+//! `with_data_mut` from inside the closure passed to `with_data_mut` or from inside the control object's `op`.
+
+use super::ReentrantAccessError;
+use std::cell::Cell;
+
+/// Maximum number of control objects that can be tracked as being accessed by a thread at the same time. Accesses
+/// nested deeper than that, which would require that many distinct control objects to be accessed from inside each
+/// other's closures or `op`s, are not tracked.
+const CAPACITY: usize = 16;
+
+/// Keys of the control objects being accessed by a thread, innermost last. Neither this nor its fields need to be
+/// dropped, so the thread-local variable holding it is never destroyed and accessing it is as cheap as accessing a
+/// plain `Cell`, which keeps the check off the cost of contributions.
+struct Active {
+    len: Cell<usize>,
+    keys: [Cell<usize>; CAPACITY],
+}
+
+impl Active {
+    #[inline]
+    fn contains(&self, key: usize) -> bool {
+        self.keys[..self.len.get()].iter().any(|k| k.get() == key)
+    }
+
+    #[inline]
+    fn push(&self, key: usize) -> bool {
+        let len = self.len.get();
+        if len == CAPACITY {
+            return false;
+        }
+        self.keys[len].set(key);
+        self.len.set(len + 1);
+        true
+    }
+
+    #[inline]
+    fn remove(&self, key: usize) {
+        let len = self.len.get();
+        // Guards are usually dropped in the reverse order in which they were created.
+        if len > 0 && self.keys[len - 1].get() == key {
+            self.len.set(len - 1);
+        } else if let Some(i) = self.keys[..len].iter().rposition(|k| k.get() == key) {
+            for j in i..len - 1 {
+                self.keys[j].set(self.keys[j + 1].get());
+            }
+            self.len.set(len - 1);
+        }
+    }
+}
+
+thread_local! {
+    static ACTIVE: Active = const {
+        Active {
+            len: Cell::new(0),
+            keys: [const { Cell::new(0) }; CAPACITY],
+        }
+    };
+}
+
+/// Marks the control object identified by `key` as being accessed by the current thread until dropped.
+#[derive(Debug)]
+pub(crate) struct AccessGuard(Option<usize>);
+
+impl AccessGuard {
+    /// Marks the control object identified by `key` as being accessed, unless it already is.
+    ///
+    /// # Errors
+    /// Returns an error if the current thread is already accessing the control object.
+    #[inline]
+    pub(crate) fn enter(key: usize) -> Result<Self, ReentrantAccessError> {
+        ACTIVE.with(|active| {
+            if active.contains(key) {
+                return Err(ReentrantAccessError);
+            }
+            Ok(Self(active.push(key).then_some(key)))
+        })
+    }
+
+    /// Marks the control object identified by `key` as being accessed, whether or not it already is. Nothing is
+    /// marked if [`CAPACITY`] control objects are already marked.
+    #[inline]
+    pub(crate) fn mark(key: usize) -> Self {
+        let marked = ACTIVE.with(|active| active.push(key));
+        Self(marked.then_some(key))
+    }
+}
+
+impl Drop for AccessGuard {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(key) = self.0 {
+            ACTIVE.with(|active| active.remove(key));
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{AccessGuard, CAPACITY};
+
+    #[test]
+    fn out_of_order_drop() {
+        let g1 = AccessGuard::enter(1).unwrap();
+        let g2 = AccessGuard::enter(2).unwrap();
+        assert!(AccessGuard::enter(1).is_err());
+        drop(g1);
+        assert!(AccessGuard::enter(1).is_ok());
+        assert!(AccessGuard::enter(2).is_err());
+        drop(g2);
+        assert!(AccessGuard::enter(2).is_ok());
+    }
+
+    #[test]
+    fn beyond_capacity() {
+        let guards: Vec<_> = (1..=CAPACITY)
+            .map(|key| AccessGuard::enter(key).unwrap())
+            .collect();
+        // Not tracked, so neither rejected nor marked.
+        assert!(AccessGuard::enter(0).is_ok());
+        assert!(AccessGuard::enter(1).is_err());
+        drop(guards);
+        let _guard = AccessGuard::enter(0).unwrap();
+        assert!(AccessGuard::enter(0).is_err());
+    }
+}
@@ -5,10 +5,26 @@ use super::super::common::{
     ControlG, CoreParam, CtrlParam, CtrlStateCore, CtrlStateParam, HldrData, HldrLink, HldrParam,
     New,
 };
+use crate::{
+    accumulator::Accumulator,
+    activity::ThreadActivity,
+    alias::ThreadAliases,
+    diag::{DiagEvent, OpRecord},
+    handles::{ControlSender, FinishedControl},
+    tlm::{AccessError, QuiescenceTimeoutError},
+};
+
+#[cfg(feature = "overhead-stats")]
+use crate::overhead::OverheadStats;
+#[cfg(feature = "async")]
+use crate::tlm::DrainedFuture;
 use std::{
+    collections::HashMap,
     fmt::Debug,
     mem::take,
+    ops::{Add, AddAssign},
     thread::{self, LocalKey, ThreadId},
+    time::Duration,
 };
 
 /// Wrapper of [`crate::tlm::common::ControlG`] that provides  an API
@@ -59,6 +75,221 @@ where
             acc_zero,
         }
     }
+
+    /// Instantiates a [`ControlRestrG`] object whose accumulation is defined by the [`Accumulator`] implementation
+    /// of `U`, with [`Accumulator::zero`] as `acc_zero` and [`Accumulator::merge`] as `op_r`.
+    /// See [`ControlRestrG::new`].
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn from_accumulator(tl: &'static LocalKey<P::Hldr>) -> Self
+    where
+        U: Accumulator + 'static,
+    {
+        Self::new(tl, U::zero, U::merge)
+    }
+
+    /// Instantiates a [`ControlRestrG`] object for the common case of additive accumulations, e.g., counting or
+    /// summing, with `U::default` as `acc_zero` and `+` as `op_r`. See [`ControlRestrG::new`].
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn new_additive(tl: &'static LocalKey<P::Hldr>) -> Self
+    where
+        U: Default + Add<Output = U> + 'static,
+    {
+        Self::new(tl, U::default, |acc1, acc2| acc1 + acc2)
+    }
+
+    /// Instantiates a [`ControlRestrG`] object for accumulators that implement [`Extend`] with their own items,
+    /// e.g., [`Vec`] or [`HashMap`], with `U::default` as `acc_zero` and [`Extend::extend`] as `op_r`. Data can then
+    /// be contributed with [`Self::extend_data`] without an `op` closure. See [`ControlRestrG::new`].
+    ///
+    /// - `tl` - reference to thread-local static.
+    pub fn from_extend(tl: &'static LocalKey<P::Hldr>) -> Self
+    where
+        U: Default + Extend<U::Item> + IntoIterator + 'static,
+    {
+        Self::new(tl, U::default, |mut acc1, acc2| {
+            acc1.extend(acc2);
+            acc1
+        })
+    }
+
+    /// Returns a clone of `self` that is linked to thread-local static `tl` instead of the thread-local static
+    /// `self` was instantiated with. The returned object shares `self`'s state and accumulated value, so that
+    /// multiple thread-local statics (e.g., defined by separate subsystems) can contribute to a single aggregate.
+    pub fn for_tl(&self, tl: &'static LocalKey<P::Hldr>) -> Self {
+        Self {
+            control: self.control.for_tl(tl),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Returns whether `self` and `other` are the same control object or clones of each other, including clones
+    /// obtained with [`Self::for_tl`], i.e., whether they aggregate into the same accumulated value.
+    pub fn same_control(&self, other: &Self) -> bool {
+        self.control.same_control(&other.control)
+    }
+
+    /// Attaches a diagnostics hook that receives structured events about `self`'s internal operations.
+    /// See [`crate::diag`].
+    pub fn with_diagnostics(self, hook: impl Fn(&DiagEvent) + 'static + Send + Sync) -> Self {
+        Self {
+            control: self.control.with_diagnostics(hook),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Enables a bounded history that keeps clones of the last `capacity` accumulated values drained from `self`,
+    /// which can be retrieved with [`Self::history`]. The history is shared by all clones of `self`.
+    pub fn with_history(self, capacity: usize) -> Self
+    where
+        U: Clone,
+    {
+        Self {
+            control: self.control.with_history(capacity),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Returns the last accumulated values drained from `self`, from oldest to newest, if history is enabled
+    /// with [`Self::with_history`], or an empty vector otherwise.
+    ///
+    /// # Panics
+    /// If the history's mutex is poisoned.
+    pub fn history(&self) -> Vec<U> {
+        self.control.history().into_iter().flatten().collect()
+    }
+
+    /// Returns the contribution activity of each thread that has contributed to `self` since activity tracking was
+    /// enabled with [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn thread_activity(&self) -> HashMap<ThreadId, ThreadActivity> {
+        self.control.thread_activity()
+    }
+
+    /// Returns the number of contributions made by each thread to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`], or an empty map if it is not enabled.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn contribution_counts(&self) -> HashMap<ThreadId, u64> {
+        self.control.contribution_counts()
+    }
+
+    /// Returns the threads that have contributed to `self` since activity tracking was enabled with
+    /// [`Self::with_activity_tracking`] but have not contributed in the last `since`, e.g., so that a supervisor can
+    /// detect hung workers, or an empty vector if tracking is not enabled. Threads that have terminated are included,
+    /// since the tracker does not observe thread termination.
+    ///
+    /// # Panics
+    /// If the activity tracker's mutex is poisoned.
+    pub fn idle_threads(&self, since: Duration) -> Vec<ThreadId> {
+        self.control.idle_threads(since)
+    }
+
+    /// Returns the latency statistics of the contributions made to `self` and its clones. See [`crate::overhead`].
+    /// Requires the **"overhead-stats"** feature.
+    #[cfg(feature = "overhead-stats")]
+    pub fn overhead_stats(&self) -> OverheadStats {
+        self.control.overhead_stats()
+    }
+
+    /// Attaches a predicate that is applied to each thread-local accumulation, together with the [`ThreadId`] of
+    /// the thread it came from, before it is combined with the accumulated value. Accumulations for which the
+    /// predicate returns `false` are discarded. The predicate applies to all clones of `self` created afterwards.
+    pub fn with_filter(
+        self,
+        filter: impl Fn(&U, ThreadId) -> bool + 'static + Send + Sync,
+    ) -> Self {
+        Self {
+            control: self.control.with_filter(filter),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Wraps the operation that combines thread-local accumulations with the accumulated value, as configured so far,
+    /// so that each invocation is logged with `logger`, which receives an [`OpRecord`] with the thread the
+    /// accumulation came from, a summary of it, and the time the operation took. The wrapper applies to all clones of
+    /// `self` created afterwards. Invocations by probes are not logged, see [`ControlG::instrumented`].
+    ///
+    /// `logger` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    pub fn instrumented(self, logger: impl Fn(&OpRecord) + 'static + Send + Sync) -> Self
+    where
+        U: Debug,
+    {
+        Self {
+            control: self.control.instrumented(logger),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Attaches an overflow hook: whenever, after a thread-local accumulation has been combined with the accumulated value,
+    /// the size of the accumulated value as computed by `size` exceeds `max_size`, the accumulated value is
+    /// passed to `spill` (e.g., to write it to disk) and replaced with the zero value of the accumulator. This keeps long-running
+    /// collections from growing in memory indefinitely. The hook applies to all clones of `self`
+    /// created afterwards.
+    ///
+    /// `spill` is called while `self`'s state lock is held, so it must not call methods of `self`.
+    pub fn with_spill(
+        self,
+        max_size: usize,
+        size: impl Fn(&U) -> usize + 'static + Send + Sync,
+        spill: impl Fn(U) + 'static + Send + Sync,
+    ) -> Self
+    where
+        U: 'static,
+    {
+        let acc_zero = self.acc_zero;
+        Self {
+            control: self.control.with_spill_zero(
+                max_size,
+                move |acc| size(acc.as_ref().expect("accumulator is never None")),
+                move |acc| spill(acc.expect("accumulator is never None")),
+                move || Some(acc_zero()),
+            ),
+            acc_zero,
+        }
+    }
+
+    /// Enables the tracking of the contribution activity of each thread, which can be retrieved with
+    /// [`Self::thread_activity`]. See [`crate::activity`].
+    pub fn with_activity_tracking(self) -> Self {
+        Self {
+            control: self.control.with_activity_tracking(),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Assigns each thread an alias in `aliases` when it registers with `self`. See
+    /// [`ControlG::with_thread_aliases`].
+    pub fn with_thread_aliases(self, aliases: ThreadAliases) -> Self {
+        Self {
+            control: self.control.with_thread_aliases(aliases),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Uses `tl_zero` instead of `acc_zero` (see [`Self::new`]) to produce the initial value of each thread's local
+    /// accumulated value, given the [`ThreadId`] of the thread, e.g., to pre-size or label it per worker. The values
+    /// produced by `tl_zero` are reduced into the accumulated value, so they must behave as zero values for
+    /// consistent aggregation results.
+    pub fn with_tl_init(self, tl_zero: impl Fn(ThreadId) -> U + 'static + Send + Sync) -> Self {
+        Self {
+            control: self.control.with_tl_init(tl_zero),
+            acc_zero: self.acc_zero,
+        }
+    }
+
+    /// Registers `touch`, which must access the thread-local variables that the aggregation operations depend on, so
+    /// that they outlive the thread-local variables linked to `self`. See [`ControlG::with_tl_dependencies`].
+    pub fn with_tl_dependencies(self, touch: impl Fn() + 'static + Send + Sync) -> Self {
+        Self {
+            control: self.control.with_tl_dependencies(touch),
+            acc_zero: self.acc_zero,
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -85,6 +316,57 @@ where
         let acc = self.control.take_acc(Some((self.acc_zero)()));
         acc.expect("accumulator is never None")
     }
+
+    /// Returns the accumulation of the thread-local values, as with [`Self::drain_tls`], and finalizes `self`:
+    /// further calls to [`Self::with_tl_acc_mut`] and the methods based on it, on `self` or its clones, panic, and
+    /// calls to [`Self::try_with_tl_acc_mut`] return [`AccessError::Sealed`].
+    pub fn finalize(&mut self) -> U {
+        let acc = self
+            .control
+            .seal_and_take_acc(Some((self.acc_zero)()), || Self::take_tls(&self.control));
+        acc.expect("accumulator is never None")
+    }
+
+    /// Returns whether `self` has been finalized with [`Self::finalize`].
+    pub fn is_sealed(&self) -> bool {
+        self.control.is_sealed()
+    }
+
+    /// Enables or disables contributions to `self` and its clones. While disabled, [`Self::aggregate_data`] and the
+    /// methods based on it do nothing, at the cost of a single relaxed atomic load, and [`Self::with_tl_acc_mut`]
+    /// invokes its closure on a scratch zero value. See [`ControlG::set_enabled`].
+    pub fn set_enabled(&self, enabled: bool) {
+        self.control.set_enabled(enabled)
+    }
+
+    /// Returns whether contributions to `self` are enabled. See [`Self::set_enabled`].
+    pub fn is_enabled(&self) -> bool {
+        self.control.is_enabled()
+    }
+
+    /// Wraps `self` in a [`FinishedControl`] whose [`drain_tls`](FinishedControl::drain_tls) method returns the
+    /// accumulation of the thread-local values post-processed with `finish`.
+    pub fn with_finisher<V, F>(self, finish: F) -> FinishedControl<Self, F>
+    where
+        F: Fn(U) -> V,
+    {
+        FinishedControl::new(self, finish)
+    }
+}
+
+impl<P, U, F, V> FinishedControl<ControlRestrG<P, U>, F>
+where
+    P: CoreParam<Acc = Option<U>, Dat = U> + CtrlStateParam + HldrParam,
+
+    ControlRestrG<P, U>: WithTakeTls<P, U>,
+    P::CtrlState: CtrlStateCore<P>,
+    F: Fn(U) -> V,
+{
+    /// Returns the accumulation of the thread-local values post-processed with the finisher. See
+    /// [`ControlRestrG::drain_tls`].
+    pub fn drain_tls(&mut self) -> V {
+        (self.finish)(self.control.drain_tls())
+    }
 }
 
 impl<P, U> ControlRestrG<P, U>
@@ -100,15 +382,236 @@ where
         self.control.with_data(f)
     }
 
-    /// Called from a thread to mutably access the thread's local accumulated value.
+    /// Called from a thread to mutably access the thread's local accumulated value. If `self` is disabled (see
+    /// [`Self::set_enabled`]), `f` is invoked on a scratch zero value that is then dropped. See
+    /// [`ControlG::with_data_mut`].
+    ///
+    /// # Panics
+    /// If `self` has been finalized with [`Self::finalize`], in which case [`Self::try_with_tl_acc_mut`] returns
+    /// [`AccessError::Sealed`] instead, or under the other conditions of [`ControlG::with_data_mut`].
     pub fn with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> V {
         self.control.with_data_mut(f)
     }
 
-    /// Called from a thread to aggregate data with aggregation operation `op`.
+    /// Called from a thread to mutably access the thread's local accumulated value, unless `self` has been
+    /// finalized or disabled. See [`ControlG::try_with_data_mut`].
+    ///
+    /// # Errors
+    /// Returns an error, without invoking `f`, under the conditions of [`ControlG::try_with_data_mut`], e.g.,
+    /// [`AccessError::Sealed`] if `self` has been finalized with [`Self::finalize`].
+    pub fn try_with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> Result<V, AccessError> {
+        self.control.try_with_data_mut(f)
+    }
+
+    /// Called from a thread to aggregate data with aggregation operation `op`. Does nothing if `self` is disabled
+    /// (see [`Self::set_enabled`]) or collection is compiled out with the `thread_local_collect_off` or
+    /// `thread_local_collect_release_off` option.
+    ///
+    /// # Panics
+    /// If `self` has been finalized with [`Self::finalize`], or under the other conditions of
+    /// [`Self::with_tl_acc_mut`]. The same applies to the methods based on this one, e.g., [`Self::add_data`].
     pub fn aggregate_data<T>(&self, data: T, op: impl FnOnce(T, &mut U, ThreadId)) {
+        if !self.control.is_enabled() {
+            return;
+        }
         self.with_tl_acc_mut(|acc| op(data, acc, thread::current().id()))
     }
+
+    /// Called from a thread to absorb data into the thread's local accumulated value with
+    /// [`Accumulator::absorb`].
+    pub fn absorb_data(&self, data: U::Dat)
+    where
+        U: Accumulator,
+    {
+        self.aggregate_data(data, |data, acc, tid| acc.absorb(data, tid))
+    }
+
+    /// Called from a thread to add data to the thread's local accumulated value with `+=`.
+    pub fn add_data<T>(&self, data: T)
+    where
+        U: AddAssign<T>,
+    {
+        self.aggregate_data(data, |data, acc, _| *acc += data)
+    }
+
+    /// Called from a thread to extend the thread's local accumulated value with a `(ThreadId, T)` pair, where the
+    /// [`ThreadId`] is the current thread's.
+    pub fn extend_data<T>(&self, data: T)
+    where
+        U: Extend<(ThreadId, T)>,
+    {
+        self.aggregate_data(data, |data, acc, tid| acc.extend([(tid, data)]))
+    }
+
+    /// Aggregates the current thread's local accumulated value, if any, with `self`'s accumulated value, replacing
+    /// it with the zero value. See [`ControlG::flush_tl`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn flush_tl(&self) {
+        self.control.flush_tl()
+    }
+
+    /// Links the current thread's thread-local variable to `self`, first aggregating its value with the accumulated
+    /// value of the control object it is linked to, if that is another one. See [`ControlG::relink`].
+    ///
+    /// # Panics
+    /// If the mutex of either control object is poisoned.
+    pub fn relink(&self) {
+        self.control.relink()
+    }
+
+    /// Registers `f` to be invoked when the thread-local variable of thread `tid` that is linked to `self` is dropped,
+    /// right after its value has been aggregated with `self`'s accumulated value. See [`ControlG::on_tl_dropped`].
+    pub fn on_tl_dropped(&self, tid: ThreadId, f: impl FnOnce() + Send + 'static) {
+        self.control.on_tl_dropped(tid, f)
+    }
+
+    /// Blocks until all thread-local variables linked to `self`, other than the current thread's, have been dropped.
+    /// See [`ControlG::wait_quiescent`].
+    ///
+    /// # Errors
+    /// Returns an error if `timeout` expires first.
+    pub fn wait_quiescent(&self, timeout: Duration) -> Result<(), QuiescenceTimeoutError> {
+        self.control.wait_quiescent(timeout)
+    }
+
+    /// Returns a future that resolves when all thread-local variables linked to `self`, other than the current
+    /// thread's, have been dropped. See [`ControlG::drained`].
+    #[cfg(feature = "async")]
+    pub fn drained(&self) -> DrainedFuture {
+        self.control.drained()
+    }
+
+    /// Runs `f` on the blocking thread pool of the current Tokio runtime, passing it a clone of `self`, and flushes
+    /// the thread's local accumulated value after `f` returns. See [`ControlG::spawn_blocking`].
+    ///
+    /// # Panics
+    /// If called outside of a Tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_blocking<R>(
+        &self,
+        f: impl FnOnce(&Self) -> R + Send + 'static,
+    ) -> tokio::task::JoinHandle<R>
+    where
+        Self: Send + 'static,
+        ControlG<P>: Send + 'static,
+        R: Send + 'static,
+    {
+        let control = self.clone();
+        self.control.spawn_blocking(move |_| f(&control))
+    }
+
+    /// Runs `f` on the thread pool of the [`blocking`](https://docs.rs/blocking) crate, used by the `smol` and
+    /// `async-std` runtimes, passing it a clone of `self`, and flushes the thread's local accumulated value after `f`
+    /// returns. See [`ControlG::unblock`].
+    #[cfg(feature = "blocking")]
+    pub fn unblock<R>(&self, f: impl FnOnce(&Self) -> R + Send + 'static) -> blocking::Task<R>
+    where
+        Self: Send + 'static,
+        ControlG<P>: Send + 'static,
+        R: Send + 'static,
+    {
+        let control = self.clone();
+        self.control.unblock(move |_| f(&control))
+    }
+
+    /// Runs `f` as a job on `pool`, a [`threadpool::ThreadPool`], passing it a clone of `self`, and flushes the
+    /// worker thread's local accumulated value after `f` returns. See [`ControlG::execute`].
+    #[cfg(feature = "threadpool")]
+    pub fn execute(&self, pool: &threadpool::ThreadPool, f: impl FnOnce(&Self) + Send + 'static)
+    where
+        Self: Send + 'static,
+        ControlG<P>: Send + 'static,
+    {
+        let control = self.clone();
+        self.control.execute(pool, move |_| f(&control));
+    }
+
+    /// Runs `f` as a job in `scope`, a [`scoped_threadpool::Scope`], passing it a clone of `self`, and flushes the
+    /// worker thread's local accumulated value after `f` returns. See [`ControlG::execute_scoped`].
+    #[cfg(feature = "scoped_threadpool")]
+    pub fn execute_scoped<'pool, 'scope>(
+        &self,
+        scope: &scoped_threadpool::Scope<'pool, 'scope>,
+        f: impl FnOnce(&Self) + Send + 'scope,
+    ) where
+        Self: Send + 'scope,
+        ControlG<P>: Send + 'scope,
+    {
+        let control = self.clone();
+        self.control.execute_scoped(scope, move |_| f(&control));
+    }
+
+    /// Returns a send-only handle to `self`.
+    pub fn sender(&self) -> ControlSender<Self> {
+        ControlSender(self.clone())
+    }
+}
+
+impl<P, U> ControlSender<ControlRestrG<P, U>>
+where
+    P: CoreParam<Acc = Option<U>, Dat = U> + CtrlStateParam + HldrParam,
+
+    P::CtrlState: CtrlStateCore<P>,
+    P: CtrlParam<Ctrl = ControlG<P>>,
+    P::Hldr: HldrLink<P> + HldrData<P>,
+{
+    /// Called from a thread to mutably access the thread's local accumulated value.
+    /// See [`ControlRestrG::with_tl_acc_mut`].
+    ///
+    /// # Panics
+    /// If the control object has been finalized, or under the other conditions of
+    /// [`ControlRestrG::with_tl_acc_mut`].
+    pub fn with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> V {
+        self.0.with_tl_acc_mut(f)
+    }
+
+    /// Called from a thread to mutably access the thread's local accumulated value, unless the control object has
+    /// been finalized or disabled. See [`ControlRestrG::try_with_tl_acc_mut`].
+    ///
+    /// # Errors
+    /// Returns an error, without invoking `f`, under the conditions of [`ControlRestrG::try_with_tl_acc_mut`].
+    pub fn try_with_tl_acc_mut<V>(&self, f: impl FnOnce(&mut U) -> V) -> Result<V, AccessError> {
+        self.0.try_with_tl_acc_mut(f)
+    }
+
+    /// Called from a thread to aggregate data with aggregation operation `op`.
+    /// See [`ControlRestrG::aggregate_data`].
+    ///
+    /// # Panics
+    /// If the control object has been finalized, or under the other conditions of
+    /// [`ControlRestrG::aggregate_data`]. The same applies to the methods based on this one.
+    pub fn aggregate_data<T>(&self, data: T, op: impl FnOnce(T, &mut U, ThreadId)) {
+        self.0.aggregate_data(data, op)
+    }
+
+    /// Called from a thread to absorb data into the thread's local accumulated value.
+    /// See [`ControlRestrG::absorb_data`].
+    pub fn absorb_data(&self, data: U::Dat)
+    where
+        U: Accumulator,
+    {
+        self.0.absorb_data(data)
+    }
+
+    /// Called from a thread to add data to the thread's local accumulated value.
+    /// See [`ControlRestrG::add_data`].
+    pub fn add_data<T>(&self, data: T)
+    where
+        U: AddAssign<T>,
+    {
+        self.0.add_data(data)
+    }
+
+    /// Called from a thread to extend the thread's local accumulated value with a `(ThreadId, T)` pair.
+    /// See [`ControlRestrG::extend_data`].
+    pub fn extend_data<T>(&self, data: T)
+    where
+        U: Extend<(ThreadId, T)>,
+    {
+        self.0.extend_data(data)
+    }
 }
 
 impl<P, U> Clone for ControlRestrG<P, U>
@@ -92,7 +92,7 @@ mod tests {
         acc
     }
 
-    thread_local! {static MY_TL: Holder<AccValue> = Holder::new();}
+    thread_local! {static MY_TL: Holder<AccValue> = const { Holder::new() };}
 
     const NTHREADS: usize = 5;
 
@@ -212,4 +212,70 @@ mod tests {
         let acc = control.drain_tls();
         assert_eq_and_println(&acc, &HashMap::new(), "empty accumulatore expected");
     }
+    #[test]
+    fn with_finisher() {
+        thread_local! {static VEC_TL: Holder<Vec<i32>> = const { Holder::new() };}
+
+        let mut control = Control::new(&VEC_TL, Vec::new, |mut acc1, acc2| {
+            acc1.extend(acc2);
+            acc1
+        })
+        .with_finisher(|mut acc: Vec<i32>| {
+            acc.sort();
+            acc
+        });
+
+        let hs = (0..5)
+            .rev()
+            .map(|i| {
+                let control = control.clone();
+                thread::spawn(move || control.aggregate_data(i, |data, acc, _| acc.push(data)))
+            })
+            .collect::<Vec<_>>();
+        hs.into_iter().for_each(|h| h.join().unwrap());
+        control.aggregate_data(5, |data, acc, _| acc.push(data));
+
+        assert_eq_and_println(
+            &control.drain_tls(),
+            &vec![0, 1, 2, 3, 4, 5],
+            "sorted drain_tls",
+        );
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn unblock() {
+        thread_local! {static U64_TL: Holder<u64> = const { Holder::new() };}
+
+        let mut control = Control::<u64>::new(&U64_TL, || 0, |acc1, acc2| acc1 + acc2);
+        let tasks = (1..=4)
+            .map(|i| control.unblock(move |control| control.add_data(i)))
+            .collect::<Vec<_>>();
+        futures_lite::future::block_on(async {
+            for task in tasks {
+                task.await;
+            }
+        });
+
+        // The threads of the pool are still alive.
+        assert_eq_and_println(&control.drain_tls(), &10, "unblock");
+    }
+
+    #[cfg(feature = "scoped_threadpool")]
+    #[test]
+    fn execute_scoped() {
+        thread_local! {static U64_TL: Holder<u64> = const { Holder::new() };}
+
+        let mut control = Control::<u64>::new(&U64_TL, || 0, |acc1, acc2| acc1 + acc2);
+        let mut pool = scoped_threadpool::Pool::new(2);
+        let values = vec![1, 2, 3, 4];
+        pool.scoped(|scope| {
+            for value in &values {
+                control.execute_scoped(scope, move |control| control.add_data(*value));
+            }
+        });
+
+        // The worker threads are still alive.
+        assert_eq_and_println(&control.drain_tls(), &10, "execute_scoped");
+    }
 }
@@ -2,6 +2,9 @@
 
 pub(crate) mod control_restr;
 
+#[cfg(feature = "joined")]
 pub mod joined;
+#[cfg(feature = "probed")]
 pub mod probed;
+#[cfg(feature = "joined")]
 pub mod simple_joined;
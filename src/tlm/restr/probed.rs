@@ -24,7 +24,9 @@
 pub use super::control_restr::ControlRestrG;
 
 use super::control_restr::WithTakeTls;
+use crate::handles::ControlReader;
 use crate::tlm::probed::{Control as ControlInner, Holder as HolderInner, Probed};
+use std::{ops::Sub, thread::ThreadId};
 
 /// Specialization of [`ControlRestrG`] for this module.
 /// Controls the collection and accumulation of thread-local values linked to this object.
@@ -42,15 +44,75 @@ where
     }
 }
 
+impl<U> Control<U>
+where
+    U: 'static,
+{
+    /// Enables the caching of the result of [`Self::probe_tls`], so that probing again when nothing has changed is
+    /// cheap. See [`crate::tlm::probed::Control::with_probe_cache`].
+    pub fn with_probe_cache(mut self) -> Self {
+        self.control = self.control.with_probe_cache();
+        self
+    }
+}
+
 impl<U> Control<U>
 where
     U: Clone,
 {
+    /// Returns a clone of the current accumulation of the thread-local values, without changing the state of `self`.
     pub fn probe_tls(&self) -> U {
         self.control
             .probe_tls()
             .expect("accumulator guaranteed to never be None")
     }
+
+    /// Returns the change of the current accumulation since the previous call of this method or
+    /// [`Self::probe_delta_with`]. See [`crate::tlm::probed::Control::probe_delta`].
+    pub fn probe_delta(&self) -> U
+    where
+        U: Sub<Output = U>,
+    {
+        self.probe_delta_with(|current, previous| current.clone() - previous.clone())
+    }
+
+    /// Same as [`Self::probe_delta`], with the change computed by `diff` from the current and previous
+    /// accumulations. See [`crate::tlm::probed::Control::probe_delta_with`].
+    pub fn probe_delta_with(&self, diff: impl FnOnce(&U, &U) -> U) -> U {
+        let acc_none = "accumulator guaranteed to never be None";
+        self.control
+            .probe_delta_with(|current, previous| {
+                Some(diff(
+                    current.as_ref().expect(acc_none),
+                    previous.as_ref().expect(acc_none),
+                ))
+            })
+            .expect(acc_none)
+    }
+
+    /// Returns whether thread `tid` is currently registered with `self`.
+    /// See [`crate::tlm::probed::Control::contains_thread`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn contains_thread(&self, tid: ThreadId) -> bool {
+        self.control.contains_thread(tid)
+    }
+
+    /// Returns a read-only handle to `self`.
+    pub fn reader(&self) -> ControlReader<Self> {
+        ControlReader(self.clone())
+    }
+}
+
+impl<U> ControlReader<Control<U>>
+where
+    U: Clone,
+{
+    /// Returns a clone of the current accumulation of the thread-local values. See [`Control::probe_tls`].
+    pub fn probe_tls(&self) -> U {
+        self.0.probe_tls()
+    }
 }
 
 /// Specialization of [`crate::tlm::probed::Holder`] for this module.
@@ -62,11 +124,17 @@ pub type Holder<U> = HolderInner<U, Option<U>>;
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::{Control, Holder};
-    use crate::dev_support::{assert_eq_and_println, ThreadGater};
+    use crate::{
+        accumulator::{Accumulator, Keyed, Summary},
+        dev_support::{assert_eq_and_println, ThreadGater},
+    };
     use std::{
         collections::HashMap,
         fmt::Debug,
-        sync::Mutex,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
         thread::{self, ThreadId},
     };
 
@@ -333,4 +401,248 @@ mod tests {
         let acc = control.drain_tls();
         assert_eq!(acc, HashMap::new(), "empty accumulator expected");
     }
+
+    thread_local! {static COUNT_TL: Holder<u64> = Holder::new();}
+
+    /// Threads link, contribute, and terminate concurrently with `probe_tls` calls. Every contribution must be
+    /// accumulated exactly once.
+    #[test]
+    fn registration_vs_probe_stress() {
+        const NWAVES: u64 = 20;
+        const NTHREADS: u64 = 8;
+        const NINCS: u64 = 200;
+
+        let mut control = Control::new(&COUNT_TL, || 0, |a, b| a + b);
+        let done = AtomicBool::new(false);
+
+        thread::scope(|s| {
+            let control = &control;
+            let done = &done;
+
+            let prober = s.spawn(move || {
+                let mut last_probe = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let probe = control.probe_tls();
+                    assert!(probe >= last_probe, "probes must be monotonic");
+                    assert!(
+                        probe <= NWAVES * NTHREADS * NINCS,
+                        "probe must not overcount"
+                    );
+                    last_probe = probe;
+                }
+            });
+
+            for _ in 0..NWAVES {
+                let hs = (0..NTHREADS)
+                    .map(|_| {
+                        s.spawn(move || {
+                            for _ in 0..NINCS {
+                                control.aggregate_data(1, |data, acc, _| *acc += data);
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                hs.into_iter().for_each(|h| h.join().unwrap());
+            }
+
+            done.store(true, Ordering::Relaxed);
+            prober.join().unwrap();
+        });
+
+        let acc = control.drain_tls();
+        assert_eq!(
+            acc,
+            NWAVES * NTHREADS * NINCS,
+            "every contribution counted once"
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Sum(u64);
+
+    impl Accumulator for Sum {
+        type Dat = u64;
+
+        fn zero() -> Self {
+            Sum(0)
+        }
+
+        fn absorb(&mut self, data: u64, _tid: ThreadId) {
+            self.0 += data;
+        }
+
+        fn merge(self, other: Self) -> Self {
+            Sum(self.0 + other.0)
+        }
+    }
+
+    thread_local! {static SUM_TL: Holder<Sum> = Holder::new();}
+
+    #[test]
+    fn from_accumulator() {
+        let mut control = Control::from_accumulator(&SUM_TL);
+
+        control.absorb_data(1);
+        thread::scope(|s| {
+            for i in 0..NTHREADS as u64 {
+                let sender = control.sender();
+                s.spawn(move || sender.absorb_data(i + 2));
+            }
+        });
+
+        let expected = Sum(1 + (2..NTHREADS as u64 + 2).sum::<u64>());
+        assert_eq_and_println(
+            &control.probe_tls(),
+            &expected,
+            "probe_tls from_accumulator",
+        );
+        assert_eq_and_println(
+            &control.drain_tls(),
+            &expected,
+            "drain_tls from_accumulator",
+        );
+        assert_eq_and_println(&control.drain_tls(), &Sum(0), "drain_tls after drain_tls");
+    }
+
+    #[test]
+    fn contribution_counts() {
+        let mut control = Control::<u64>::new_additive(&COUNT_TL).with_activity_tracking();
+
+        control.add_data(1);
+        let spawned_tid = thread::scope(|s| {
+            let sender = control.sender();
+            s.spawn(move || {
+                sender.add_data(2);
+                sender.aggregate_data(3, |data, acc, _| *acc += data);
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+
+        let expected = HashMap::from([(thread::current().id(), 1), (spawned_tid, 2)]);
+        assert_eq_and_println(
+            &control.contribution_counts(),
+            &expected,
+            "contribution_counts",
+        );
+        assert_eq_and_println(&control.drain_tls(), &6, "drain_tls");
+    }
+
+    thread_local! {static KEYED_TL: Holder<Keyed<&'static str, Sum>> = Holder::new();}
+
+    #[test]
+    fn keyed_accumulator() {
+        let mut control = Control::from_accumulator(&KEYED_TL);
+
+        control.absorb_data(("a", 1));
+        thread::scope(|s| {
+            for i in 0..NTHREADS as u64 {
+                let sender = control.sender();
+                s.spawn(move || {
+                    sender.absorb_data(("a", i));
+                    sender.absorb_data(("b", 10));
+                });
+            }
+        });
+
+        let expected = Keyed::from(HashMap::from([
+            ("a", Sum(1 + (0..NTHREADS as u64).sum::<u64>())),
+            ("b", Sum(10 * NTHREADS as u64)),
+        ]));
+        assert_eq_and_println(&control.probe_tls(), &expected, "probe_tls keyed");
+        assert_eq_and_println(&control.drain_tls(), &expected, "drain_tls keyed");
+    }
+
+    thread_local! {
+        static SUMMARY_TL: Holder<Summary<u32>> = Holder::new();
+        static THREAD_SUMMARY_TL: Holder<Keyed<ThreadId, Summary<u32>>> = Holder::new();
+    }
+
+    #[test]
+    fn summary_accumulator() {
+        let mut control = Control::from_accumulator(&SUMMARY_TL);
+        let mut thread_control = Control::from_accumulator(&THREAD_SUMMARY_TL);
+
+        let tids = thread::scope(|s| {
+            let hs = (0..NTHREADS as u32)
+                .map(|i| {
+                    let sender = control.sender();
+                    let thread_sender = thread_control.sender();
+                    s.spawn(move || {
+                        let tid = thread::current().id();
+                        for v in [i, i + 10] {
+                            sender.absorb_data(v);
+                            thread_sender.absorb_data((tid, v));
+                        }
+                        tid
+                    })
+                })
+                .collect::<Vec<_>>();
+            hs.into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let summary = control.drain_tls();
+        let n = NTHREADS as u32;
+        assert_eq_and_println(&summary.count(), &(2 * n as u64), "count");
+        assert_eq_and_println(&summary.sum(), &(n * (n - 1) + 10 * n), "sum");
+        assert_eq_and_println(&summary.min(), &Some(0), "min");
+        assert_eq_and_println(&summary.max(), &Some(n + 9), "max");
+        assert_eq_and_println(&summary.mean(), &Some((n - 1) as f64 / 2.0 + 5.0), "mean");
+        assert_eq_and_println(&Summary::<u32>::zero().mean(), &None, "mean of zero");
+
+        let thread_summaries = thread_control.drain_tls();
+        for (i, tid) in tids.iter().enumerate() {
+            let thread_summary = thread_summaries[tid];
+            assert_eq_and_println(
+                &(
+                    thread_summary.count(),
+                    thread_summary.min(),
+                    thread_summary.max(),
+                ),
+                &(2, Some(i as u32), Some(i as u32 + 10)),
+                "per-thread summary",
+            );
+        }
+    }
+
+    #[cfg(thread_local_collect_off)]
+    #[test]
+    fn collect_off() {
+        let mut control = Control::<u64>::new_additive(&COUNT_TL);
+        control.add_data(1);
+        control.aggregate_data(2, |data, acc, _| *acc += data);
+        let res = control.with_tl_acc_mut(|acc| {
+            *acc += 3;
+            *acc
+        });
+        assert_eq_and_println(&res, &3, "closure invoked on scratch zero value");
+        thread::scope(|s| {
+            let sender = control.sender();
+            s.spawn(move || sender.add_data(4));
+        });
+        assert_eq_and_println(&control.probe_tls(), &0, "probe");
+        assert_eq_and_println(&control.drain_tls(), &0, "contributions compiled out");
+    }
+
+    #[test]
+    fn finalize() {
+        use crate::tlm::{AccessError, SealedError};
+
+        let mut control = Control::<u64>::new_additive(&COUNT_TL);
+        control.add_data(1);
+        let sender = control.sender();
+        thread::scope(|s| {
+            s.spawn(|| sender.add_data(2));
+        });
+        assert_eq_and_println(&control.finalize(), &3, "finalized");
+        assert_eq_and_println(
+            &sender.try_with_tl_acc_mut(|acc| *acc += 4),
+            &Err(AccessError::Sealed(SealedError)),
+            "contribution after finalize",
+        );
+        assert_eq_and_println(&control.drain_tls(), &0, "no contribution after finalize");
+    }
 }
@@ -90,7 +90,7 @@ mod tests {
         acc
     }
 
-    thread_local! {static MY_TL: Holder<AccValue> = Holder::new();}
+    thread_local! {static MY_TL: Holder<AccValue> = const { Holder::new() };}
 
     const NTHREADS: usize = 5;
 
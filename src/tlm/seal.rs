@@ -0,0 +1,51 @@
+//! Sealed state of a control object, entered when the control object is finalized with its `finalize` method.
+
+use std::{
+    error::Error,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Indicates the illegal attempt to contribute data to a control object that has been finalized.
+#[derive(Debug, PartialEq)]
+pub struct SealedError;
+
+impl Display for SealedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Illegal contribution to a control object that has been finalized.")
+    }
+}
+
+impl Error for SealedError {}
+
+/// Sealed state shared by a control object and its clones.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Seal(Arc<AtomicBool>);
+
+impl Seal {
+    /// Puts `self` in the sealed state.
+    pub(crate) fn seal(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Returns whether `self` is in the sealed state.
+    #[inline]
+    pub(crate) fn is_sealed(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Checks that `self` is not in the sealed state.
+    ///
+    /// # Errors
+    /// Returns an error if `self` is in the sealed state.
+    #[inline]
+    pub(crate) fn check(&self) -> Result<(), SealedError> {
+        if self.is_sealed() {
+            return Err(SealedError);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,594 @@
+//! This module provides a restricted contribution path that is async-signal-safe, for use cases such as sampling
+//! profilers that record samples from `SIGPROF` handlers. It requires the **"signal"** feature.
+//! The following capabilities and constraints apply ...
+//! - Each participating thread registers with the [`Control`] object, outside of any signal handler, by calling
+//!   [`Control::register`], which preallocates a fixed-capacity ring buffer for the thread. This must be the first
+//!   access to the thread-local variable on the thread, and must happen before any signal handler that sends values
+//!   may run on it: [`Holder`] has drop glue, so the first access to the thread-local variable registers its
+//!   destructor with the runtime, which may allocate and is therefore not async-signal-safe.
+//! - Thereafter, values are sent with [`Control::send`] (or [`Holder::send`]), which is async-signal-safe: it does not
+//!   allocate or take locks, and only performs atomic operations on the thread's ring buffer. A value sent from a
+//!   thread that has not registered is dropped, as is a value sent when the thread's ring buffer is full, in which
+//!   case it is counted (see [`Control::dropped_count`]).
+//! - Values are of a type that implements [`SignalPayload`], i.e., that can be losslessly converted to and from a
+//!   `u64` (e.g., an integer type), so that they can be stored in atomic slots.
+//! - Each ring buffer has a single producer: within a thread, [`Control::send`] must not be called both from a
+//!   signal handler and from the code the signal handler may interrupt.
+//! - The ring buffers are drained, on a normal thread, with [`Control::drain_tls`], which aggregates the values
+//!   with the accumulated value. The ring buffers of terminated threads are released once drained.
+//!
+//! ## Usage pattern
+//!
+//! ```rust
+//! use std::{collections::HashMap, thread::ThreadId};
+//! use thread_local_collect::tlm::signal::{Control, Holder};
+//!
+//! thread_local! {
+//!     static SAMPLES_TL: Holder<u64> = const { Holder::new() };
+//! }
+//!
+//! // Counts samples (e.g., instruction addresses) per thread.
+//! fn op(sample: u64, acc: &mut HashMap<ThreadId, Vec<u64>>, tid: ThreadId) {
+//!     acc.entry(tid).or_default().push(sample);
+//! }
+//!
+//! let control = Control::new(&SAMPLES_TL, 1024, HashMap::new(), op);
+//!
+//! std::thread::scope(|s| {
+//!     s.spawn(|| {
+//!         control.register();
+//!         // This call could be made from a signal handler.
+//!         control.send(0x1234);
+//!     });
+//! });
+//!
+//! control.drain_tls();
+//! assert_eq!(control.with_acc(|acc| acc.values().flatten().count()), 1);
+//! ```
+
+use crate::{
+    accumulator::Tabular,
+    diag::{DiagEvent, DiagHook},
+    fork::ForkGuard,
+    gate::COLLECT_ENABLED,
+    handles::MappedGuard,
+    instr::OpInstr,
+    lock_order::{LockKind, Tracked},
+};
+use std::{
+    cell::OnceCell,
+    marker::PhantomData,
+    mem::replace,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, LocalKey, ThreadId},
+};
+
+//=================
+// Error consts
+
+const POISONED_CONTROL_MUTEX: &str = "poisoned control mutex";
+
+//=================
+// Payload
+
+/// Type of values that can be sent through the async-signal-safe path of this module, which stores them in
+/// atomic `u64` slots.
+pub trait SignalPayload: Copy {
+    /// Converts `self` to its `u64` representation.
+    fn to_bits(self) -> u64;
+
+    /// Converts the `u64` representation produced by [`Self::to_bits`] back to a value of this type.
+    fn from_bits(bits: u64) -> Self;
+}
+
+macro_rules! impl_signal_payload {
+    ($($t:ty),*) => {
+        $(
+            impl SignalPayload for $t {
+                fn to_bits(self) -> u64 {
+                    self as u64
+                }
+
+                fn from_bits(bits: u64) -> Self {
+                    bits as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_signal_payload!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl SignalPayload for bool {
+    fn to_bits(self) -> u64 {
+        self as u64
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        bits != 0
+    }
+}
+
+//=================
+// Ring buffer
+
+/// Fixed-capacity single-producer single-consumer ring buffer of `u64` values.
+#[derive(Debug)]
+struct Ring {
+    tid: ThreadId,
+    slots: Box<[AtomicU64]>,
+    /// Number of values written, only updated by the producer.
+    head: AtomicUsize,
+    /// Number of values read, only updated by the consumer.
+    tail: AtomicUsize,
+    /// Number of values dropped because the ring was full.
+    dropped: AtomicU64,
+}
+
+impl Ring {
+    fn new(tid: ThreadId, capacity: usize) -> Self {
+        Self {
+            tid,
+            slots: (0..capacity).map(|_| AtomicU64::new(0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Writes `value` to the ring, returning `false` if the ring is full. Async-signal-safe.
+    fn push(&self, value: u64) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head.wrapping_sub(tail) >= self.slots.len() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        self.slots[head % self.slots.len()].store(value, Ordering::Relaxed);
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Reads all values written to the ring since the last call, passing them to `f`.
+    fn pop_all(&self, mut f: impl FnMut(u64)) {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        let mut i = tail;
+        while i != head {
+            f(self.slots[i % self.slots.len()].load(Ordering::Relaxed));
+            i = i.wrapping_add(1);
+        }
+        self.tail.store(head, Ordering::Release);
+    }
+}
+
+//=================
+// Control
+
+/// State of [`Control`].
+#[derive(Debug)]
+struct SignalState<U> {
+    acc: U,
+    rings: Vec<Arc<Ring>>,
+    /// Values dropped by rings that have been released.
+    released_dropped: u64,
+}
+
+/// Guard object of a [`Control`]'s accumulated value. A lock is held during the guard's lifetime.
+#[derive(Debug)]
+struct AccGuard<'a, U>(Tracked<MutexGuard<'a, SignalState<U>>>);
+
+impl<U> Deref for AccGuard<'_, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.acc
+    }
+}
+
+/// Controls the collection and accumulation of values sent through the async-signal-safe path of this module.
+///
+/// `T` is the type of the values sent and `U` is the type of the accumulated value.
+pub struct Control<T, U>
+where
+    T: 'static,
+{
+    /// Reference to thread-local
+    tl: &'static LocalKey<Holder<T>>,
+    /// Keeps track of the ring buffers of registered threads and accumulated value.
+    state: Arc<Mutex<SignalState<U>>>,
+    /// Capacity of the ring buffer of each registered thread.
+    capacity: usize,
+    /// Operation that combines values sent from threads with accumulated value.
+    op: Arc<dyn Fn(T, &mut U, ThreadId) + Send + Sync>,
+    /// Optional diagnostics hook.
+    diag: Option<DiagHook>,
+    /// Process in which `self` was instantiated.
+    fork: ForkGuard,
+}
+
+impl<T, U> Clone for Control<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            tl: self.tl,
+            state: self.state.clone(),
+            capacity: self.capacity,
+            op: self.op.clone(),
+            diag: self.diag.clone(),
+            fork: self.fork,
+        }
+    }
+}
+
+impl<T, U: std::fmt::Debug> std::fmt::Debug for Control<T, U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("Control({:?})", self.state))
+    }
+}
+
+impl<T, U> Control<T, U>
+where
+    T: SignalPayload,
+{
+    /// Instantiates a [`Control`] object.
+    ///
+    /// - `tl` - reference to thread-local static.
+    /// - `capacity` - number of values that the ring buffer of each registered thread can hold between drains.
+    /// - `acc_base` - initial value for accumulation.
+    /// - `op` - operation that combines values sent from threads with accumulated value.
+    pub fn new(
+        tl: &'static LocalKey<Holder<T>>,
+        capacity: usize,
+        acc_base: U,
+        op: impl Fn(T, &mut U, ThreadId) + 'static + Send + Sync,
+    ) -> Self {
+        Self {
+            tl,
+            state: Arc::new(Mutex::new(SignalState {
+                acc: acc_base,
+                rings: Vec::new(),
+                released_dropped: 0,
+            })),
+            capacity,
+            op: Arc::new(op),
+            diag: None,
+            fork: ForkGuard::new(),
+        }
+    }
+
+    /// Attaches a diagnostics hook that receives structured events about `self`'s internal operations.
+    /// See [`crate::diag`].
+    pub fn with_diagnostics(mut self, hook: impl Fn(&DiagEvent) + 'static + Send + Sync) -> Self {
+        self.diag = Some(Arc::new(hook));
+        self
+    }
+
+    /// Acquires a lock on [`Control`]'s internal mutex.
+    ///
+    /// # Panics
+    /// - If `self`'s mutex is poisoned.
+    /// - With the **"fork-aware"** feature, if `self` is used in a child process created with `fork()`.
+    fn lock(&self) -> Tracked<MutexGuard<'_, SignalState<U>>> {
+        self.fork.check();
+        Tracked::acquire(LockKind::Control, self.state.as_ref(), || {
+            self.state.lock().expect(POISONED_CONTROL_MUTEX)
+        })
+    }
+
+    /// Registers the current thread with `self`, preallocating its ring buffer. Must be called from each
+    /// participating thread, outside of any signal handler, before values are sent from that thread. Has no effect
+    /// if the current thread is already registered.
+    ///
+    /// It must be the first access to the thread-local variable on the current thread, and must happen before any
+    /// signal handler that calls [`Self::send`] may run on it, since the first access registers the thread-local
+    /// variable's destructor, which is not async-signal-safe. Nothing enforces this.
+    ///
+    /// This method is NOT async-signal-safe.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn register(&self) {
+        self.tl.with(|h| {
+            if h.ring.get().is_some() {
+                return;
+            }
+            let ring = Arc::new(Ring::new(thread::current().id(), self.capacity));
+            self.lock().rings.push(ring.clone());
+            _ = h.ring.set(ring);
+        })
+    }
+
+    /// Sends `data` from the current thread to be aggregated by `self`. Returns `false`, in which case `data` is
+    /// dropped, if the current thread is not registered, its ring buffer is full, or collection is compiled out.
+    /// See [`Holder::send`].
+    ///
+    /// This method is async-signal-safe, provided that the current thread has registered with [`Self::register`]
+    /// before any other access to the thread-local variable (see [`Self::register`]). Otherwise, the first call
+    /// initializes the thread-local variable, which is not async-signal-safe.
+    pub fn send(&self, data: T) -> bool {
+        self.tl.try_with(|h| h.send(data)).unwrap_or(false)
+    }
+
+    /// Drains the ring buffers of the registered threads and aggregates the values with the accumulated value.
+    /// The ring buffers of threads that have terminated are released.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn drain_tls(&self) {
+        let mut instr = OpInstr::start("drain_tls", self.diag.as_ref());
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        let acc = &mut state.acc;
+        let released_dropped = &mut state.released_dropped;
+        state.rings.retain(|ring| {
+            // The holder of a terminated thread has released its reference to the ring. The count is read before
+            // popping, so that the values pushed by a thread that terminates during the pop are not lost.
+            let live = Arc::strong_count(ring) > 1;
+            ring.pop_all(|bits| {
+                instr.item(ring.tid);
+                (self.op)(T::from_bits(bits), acc, ring.tid)
+            });
+            if !live {
+                *released_dropped += ring.dropped.load(Ordering::Relaxed);
+            }
+            live
+        });
+    }
+
+    /// Returns the number of values dropped since `self` was instantiated because the ring buffer of the sending
+    /// thread was full.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn dropped_count(&self) -> u64 {
+        let state = self.lock();
+        state.released_dropped
+            + state
+                .rings
+                .iter()
+                .map(|ring| ring.dropped.load(Ordering::Relaxed))
+                .sum::<u64>()
+    }
+
+    /// Returns a guard object that dereferences to `self`'s accumulated value. A lock is held during the guard's
+    /// lifetime.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn acc(&self) -> impl Deref<Target = U> + '_ {
+        AccGuard(self.lock())
+    }
+
+    /// Returns a guard object that dereferences to the part of `self`'s accumulated value selected by `f`, e.g., a
+    /// field, as in `control.acc_map(|acc| &acc.latency_hist)`. A lock is held during the guard's lifetime.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn acc_map<'a, V: ?Sized + 'a>(
+        &'a self,
+        f: impl Fn(&U) -> &V + 'a,
+    ) -> impl Deref<Target = V> + 'a {
+        MappedGuard::new(self.acc(), f)
+    }
+
+    /// Provides access to `self`'s accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn with_acc<V>(&self, f: impl FnOnce(&U) -> V) -> V {
+        let acc = self.acc();
+        f(&acc)
+    }
+
+    /// Returns a clone of `self`'s accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn clone_acc(&self) -> U
+    where
+        U: Clone,
+    {
+        self.acc().clone()
+    }
+
+    /// Renders `self`'s accumulated value as a table with one `key  value` row per entry, e.g., for a debugging dump
+    /// of a per-thread map accumulator at shutdown. See [`Tabular`].
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn format_acc(&self) -> String
+    where
+        U: Tabular,
+    {
+        self.with_acc(Tabular::format_table)
+    }
+
+    /// Returns `self`'s accumulated value, using a value of the same type to replace
+    /// the existing accumulated value.
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn take_acc(&self, replacement: U) -> U {
+        replace(&mut self.lock().acc, replacement)
+    }
+}
+
+//=================
+// Holder
+
+/// Holds the ring buffer of a thread registered with a [`Control`]. A thread-local static of this type must only
+/// be linked to a single [`Control`] object (and its clones). Instances must be defined with a `const`
+/// initializer in a [`std::thread_local`] static, so that accessing them from a signal handler does not
+/// lazily initialize them:
+///
+/// ```rust
+/// # use thread_local_collect::tlm::signal::Holder;
+/// thread_local! {
+///     static MY_TL: Holder<u64> = const { Holder::new() };
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Holder<T> {
+    ring: OnceCell<Arc<Ring>>,
+    _t: PhantomData<fn(T)>,
+}
+
+impl<T> Holder<T> {
+    /// Instantiates a holder object.
+    pub const fn new() -> Self {
+        Self {
+            ring: OnceCell::new(),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T: SignalPayload> Holder<T> {
+    /// Sends `data` to be aggregated by the [`Control`] this holder's thread registered with. Returns `false`,
+    /// in which case `data` is dropped, if the thread is not registered, its ring buffer is full, or collection is
+    /// compiled out with the `thread_local_collect_off` or `thread_local_collect_release_off` option.
+    ///
+    /// This method is async-signal-safe, but accessing the thread-local variable through which it is called is only
+    /// async-signal-safe if the variable has already been accessed on the current thread, e.g., by
+    /// [`Control::register`], which must therefore be the first access to it.
+    pub fn send(&self, data: T) -> bool {
+        if !COLLECT_ENABLED {
+            return false;
+        }
+        match self.ring.get() {
+            Some(ring) => ring.push(data.to_bits()),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{Control, Holder};
+    use crate::dev_support::assert_eq_and_println;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Barrier, Mutex},
+        thread::{self, ThreadId},
+    };
+
+    thread_local! {
+        static MY_TL: Holder<u32> = const { Holder::new() };
+    }
+
+    fn op(data: u32, acc: &mut HashMap<ThreadId, u32>, tid: ThreadId) {
+        *acc.entry(tid).or_default() += data;
+    }
+
+    #[test]
+    fn send_and_drain() {
+        let control = Control::new(&MY_TL, 4, HashMap::new(), op);
+
+        assert!(!control.send(1), "send before register is dropped");
+
+        let tid = thread::scope(|s| {
+            s.spawn(|| {
+                control.register();
+                for i in 1..=6 {
+                    control.send(i);
+                }
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+        });
+
+        control.register();
+        assert!(control.send(100));
+
+        control.drain_tls();
+        let expected = HashMap::from([(tid, 1 + 2 + 3 + 4), (thread::current().id(), 100)]);
+        assert_eq_and_println(&control.clone_acc(), &expected, "accumulated after drain");
+        assert_eq_and_println(&control.dropped_count(), &2, "dropped when ring full");
+        assert_eq_and_println(
+            &control.lock().rings.len(),
+            &1,
+            "ring of terminated thread released",
+        );
+
+        // Ring has room again after draining.
+        for i in 0..4 {
+            assert!(control.send(i));
+        }
+        control.drain_tls();
+        assert_eq_and_println(
+            &control.acc()[&thread::current().id()],
+            &(100 + 6),
+            "accumulated after second drain",
+        );
+    }
+
+    #[test]
+    fn thread_terminates_during_drain() {
+        thread_local! {static U32_TL: Holder<u32> = const { Holder::new() };}
+
+        let barrier = Arc::new(Barrier::new(2));
+        let handle = Arc::new(Mutex::new(None::<thread::JoinHandle<()>>));
+
+        // When the first value is aggregated, lets the sending thread send another value and terminate.
+        let control = {
+            let barrier = barrier.clone();
+            let handle = handle.clone();
+            Control::new(&U32_TL, 4, 0, move |data, acc: &mut u32, _| {
+                *acc += data;
+                if data == 1 {
+                    barrier.wait();
+                    handle.lock().unwrap().take().unwrap().join().unwrap();
+                }
+            })
+        };
+
+        control.register();
+        let spawned = {
+            let control = control.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                control.register();
+                control.send(1);
+                barrier.wait();
+                barrier.wait();
+                control.send(2);
+            })
+        };
+        barrier.wait();
+        *handle.lock().unwrap() = Some(spawned);
+
+        control.drain_tls();
+        assert_eq_and_println(&control.clone_acc(), &1, "accumulated after first drain");
+        assert_eq_and_println(
+            &control.lock().rings.len(),
+            &2,
+            "ring of thread terminated during drain retained",
+        );
+
+        control.drain_tls();
+        assert_eq_and_println(&control.clone_acc(), &3, "accumulated after second drain");
+        assert_eq_and_println(
+            &control.lock().rings.len(),
+            &1,
+            "ring of terminated thread released",
+        );
+    }
+
+    #[cfg(thread_local_collect_off)]
+    #[test]
+    fn collect_off() {
+        thread_local! {static U32_TL: Holder<u32> = const { Holder::new() };}
+
+        let control = Control::new(&U32_TL, 4, 0, |data, acc: &mut u32, _| *acc += data);
+        control.register();
+        assert!(!control.send(1), "send compiled out");
+        control.drain_tls();
+        assert_eq_and_println(&control.take_acc(0), &0, "sends compiled out");
+    }
+}
@@ -94,11 +94,39 @@ impl<T, U> CtrlStateParam for P<T, U> {
 /// The data values are held in thread-locals of type [`Holder<T, U>`].
 pub type Control<T, U> = ControlG<P<T, U>>;
 
+impl<T, U> Control<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
+    /// Finalizes `self`, returning the accumulated value, which is replaced with `replacement`, and rejecting further
+    /// contributions to `self` or its clones: [`ControlG::with_data_mut`] panics and [`ControlG::try_with_data_mut`]
+    /// returns [`AccessError::Sealed`](crate::tlm::AccessError::Sealed).
+    ///
+    /// # Panics
+    /// If `self`'s mutex is poisoned.
+    pub fn finalize(&self, replacement: U) -> U {
+        self.seal_and_take_acc(replacement, || ())
+    }
+}
+
 /// Specialization of [`HolderG`] for this module.
 /// Holds thread-local data of type `T` and a smart pointer to a [`Control<T, U>`], enabling the linkage of
 /// the held data with the control object.
 pub type Holder<T, U> = HolderG<P<T, U>, DefaultDiscr>;
 
+impl<T, U> Holder<T, U>
+where
+    T: 'static,
+    U: 'static,
+{
+    /// Instantiates a holder object. As this is a `const fn`, the thread-local can be declared with a `const`
+    /// initializer, e.g., `static MY_TL: Holder<i32, i32> = const { Holder::new() };`.
+    pub const fn new() -> Self {
+        Self::with_data(RefCell::new(None))
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -118,7 +146,7 @@ mod tests {
     type AccumulatorMap = HashMap<ThreadId, HashMap<i32, Foo>>;
 
     thread_local! {
-        static MY_TL: Holder<Data, AccumulatorMap> = Holder::new();
+        static MY_TL: Holder<Data, AccumulatorMap> = const { Holder::new() };
     }
 
     fn insert_tl_entry(k: i32, v: Foo, control: &Control<Data, AccumulatorMap>) {
@@ -0,0 +1,61 @@
+//! Runtime switch of a control object, toggled with its `set_enabled` method.
+
+use crate::gate::COLLECT_ENABLED;
+use std::{
+    error::Error,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Indicates the attempt to contribute data to a control object that has been disabled with its `set_enabled`
+/// method.
+#[derive(Debug, PartialEq)]
+pub struct DisabledError;
+
+impl Display for DisabledError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Contribution to a control object that has been disabled.")
+    }
+}
+
+impl Error for DisabledError {}
+
+/// Enabled state shared by a control object and its clones. Reads and writes are relaxed, as the state does not
+/// guard any other memory: contributions made concurrently with a toggle may or may not be accepted.
+#[derive(Debug, Clone)]
+pub(crate) struct Switch(Arc<AtomicBool>);
+
+impl Default for Switch {
+    fn default() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+}
+
+impl Switch {
+    /// Puts `self` in the enabled state if `enabled` is `true`, or in the disabled state otherwise.
+    pub(crate) fn set(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns whether `self` is in the enabled state. Always `false` if collection is compiled out, see
+    /// [`crate::gate`], in which case the check has no code generation cost.
+    #[inline]
+    pub(crate) fn is_enabled(&self) -> bool {
+        COLLECT_ENABLED && self.0.load(Ordering::Relaxed)
+    }
+
+    /// Checks that `self` is in the enabled state.
+    ///
+    /// # Errors
+    /// Returns an error if `self` is in the disabled state.
+    #[inline]
+    pub(crate) fn check(&self) -> Result<(), DisabledError> {
+        if !self.is_enabled() {
+            return Err(DisabledError);
+        }
+        Ok(())
+    }
+}
@@ -1,22 +1,32 @@
-//! Defines a struct containing a map from thread IDs to thread-local values for use as
+//! Defines a struct containing a map from linked thread-local variables to thread-local values for use as
 //! the sub-state of [`ControlG`]'s state.
 
 use std::{collections::HashMap, thread::ThreadId};
 
-use super::common::*;
+use super::{common::*, probed::Reregistration};
 
 //=================
 // Control sub-state struct with a thread map.
 
-/// Struct containing a map from thread IDs to thread-local values, used by the specialization of
+/// Struct containing a map from linked thread-local variables (see [`TlKey`]) to thread-local values, used by the specialization of
 /// [`CtrlStateG`] for module [`super::probed`].
 /// Also used as the `D` discriminant parameter for [`CtrlStateG`] impls.
 #[derive(Debug)]
 pub struct TmapD<P>
 where
-    P: NodeParam,
+    P: NodeParam + CoreParam,
 {
-    pub(crate) tmap: HashMap<ThreadId, P::Node>,
+    pub(crate) tmap: HashMap<TlKey, P::Node>,
+    /// Number of thread-local variables registered since instantiation.
+    pub(crate) registered: usize,
+    /// Current epoch number, see [`super::probed::Epoch`].
+    pub(crate) epoch: u64,
+    /// Policy applied when a node is registered under the key of an already registered node.
+    pub(crate) reregistration: Reregistration,
+    /// Result of the last probe, returned by the next probe if nothing has changed in the meantime.
+    pub(crate) probe_cache: Option<P::Acc>,
+    /// Result of the last delta probe, relative to which the next delta probe is computed.
+    pub(crate) delta_base: Option<P::Acc>,
 }
 
 impl<P> New<Self> for TmapD<P>
@@ -30,6 +40,11 @@ where
     fn new(_: ()) -> Self {
         Self {
             tmap: HashMap::new(),
+            registered: 0,
+            epoch: 0,
+            reregistration: Reregistration::default(),
+            probe_cache: None,
+            delta_base: None,
         }
     }
 }
@@ -44,11 +59,11 @@ where
         &mut self,
         op: &(dyn Fn(P::Dat, &mut P::Acc, ThreadId) + Send + Sync),
         data: P::Dat,
-        tid: ThreadId,
+        key: TlKey,
     ) {
-        self.s.tmap.remove(&tid);
+        self.s.tmap.remove(&key);
         let acc = self.acc_mut_priv();
-        op(data, acc, tid);
+        op(data, acc, key.tid);
     }
 }
 
@@ -58,7 +73,12 @@ where
 
     P: CoreParam + SubStateParam<SubState = TmapD<P>>,
 {
-    fn register_node(&mut self, node: <P as NodeParam>::Node, tid: ThreadId) {
-        self.s.tmap.insert(tid, node);
+    fn register_node(&mut self, node: <P as NodeParam>::Node, key: TlKey) -> bool {
+        if self.s.reregistration == Reregistration::Reject && self.s.tmap.contains_key(&key) {
+            return false;
+        }
+        self.s.tmap.insert(key, node);
+        self.s.registered += 1;
+        true
     }
 }
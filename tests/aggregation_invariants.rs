@@ -0,0 +1,136 @@
+//! Property-based tests asserting that, for arbitrary contributions from multiple threads interleaved with arbitrary
+//! takes and probes on the main thread, the values collected with an associative and commutative operation add up to
+//! the sequential fold of all contributions.
+
+#![cfg(all(feature = "probed", feature = "channeled", feature = "tlcr"))]
+
+use proptest::prelude::*;
+use std::thread;
+use thread_local_collect::{tlcr, tlm};
+
+/// Operation performed on the main thread while worker threads contribute.
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Probe,
+    Take,
+    Yield,
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![Just(Op::Probe), Just(Op::Take), Just(Op::Yield)]
+}
+
+/// Contributions of each worker thread.
+fn workers_strategy() -> impl Strategy<Value = Vec<Vec<u64>>> {
+    prop::collection::vec(prop::collection::vec(0..1000u64, 0..20), 1..5)
+}
+
+fn total(workers: &[Vec<u64>]) -> u64 {
+    workers.iter().flatten().sum()
+}
+
+thread_local! {
+    static PROBED_TL: tlm::probed::Holder<u64, u64> = tlm::probed::Holder::new();
+    static CHANNELED_TL: tlm::channeled::Holder<u64> = const { tlm::channeled::Holder::new() };
+}
+
+proptest! {
+    #[test]
+    fn tlm_probed_fold(workers in workers_strategy(), ops in prop::collection::vec(op_strategy(), 0..10)) {
+        let control = tlm::probed::Control::<u64, u64>::new_additive(&PROBED_TL);
+        let expected = total(&workers);
+
+        let mut taken = 0;
+        thread::scope(|s| {
+            for values in &workers {
+                let control = &control;
+                s.spawn(move || {
+                    for &v in values {
+                        control.with_data_mut(|data| *data += v);
+                    }
+                });
+            }
+            for op in &ops {
+                match op {
+                    Op::Probe => {
+                        let probed = control.probe_tls();
+                        prop_assert!(taken + probed <= expected, "probe exceeds contributions");
+                    }
+                    Op::Take => {
+                        control.take_tls();
+                        taken += control.take_acc(0);
+                    }
+                    Op::Yield => thread::yield_now(),
+                }
+            }
+            Ok(())
+        })?;
+
+        control.take_tls();
+        taken += control.take_acc(0);
+        prop_assert_eq!(taken, expected);
+    }
+
+    #[test]
+    fn tlm_channeled_fold(workers in workers_strategy(), ops in prop::collection::vec(op_strategy(), 0..10)) {
+        let control = tlm::channeled::Control::<u64, u64>::new_additive(&CHANNELED_TL);
+        let expected = total(&workers);
+
+        let mut taken = 0;
+        thread::scope(|s| {
+            for values in &workers {
+                let sender = control.sender();
+                s.spawn(move || {
+                    for &v in values {
+                        sender.send_data(v);
+                    }
+                });
+            }
+            for op in &ops {
+                match op {
+                    // `tlm::channeled` has no probe; the accumulated value is read without draining instead.
+                    Op::Probe => {
+                        let acc = control.clone_acc();
+                        prop_assert!(taken + acc <= expected, "accumulated value exceeds contributions");
+                    }
+                    Op::Take => taken += control.drain_and_reset(0),
+                    Op::Yield => thread::yield_now(),
+                }
+            }
+            Ok(())
+        })?;
+
+        taken += control.drain_and_reset(0);
+        prop_assert_eq!(taken, expected);
+    }
+
+    #[test]
+    fn tlcr_probed_fold(workers in workers_strategy(), ops in prop::collection::vec(op_strategy(), 0..10)) {
+        let mut control = tlcr::probed::Control::<u64>::new_additive();
+        let expected = total(&workers);
+
+        thread::scope(|s| {
+            for values in &workers {
+                let sender = control.sender();
+                s.spawn(move || {
+                    for &v in values {
+                        sender.add_data(v);
+                    }
+                });
+            }
+            // `tlcr` control objects can only be drained after all contributing threads have terminated.
+            for op in &ops {
+                match op {
+                    Op::Probe | Op::Take => {
+                        prop_assert!(control.probe_tls() <= expected, "probe exceeds contributions");
+                    }
+                    Op::Yield => thread::yield_now(),
+                }
+            }
+            Ok(())
+        })?;
+
+        prop_assert_eq!(control.probe_tls(), expected);
+        prop_assert_eq!(control.drain_tls().unwrap(), expected);
+    }
+}
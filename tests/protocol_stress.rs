@@ -0,0 +1,149 @@
+//! Stress tests for the `tlm::channeled` start/stop/drain protocol and the `tlm::probed` register/take race.
+//!
+//! Each case is generated by `proptest`, in which the main thread and the participating threads interleave protocol
+//! operations with pauses of random lengths. This exercises interleavings that the fixed sleeps of the unit tests
+//! do not reach. The interleaving itself is decided by the OS scheduler, so the tests are not deterministic: a
+//! failing case may not fail again when re-run, and failing cases are therefore not persisted.
+//!
+//! Deterministic schedulers, e.g., `shuttle` or `loom`, are not used, as they require the library to be built on
+//! their own synchronization primitives and thread-local keys, whereas the public API takes
+//! [`std::thread::LocalKey`].
+
+#![cfg(all(feature = "probed", feature = "channeled"))]
+
+use proptest::prelude::*;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use thread_local_collect::tlm;
+
+/// Yields the current thread `n` times.
+fn pause(n: u8) {
+    for _ in 0..n {
+        thread::yield_now();
+    }
+}
+
+/// Contributions of each participating thread, each preceded by a pause.
+fn workers_strategy() -> impl Strategy<Value = Vec<Vec<(u8, u64)>>> {
+    prop::collection::vec(prop::collection::vec((0..20u8, 0..1000u64), 0..10), 1..5)
+}
+
+fn total(workers: &[Vec<(u8, u64)>]) -> u64 {
+    workers.iter().flatten().map(|(_, v)| v).sum()
+}
+
+/// Operation of the `tlm::channeled` protocol performed by the main thread.
+#[derive(Debug, Clone, Copy)]
+enum ChanneledOp {
+    Start,
+    Stop,
+    Drain,
+    Pause(u8),
+}
+
+fn channeled_op_strategy() -> impl Strategy<Value = ChanneledOp> {
+    prop_oneof![
+        Just(ChanneledOp::Start),
+        Just(ChanneledOp::Stop),
+        Just(ChanneledOp::Drain),
+        (0..20u8).prop_map(ChanneledOp::Pause),
+    ]
+}
+
+/// Operation of the `tlm::probed` protocol performed by the main thread.
+#[derive(Debug, Clone, Copy)]
+enum ProbedOp {
+    Take,
+    Pause(u8),
+}
+
+fn probed_op_strategy() -> impl Strategy<Value = ProbedOp> {
+    prop_oneof![Just(ProbedOp::Take), (0..20u8).prop_map(ProbedOp::Pause),]
+}
+
+thread_local! {
+    static CHANNELED_TL: tlm::channeled::Holder<u64> = const { tlm::channeled::Holder::new() };
+    static PROBED_TL: tlm::probed::Holder<u64, u64> = tlm::probed::Holder::new();
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn channeled_start_stop_drain(
+        workers in workers_strategy(),
+        ops in prop::collection::vec(channeled_op_strategy(), 0..20),
+    ) {
+        let control = tlm::channeled::Control::<u64, u64>::new_additive(&CHANNELED_TL);
+
+        thread::scope(|s| {
+            for contributions in &workers {
+                let sender = control.sender();
+                s.spawn(move || {
+                    for &(n, v) in contributions {
+                        pause(n);
+                        sender.send_data(v);
+                    }
+                });
+            }
+            for op in &ops {
+                match *op {
+                    // Fails if a background receiver thread is active, which is part of the protocol.
+                    ChanneledOp::Start => _ = control.start_receiving_tls(),
+                    ChanneledOp::Stop => control.stop_receiving_tls(),
+                    ChanneledOp::Drain => control.drain_tls(),
+                    ChanneledOp::Pause(n) => pause(n),
+                }
+            }
+        });
+        control.drain_tls();
+        prop_assert_eq!(control.clone_acc(), total(&workers));
+
+        // After a drain, any background receiver thread terminates, so a new one can be started.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while control.start_receiving_tls().is_err() {
+            prop_assert!(Instant::now() < deadline, "background receiver not terminated by drain_tls");
+            thread::yield_now();
+        }
+        control.drain_tls();
+    }
+
+    #[test]
+    fn probed_register_take(
+        workers in workers_strategy(),
+        ops in prop::collection::vec(probed_op_strategy(), 0..20),
+    ) {
+        let control = tlm::probed::Control::<u64, u64>::new_additive(&PROBED_TL);
+
+        let mut taken = 0;
+        thread::scope(|s| {
+            for contributions in &workers {
+                let control = &control;
+                // Each thread registers on its first contribution and its value is collected when it terminates.
+                s.spawn(move || {
+                    for &(n, v) in contributions {
+                        pause(n);
+                        control.with_data_mut(|data| *data += v);
+                    }
+                });
+            }
+            for op in &ops {
+                match *op {
+                    ProbedOp::Take => {
+                        control.take_tls();
+                        taken += control.take_acc(0);
+                    }
+                    ProbedOp::Pause(n) => pause(n),
+                }
+            }
+        });
+        control.take_tls();
+        taken += control.take_acc(0);
+        prop_assert_eq!(taken, total(&workers));
+    }
+}